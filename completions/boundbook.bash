@@ -37,9 +37,15 @@ _boundbook() {
             boundbook,info)
                 cmd="boundbook__info"
                 ;;
+            boundbook,petrify)
+                cmd="boundbook__petrify"
+                ;;
             boundbook,read)
                 cmd="boundbook__read"
                 ;;
+            boundbook,repair)
+                cmd="boundbook__repair"
+                ;;
             boundbook,verify)
                 cmd="boundbook__verify"
                 ;;
@@ -64,9 +70,15 @@ _boundbook() {
             boundbook__help,info)
                 cmd="boundbook__help__info"
                 ;;
+            boundbook__help,petrify)
+                cmd="boundbook__help__petrify"
+                ;;
             boundbook__help,read)
                 cmd="boundbook__help__read"
                 ;;
+            boundbook__help,repair)
+                cmd="boundbook__help__repair"
+                ;;
             boundbook__help,verify)
                 cmd="boundbook__help__verify"
                 ;;
@@ -77,7 +89,7 @@ _boundbook() {
 
     case "${cmd}" in
         boundbook)
-            opts="-h -V --help --version docs create info verify extract from-cbz read complete help"
+            opts="-h -V --help --version docs create info verify extract from-cbz read petrify repair complete help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 1 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -105,7 +117,7 @@ _boundbook() {
             return 0
             ;;
         boundbook__create)
-            opts="-o -O -S -s -m -a -r -v -d -h --output --order --sections --section --meta --alignment --ream-size --variable-ream-size --auto-detect-sections --help <INPUTS>..."
+            opts="-o -O -S -s -m -a -r -v -d -h --output --order --sections --section --meta --alignment --ream-size --variable-ream-size --auto-detect-sections --warn-media-mismatch --force --thumbnail --thumbnail-from --thumbnail-size --help <INPUTS>..."
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -167,6 +179,14 @@ _boundbook() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --thumbnail-from)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --thumbnail-size)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -189,7 +209,7 @@ _boundbook() {
             return 0
             ;;
         boundbook__extract)
-            opts="-o -h --output --section --until --range --help <INPUT>"
+            opts="-o -h --output --section --until --range --thumbnail --help <INPUT>"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -223,7 +243,7 @@ _boundbook() {
             return 0
             ;;
         boundbook__from__cbz)
-            opts="-o -m -k -d -h --output --meta --keep-temp --directory-mode --help <INPUT>"
+            opts="-o -m -k -d -h --output --meta --keep-temp --directory-mode --force --help <INPUT>"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -253,7 +273,7 @@ _boundbook() {
             return 0
             ;;
         boundbook__help)
-            opts="docs create info verify extract from-cbz read complete help"
+            opts="docs create info verify extract from-cbz read petrify repair complete help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -364,6 +384,20 @@ _boundbook() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        boundbook__help__petrify)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         boundbook__help__read)
             opts=""
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
@@ -378,6 +412,20 @@ _boundbook() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        boundbook__help__repair)
+            opts=""
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         boundbook__help__verify)
             opts=""
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
@@ -406,6 +454,20 @@ _boundbook() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        boundbook__petrify)
+            opts="-h --help <INPUT>"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         boundbook__read)
             opts="-P -W -H -f -g -l -i -m -h --prerender --max-width --max-height --max-cols --max-rows --filter --enable-gif-animation --gif-speed --gif-loop --gif-interpolate --interpolation-method --sidebar-width --slideshow-delay --help <INPUT>"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
@@ -480,8 +542,30 @@ _boundbook() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        boundbook__repair)
+            opts="-o -h --output --force --help <INPUT>"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                --output)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -o)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         boundbook__verify)
-            opts="-h --index-only --asset --help <INPUT>"
+            opts="-h --index-only --asset --deep --json --help <INPUT>"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -29,10 +29,12 @@ complete -c boundbook -n "__fish_boundbook_needs_command" -s V -l version -d 'Pr
 complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "docs" -d 'Print help'
 complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "create" -d 'Create a BBF file from images'
 complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "info" -d 'Display BBF file information'
-complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "verify" -d 'Verify BBF file integrity'
+complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "verify" -d 'Verify BBF file integrity (exits with status 2 if corruption is found)'
 complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "extract" -d 'Extract pages from a BBF file'
 complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "from-cbz" -d 'Convert CBZ archive to BBF format'
 complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "read" -d 'Read a BBF file in the terminal'
+complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "petrify" -d 'Mark a BBF file as permanently read-only'
+complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "repair" -d 'Salvage a damaged BBF file into a new one'
 complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "complete" -d 'Generate CLI completions'
 complete -c boundbook -n "__fish_boundbook_needs_command" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
 complete -c boundbook -n "__fish_boundbook_using_subcommand docs" -s h -l help -d 'Print help'
@@ -43,22 +45,31 @@ complete -c boundbook -n "__fish_boundbook_using_subcommand create" -s s -l sect
 complete -c boundbook -n "__fish_boundbook_using_subcommand create" -s m -l meta -d 'Add metadata (format: Key:Value[:Parent])' -r
 complete -c boundbook -n "__fish_boundbook_using_subcommand create" -s a -l alignment -d 'Byte alignment exponent (default: 12 = 4096 bytes)' -r
 complete -c boundbook -n "__fish_boundbook_using_subcommand create" -s r -l ream-size -d 'Ream size exponent (default: 16 = 65536 bytes)' -r
+complete -c boundbook -n "__fish_boundbook_using_subcommand create" -l thumbnail-from -d 'Embed a thumbnail generated from this image instead of the first page' -r -F
+complete -c boundbook -n "__fish_boundbook_using_subcommand create" -l thumbnail-size -d 'Maximum thumbnail width/height in pixels' -r
 complete -c boundbook -n "__fish_boundbook_using_subcommand create" -s v -l variable-ream-size -d 'Enable variable ream size for smaller files'
 complete -c boundbook -n "__fish_boundbook_using_subcommand create" -s d -l auto-detect-sections -d 'Auto-detect subdirectories with images and create sections from directory names'
+complete -c boundbook -n "__fish_boundbook_using_subcommand create" -l warn-media-mismatch -d 'Warn when an image\'s file extension doesn\'t match its contents'
+complete -c boundbook -n "__fish_boundbook_using_subcommand create" -l force -d 'Overwrite the output file even if it is petrified'
+complete -c boundbook -n "__fish_boundbook_using_subcommand create" -l thumbnail -d 'Embed a thumbnail generated from the first page'
 complete -c boundbook -n "__fish_boundbook_using_subcommand create" -s h -l help -d 'Print help'
 complete -c boundbook -n "__fish_boundbook_using_subcommand info" -s h -l help -d 'Print help'
 complete -c boundbook -n "__fish_boundbook_using_subcommand verify" -l asset -d 'Verify a specific asset by index' -r
 complete -c boundbook -n "__fish_boundbook_using_subcommand verify" -l index-only -d 'Verify only the index hash (faster)'
+complete -c boundbook -n "__fish_boundbook_using_subcommand verify" -l deep -d 'Also check all cross-references and layout invariants, reporting every problem found'
+complete -c boundbook -n "__fish_boundbook_using_subcommand verify" -l json -d 'Print the full report as JSON instead of text'
 complete -c boundbook -n "__fish_boundbook_using_subcommand verify" -s h -l help -d 'Print help'
 complete -c boundbook -n "__fish_boundbook_using_subcommand extract" -s o -l output -d 'Output directory for extracted pages' -r -F
 complete -c boundbook -n "__fish_boundbook_using_subcommand extract" -l section -d 'Extract only pages from a specific section' -r
 complete -c boundbook -n "__fish_boundbook_using_subcommand extract" -l until -d 'Stop extraction when reaching a section matching this string' -r
 complete -c boundbook -n "__fish_boundbook_using_subcommand extract" -l range -d 'Extract a specific page range (e.g., 1-10 or 5)' -r
+complete -c boundbook -n "__fish_boundbook_using_subcommand extract" -l thumbnail -d 'Extract only the embedded thumbnail'
 complete -c boundbook -n "__fish_boundbook_using_subcommand extract" -s h -l help -d 'Print help'
 complete -c boundbook -n "__fish_boundbook_using_subcommand from-cbz" -s o -l output -d 'Output BBF file' -r -F
 complete -c boundbook -n "__fish_boundbook_using_subcommand from-cbz" -s m -l meta -d 'Add metadata (format: Key:Value[:Parent])' -r
 complete -c boundbook -n "__fish_boundbook_using_subcommand from-cbz" -s k -l keep-temp -d 'Keep temporary files for debugging'
 complete -c boundbook -n "__fish_boundbook_using_subcommand from-cbz" -s d -l directory-mode -d 'Process directory of CBZ files as chapters'
+complete -c boundbook -n "__fish_boundbook_using_subcommand from-cbz" -l force -d 'Overwrite the output file even if it is petrified'
 complete -c boundbook -n "__fish_boundbook_using_subcommand from-cbz" -s h -l help -d 'Print help'
 complete -c boundbook -n "__fish_boundbook_using_subcommand read" -s W -l max-width -d 'Maximum width in pixels (aspect ratio preserved)' -r
 complete -c boundbook -n "__fish_boundbook_using_subcommand read" -s H -l max-height -d 'Maximum height in pixels (aspect ratio preserved)' -r
@@ -86,13 +97,19 @@ complete -c boundbook -n "__fish_boundbook_using_subcommand read" -s P -l preren
 complete -c boundbook -n "__fish_boundbook_using_subcommand read" -s g -l enable-gif-animation -d 'Enable GIF animation playback'
 complete -c boundbook -n "__fish_boundbook_using_subcommand read" -s l -l gif-loop -d 'Loop GIFs infinitely'
 complete -c boundbook -n "__fish_boundbook_using_subcommand read" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c boundbook -n "__fish_boundbook_using_subcommand petrify" -s h -l help -d 'Print help'
+complete -c boundbook -n "__fish_boundbook_using_subcommand repair" -s o -l output -d 'Output path for the repaired BBF file' -r -F
+complete -c boundbook -n "__fish_boundbook_using_subcommand repair" -l force -d 'Overwrite the output file even if it is petrified'
+complete -c boundbook -n "__fish_boundbook_using_subcommand repair" -s h -l help -d 'Print help'
 complete -c boundbook -n "__fish_boundbook_using_subcommand complete" -s h -l help -d 'Print help'
-complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read complete help" -f -a "docs" -d 'Print help'
-complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read complete help" -f -a "create" -d 'Create a BBF file from images'
-complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read complete help" -f -a "info" -d 'Display BBF file information'
-complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read complete help" -f -a "verify" -d 'Verify BBF file integrity'
-complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read complete help" -f -a "extract" -d 'Extract pages from a BBF file'
-complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read complete help" -f -a "from-cbz" -d 'Convert CBZ archive to BBF format'
-complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read complete help" -f -a "read" -d 'Read a BBF file in the terminal'
-complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read complete help" -f -a "complete" -d 'Generate CLI completions'
-complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read complete help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "docs" -d 'Print help'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "create" -d 'Create a BBF file from images'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "info" -d 'Display BBF file information'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "verify" -d 'Verify BBF file integrity (exits with status 2 if corruption is found)'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "extract" -d 'Extract pages from a BBF file'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "from-cbz" -d 'Convert CBZ archive to BBF format'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "read" -d 'Read a BBF file in the terminal'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "petrify" -d 'Mark a BBF file as permanently read-only'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "repair" -d 'Salvage a damaged BBF file into a new one'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "complete" -d 'Generate CLI completions'
+complete -c boundbook -n "__fish_boundbook_using_subcommand help; and not __fish_seen_subcommand_from docs create info verify extract from-cbz read petrify repair complete help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
@@ -28,10 +28,12 @@ Register-ArgumentCompleter -Native -CommandName 'boundbook' -ScriptBlock {
             [CompletionResult]::new('docs', 'docs', [CompletionResultType]::ParameterValue, 'Print help')
             [CompletionResult]::new('create', 'create', [CompletionResultType]::ParameterValue, 'Create a BBF file from images')
             [CompletionResult]::new('info', 'info', [CompletionResultType]::ParameterValue, 'Display BBF file information')
-            [CompletionResult]::new('verify', 'verify', [CompletionResultType]::ParameterValue, 'Verify BBF file integrity')
+            [CompletionResult]::new('verify', 'verify', [CompletionResultType]::ParameterValue, 'Verify BBF file integrity (exits with status 2 if corruption is found)')
             [CompletionResult]::new('extract', 'extract', [CompletionResultType]::ParameterValue, 'Extract pages from a BBF file')
             [CompletionResult]::new('from-cbz', 'from-cbz', [CompletionResultType]::ParameterValue, 'Convert CBZ archive to BBF format')
             [CompletionResult]::new('read', 'read', [CompletionResultType]::ParameterValue, 'Read a BBF file in the terminal')
+            [CompletionResult]::new('petrify', 'petrify', [CompletionResultType]::ParameterValue, 'Mark a BBF file as permanently read-only')
+            [CompletionResult]::new('repair', 'repair', [CompletionResultType]::ParameterValue, 'Salvage a damaged BBF file into a new one')
             [CompletionResult]::new('complete', 'complete', [CompletionResultType]::ParameterValue, 'Generate CLI completions')
             [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
             break
@@ -56,10 +58,15 @@ Register-ArgumentCompleter -Native -CommandName 'boundbook' -ScriptBlock {
             [CompletionResult]::new('--alignment', '--alignment', [CompletionResultType]::ParameterName, 'Byte alignment exponent (default: 12 = 4096 bytes)')
             [CompletionResult]::new('-r', '-r', [CompletionResultType]::ParameterName, 'Ream size exponent (default: 16 = 65536 bytes)')
             [CompletionResult]::new('--ream-size', '--ream-size', [CompletionResultType]::ParameterName, 'Ream size exponent (default: 16 = 65536 bytes)')
+            [CompletionResult]::new('--thumbnail-from', '--thumbnail-from', [CompletionResultType]::ParameterName, 'Embed a thumbnail generated from this image instead of the first page')
+            [CompletionResult]::new('--thumbnail-size', '--thumbnail-size', [CompletionResultType]::ParameterName, 'Maximum thumbnail width/height in pixels')
             [CompletionResult]::new('-v', '-v', [CompletionResultType]::ParameterName, 'Enable variable ream size for smaller files')
             [CompletionResult]::new('--variable-ream-size', '--variable-ream-size', [CompletionResultType]::ParameterName, 'Enable variable ream size for smaller files')
             [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Auto-detect subdirectories with images and create sections from directory names')
             [CompletionResult]::new('--auto-detect-sections', '--auto-detect-sections', [CompletionResultType]::ParameterName, 'Auto-detect subdirectories with images and create sections from directory names')
+            [CompletionResult]::new('--warn-media-mismatch', '--warn-media-mismatch', [CompletionResultType]::ParameterName, 'Warn when an image''s file extension doesn''t match its contents')
+            [CompletionResult]::new('--force', '--force', [CompletionResultType]::ParameterName, 'Overwrite the output file even if it is petrified')
+            [CompletionResult]::new('--thumbnail', '--thumbnail', [CompletionResultType]::ParameterName, 'Embed a thumbnail generated from the first page')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
@@ -72,6 +79,8 @@ Register-ArgumentCompleter -Native -CommandName 'boundbook' -ScriptBlock {
         'boundbook;verify' {
             [CompletionResult]::new('--asset', '--asset', [CompletionResultType]::ParameterName, 'Verify a specific asset by index')
             [CompletionResult]::new('--index-only', '--index-only', [CompletionResultType]::ParameterName, 'Verify only the index hash (faster)')
+            [CompletionResult]::new('--deep', '--deep', [CompletionResultType]::ParameterName, 'Also check all cross-references and layout invariants, reporting every problem found')
+            [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Print the full report as JSON instead of text')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
@@ -82,6 +91,7 @@ Register-ArgumentCompleter -Native -CommandName 'boundbook' -ScriptBlock {
             [CompletionResult]::new('--section', '--section', [CompletionResultType]::ParameterName, 'Extract only pages from a specific section')
             [CompletionResult]::new('--until', '--until', [CompletionResultType]::ParameterName, 'Stop extraction when reaching a section matching this string')
             [CompletionResult]::new('--range', '--range', [CompletionResultType]::ParameterName, 'Extract a specific page range (e.g., 1-10 or 5)')
+            [CompletionResult]::new('--thumbnail', '--thumbnail', [CompletionResultType]::ParameterName, 'Extract only the embedded thumbnail')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
@@ -95,6 +105,7 @@ Register-ArgumentCompleter -Native -CommandName 'boundbook' -ScriptBlock {
             [CompletionResult]::new('--keep-temp', '--keep-temp', [CompletionResultType]::ParameterName, 'Keep temporary files for debugging')
             [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Process directory of CBZ files as chapters')
             [CompletionResult]::new('--directory-mode', '--directory-mode', [CompletionResultType]::ParameterName, 'Process directory of CBZ files as chapters')
+            [CompletionResult]::new('--force', '--force', [CompletionResultType]::ParameterName, 'Overwrite the output file even if it is petrified')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
             break
@@ -125,6 +136,19 @@ Register-ArgumentCompleter -Native -CommandName 'boundbook' -ScriptBlock {
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
             break
         }
+        'boundbook;petrify' {
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
+        'boundbook;repair' {
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Output path for the repaired BBF file')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Output path for the repaired BBF file')
+            [CompletionResult]::new('--force', '--force', [CompletionResultType]::ParameterName, 'Overwrite the output file even if it is petrified')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
         'boundbook;complete' {
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
@@ -134,10 +158,12 @@ Register-ArgumentCompleter -Native -CommandName 'boundbook' -ScriptBlock {
             [CompletionResult]::new('docs', 'docs', [CompletionResultType]::ParameterValue, 'Print help')
             [CompletionResult]::new('create', 'create', [CompletionResultType]::ParameterValue, 'Create a BBF file from images')
             [CompletionResult]::new('info', 'info', [CompletionResultType]::ParameterValue, 'Display BBF file information')
-            [CompletionResult]::new('verify', 'verify', [CompletionResultType]::ParameterValue, 'Verify BBF file integrity')
+            [CompletionResult]::new('verify', 'verify', [CompletionResultType]::ParameterValue, 'Verify BBF file integrity (exits with status 2 if corruption is found)')
             [CompletionResult]::new('extract', 'extract', [CompletionResultType]::ParameterValue, 'Extract pages from a BBF file')
             [CompletionResult]::new('from-cbz', 'from-cbz', [CompletionResultType]::ParameterValue, 'Convert CBZ archive to BBF format')
             [CompletionResult]::new('read', 'read', [CompletionResultType]::ParameterValue, 'Read a BBF file in the terminal')
+            [CompletionResult]::new('petrify', 'petrify', [CompletionResultType]::ParameterValue, 'Mark a BBF file as permanently read-only')
+            [CompletionResult]::new('repair', 'repair', [CompletionResultType]::ParameterValue, 'Salvage a damaged BBF file into a new one')
             [CompletionResult]::new('complete', 'complete', [CompletionResultType]::ParameterValue, 'Generate CLI completions')
             [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
             break
@@ -163,6 +189,12 @@ Register-ArgumentCompleter -Native -CommandName 'boundbook' -ScriptBlock {
         'boundbook;help;read' {
             break
         }
+        'boundbook;help;petrify' {
+            break
+        }
+        'boundbook;help;repair' {
+            break
+        }
         'boundbook;help;complete' {
             break
         }
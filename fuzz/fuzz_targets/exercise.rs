@@ -0,0 +1,51 @@
+//! drives every read path of an opened reader
+
+use boundbook::{BbfReader, Storage, Thumbnail};
+
+/// calls every table accessor and follows every offset the tables contain
+///
+/// errors are expected for malformed input; the target only fails on panics, hangs, or memory
+/// errors.
+pub fn exercise<S: Storage>(reader: &BbfReader<S>) {
+    let _ = reader.header();
+    let _ = reader.footer();
+    let _ = reader.page_count();
+    let _ = reader.asset_count();
+    let _ = reader.is_petrified();
+
+    if let Ok(assets) = reader.assets() {
+        for asset in assets {
+            let _ = reader.get_asset_data(asset);
+        }
+    }
+
+    if let Ok(pages) = reader.pages() {
+        for page in pages {
+            let _ = { page.asset_index };
+        }
+    }
+
+    if let Ok(sections) = reader.sections() {
+        for section in sections {
+            let _ = reader.get_string(section.section_title_offset);
+            let _ = reader.get_string(section.section_parent_offset);
+        }
+    }
+
+    if let Ok(metadata) = reader.metadata() {
+        for meta in metadata {
+            let _ = reader.get_string(meta.key_offset);
+            let _ = reader.get_string(meta.value_offset);
+            let _ = reader.get_string(meta.parent_offset);
+        }
+    }
+
+    let _ = reader.expansions();
+    let _ = reader.expansion_records::<Thumbnail>();
+    let _ = reader.thumbnail();
+
+    let _ = reader.validate_deep();
+    let _ = reader.integrity_report();
+    let _ = reader.verify_integrity();
+    let _ = reader.verify_asset(0);
+}
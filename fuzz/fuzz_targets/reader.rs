@@ -0,0 +1,19 @@
+//! fuzzes the zero-copy reader
+//!
+//! parses the input the same way [`boundbook::BbfReader::open`] parses a mem-mapped file, then
+//! walks every table and string. seed it with the conformance corpus:
+//!
+//! ```sh
+//! cargo +nightly fuzz run reader ../fixtures/conformance
+//! ```
+#![no_main]
+
+mod exercise;
+
+use {boundbook::BbfReader, libfuzzer_sys::fuzz_target};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(reader) = BbfReader::from_storage(data) {
+        exercise::exercise(&reader);
+    }
+});
@@ -0,0 +1,34 @@
+//! fuzzes the hardened reader over positioned reads
+//!
+//! a hardened open bounds-checks every index table up front, so once it succeeds no table
+//! accessor may fail. seed it with the conformance corpus:
+//!
+//! ```sh
+//! cargo +nightly fuzz run reader_hardened ../fixtures/conformance
+//! ```
+#![no_main]
+
+mod exercise;
+
+use {
+    boundbook::{BbfReader, SeekStorage},
+    libfuzzer_sys::fuzz_target,
+    std::io::Cursor,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(storage) = SeekStorage::new(Cursor::new(data)) else {
+        return;
+    };
+
+    if let Ok(reader) = BbfReader::from_storage_hardened(storage) {
+        assert!(reader.is_hardened());
+        assert!(reader.assets().is_ok());
+        assert!(reader.pages().is_ok());
+        assert!(reader.sections().is_ok());
+        assert!(reader.metadata().is_ok());
+        assert!(reader.expansions().is_ok());
+
+        exercise::exercise(&reader);
+    }
+});
@@ -18,6 +18,50 @@ pub const BBF_PETRIFICATION_FLAG: u32 = 0x00000001;
 /// when set, small assets use reduced alignment for better space efficiency
 pub const BBF_VARIABLE_REAM_SIZE_FLAG: u32 = 0x00000002;
 
+/// asset flag marking a thumbnail
+///
+/// when set in asset flags, indicates the asset is a low-resolution cover preview rather than
+/// page content
+pub const BBF_ASSET_THUMBNAIL_FLAG: u32 = 0x00000001;
+
+/// page flag marking the front cover
+///
+/// when set in page flags, indicates the page is the book's front cover
+pub const BBF_PAGE_FRONT_COVER_FLAG: u32 = 0x00000001;
+
+/// page flag marking the back cover
+///
+/// when set in page flags, indicates the page is the book's back cover
+pub const BBF_PAGE_BACK_COVER_FLAG: u32 = 0x00000002;
+
+/// page flag marking a double-page spread
+///
+/// when set in page flags, indicates the image spans two facing pages and should be shown whole
+pub const BBF_PAGE_DOUBLE_SPREAD_FLAG: u32 = 0x00000004;
+
+/// expansion flag marking a record as required
+///
+/// readers that don't recognize the kind of a required expansion record must refuse the file
+/// instead of skipping the record
+pub const BBF_EXPANSION_REQUIRED_FLAG: u32 = 0x00000001;
+
+/// expansion record kinds understood by this implementation
+///
+/// records of any other kind are skipped unless they carry [`BBF_EXPANSION_REQUIRED_FLAG`].
+/// kinds defined outside this crate are passed to [`crate::BbfReader::open_with_expansions`]
+/// instead.
+pub const REGISTERED_EXPANSION_KINDS: &[u64] = &[THUMBNAIL_EXPANSION_KIND];
+
+/// expansion kind of the thumbnail record
+///
+/// points at the asset holding the book's cover preview
+pub const THUMBNAIL_EXPANSION_KIND: u64 = 0x00000001;
+
+/// default thumbnail bounding box in pixels
+///
+/// generated thumbnails fit within a square of this size
+pub const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+
 /// default guard alignment exponent
 ///
 /// alignment = 1 << 12 = 4096 bytes (4kb)
@@ -91,6 +135,96 @@ impl MediaType {
         }
     }
 
+    /// determines media type by sniffing the leading magic bytes of the data
+    ///
+    /// recognizes png, jpeg, gif, webp, avif (isobmff `ftyp` box with an avif brand), jpeg xl
+    /// (bare codestream and container), bmp, and tiff signatures.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - the raw image data (only the first few dozen bytes are inspected)
+    ///
+    /// # Returns
+    ///
+    /// the detected `MediaType` variant, or `Unknown` if no signature matches
+    pub fn from_bytes(data: &[u8]) -> Self {
+        const JXL_CONTAINER: &[u8] = b"\x00\x00\x00\x0CJXL \x0D\x0A\x87\x0A";
+
+        match data {
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Self::Png,
+            [0xFF, 0xD8, 0xFF, ..] => Self::Jpg,
+            [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Self::Gif,
+            [b'R', b'I', b'F', b'F', ..] if data.get(8..12) == Some(b"WEBP") => Self::Webp,
+            [0xFF, 0x0A, ..] => Self::Jxl,
+            _ if data.starts_with(JXL_CONTAINER) => Self::Jxl,
+            [b'B', b'M', ..] if Self::has_bmp_headers(data) => Self::Bmp,
+            [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => Self::Tiff,
+            [_, _, _, _, b'f', b't', b'y', b'p', ..] if Self::has_avif_brand(data) => Self::Avif,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// checks whether data starting with `BM` carries plausible bmp headers
+    ///
+    /// two bytes are a weak signature (plenty of text starts with "BM"), so the size of the info
+    /// header must be one of the sizes bitmap writers use, and the file size field must leave room
+    /// for both headers.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - the raw data, starting with the bitmap file header
+    ///
+    /// # Returns
+    ///
+    /// true if the info header size is known and the file size covers both headers
+    fn has_bmp_headers(data: &[u8]) -> bool {
+        const FILE_HEADER_LEN: u32 = 14;
+        const INFO_HEADER_LENS: [u32; 8] = [12, 16, 40, 52, 56, 64, 108, 124];
+
+        let (Some(file_size), Some(info_size)) = (
+            data.get(2..6)
+                .and_then(|b| b.try_into().ok())
+                .map(u32::from_le_bytes),
+            data.get(14..18)
+                .and_then(|b| b.try_into().ok())
+                .map(u32::from_le_bytes),
+        ) else {
+            return false;
+        };
+
+        INFO_HEADER_LENS.contains(&info_size)
+            && FILE_HEADER_LEN
+                .checked_add(info_size)
+                .is_some_and(|headers| file_size >= headers)
+    }
+
+    /// checks whether an isobmff `ftyp` box lists an avif brand
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - the raw data, starting with the `ftyp` box
+    ///
+    /// # Returns
+    ///
+    /// true if the major brand or any compatible brand is `avif` or `avis`
+    fn has_avif_brand(data: &[u8]) -> bool {
+        let Some(size_bytes) = data.first_chunk::<4>() else {
+            return false;
+        };
+
+        let box_size = (u32::from_be_bytes(*size_bytes) as usize).min(data.len());
+        let Some(brands) = data.get(8..box_size) else {
+            return false;
+        };
+
+        brands
+            .chunks_exact(4)
+            .enumerate()
+            // the second word of the box body is the minor version, not a brand
+            .filter(|(i, _)| *i != 1)
+            .any(|(_, brand)| brand == b"avif" || brand == b"avis")
+    }
+
     /// converts media type to standard file extension
     ///
     /// # Returns
@@ -109,6 +243,25 @@ impl MediaType {
             Self::Unknown => ".png",
         }
     }
+
+    /// converts media type to its IANA media type (MIME type)
+    ///
+    /// # Returns
+    ///
+    /// the media type string, or `application/octet-stream` for unknown data
+    pub const fn as_mime_type(self) -> &'static str {
+        match self {
+            Self::Avif => "image/avif",
+            Self::Png => "image/png",
+            Self::Jpg => "image/jpeg",
+            Self::Webp => "image/webp",
+            Self::Jxl => "image/jxl",
+            Self::Bmp => "image/bmp",
+            Self::Gif => "image/gif",
+            Self::Tiff => "image/tiff",
+            Self::Unknown => "application/octet-stream",
+        }
+    }
 }
 
 impl From<u8> for MediaType {
@@ -226,20 +379,72 @@ pub struct Metadata {
     pub reserved: [u8; 8],
 }
 
-/// expansion entry for future format extensions
+/// expansion entry for typed format extensions
 ///
-/// reserved structure for future format additions without breaking compatibility
+/// each entry is a versioned record identified by a kind id, which lets new features be added
+/// without breaking older BBF3 readers. `exp_reserved[0]` holds the kind id, `exp_reserved[1]`
+/// the record version, and `exp_reserved[2..10]` the kind-specific payload.
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy)]
 pub struct Expansion {
-    /// reserved fields for future data
+    /// kind id, record version, and 8 words of kind-specific payload
     pub exp_reserved: [u64; 10],
-    /// flags for expansion features
+    /// flags for expansion features (see [`BBF_EXPANSION_REQUIRED_FLAG`])
     pub flags: u32,
     /// reserved for future use, must be all zeros
     pub reserved: [u8; 44],
 }
 
+impl Expansion {
+    /// makes a new expansion entry
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - the kind id identifying the record type
+    /// * `version` - the version of the record's payload layout
+    /// * `payload` - the kind-specific payload
+    /// * `flags` - expansion flags
+    ///
+    /// # Returns
+    ///
+    /// the expansion entry with all reserved bytes zeroed
+    pub const fn new(kind: u64, version: u64, payload: [u64; 8], flags: u32) -> Self {
+        let [p0, p1, p2, p3, p4, p5, p6, p7] = payload;
+
+        Self {
+            exp_reserved: [kind, version, p0, p1, p2, p3, p4, p5, p6, p7],
+            flags,
+            reserved: [0; 44],
+        }
+    }
+
+    /// returns the kind id of the record
+    pub const fn kind(&self) -> u64 {
+        self.exp_reserved[0]
+    }
+
+    /// returns the payload layout version of the record
+    pub const fn version(&self) -> u64 {
+        self.exp_reserved[1]
+    }
+
+    /// returns the kind-specific payload of the record
+    pub const fn payload(&self) -> [u64; 8] {
+        let [_, _, p0, p1, p2, p3, p4, p5, p6, p7] = self.exp_reserved;
+        [p0, p1, p2, p3, p4, p5, p6, p7]
+    }
+
+    /// checks whether readers must understand this record's kind
+    pub const fn is_required(&self) -> bool {
+        self.flags & BBF_EXPANSION_REQUIRED_FLAG != 0
+    }
+
+    /// checks whether this record's kind is in [`REGISTERED_EXPANSION_KINDS`] (built-in kinds only)
+    pub fn is_registered(&self) -> bool {
+        REGISTERED_EXPANSION_KINDS.contains(&self.kind())
+    }
+}
+
 /// BBF file footer
 ///
 /// contains all table offsets, counts, and an integrity hash of the index region. located at the
@@ -325,6 +530,54 @@ mod tests {
         assert!(MediaType::from_extension("   ") == MediaType::Unknown);
     }
 
+    #[test]
+    fn test_mediatype_from_bytes_all_known_signatures() {
+        let avif = b"\x00\x00\x00\x1Cftypavif\x00\x00\x00\x00avifmif1miaf";
+        let avif_compat = b"\x00\x00\x00\x18ftypmif1\x00\x00\x00\x00avifmiaf";
+        let bmp = b"BM\x3A\x00\x00\x00\x00\x00\x00\x00\x36\x00\x00\x00\x28\x00\x00\x00";
+
+        assert!(MediaType::from_bytes(b"\x89PNG\r\n\x1a\nrest") == MediaType::Png);
+        assert!(MediaType::from_bytes(&[0xFF, 0xD8, 0xFF, 0xE0]) == MediaType::Jpg);
+        assert!(MediaType::from_bytes(b"GIF89a....") == MediaType::Gif);
+        assert!(MediaType::from_bytes(b"GIF87a....") == MediaType::Gif);
+        assert!(MediaType::from_bytes(b"RIFF\x10\x00\x00\x00WEBPVP8 ") == MediaType::Webp);
+        assert!(MediaType::from_bytes(avif) == MediaType::Avif);
+        assert!(MediaType::from_bytes(avif_compat) == MediaType::Avif);
+        assert!(MediaType::from_bytes(&[0xFF, 0x0A, 0x00]) == MediaType::Jxl);
+        assert!(
+            MediaType::from_bytes(b"\x00\x00\x00\x0CJXL \x0D\x0A\x87\x0A\x00") == MediaType::Jxl
+        );
+        assert!(MediaType::from_bytes(bmp) == MediaType::Bmp);
+        assert!(MediaType::from_bytes(b"II*\x00\x08\x00") == MediaType::Tiff);
+        assert!(MediaType::from_bytes(b"MM\x00*\x00\x08") == MediaType::Tiff);
+    }
+
+    #[test]
+    fn test_mediatype_from_bytes_unknown_returns_unknown() {
+        let heic = b"\x00\x00\x00\x18ftypheic\x00\x00\x00\x00mif1heic";
+        // "avif" in the minor version slot is not a brand
+        let minor_only = b"\x00\x00\x00\x10ftypheicavif";
+
+        assert!(MediaType::from_bytes(&[]) == MediaType::Unknown);
+        assert!(MediaType::from_bytes(&[0u8; 64]) == MediaType::Unknown);
+        assert!(MediaType::from_bytes(b"BM") == MediaType::Unknown);
+        assert!(
+            MediaType::from_bytes(&[b'B', b'M', 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0])
+                == MediaType::Unknown
+        );
+        // text that happens to start with "BM"
+        assert!(MediaType::from_bytes(b"BMW owners manual, page 1") == MediaType::Unknown);
+        // a known info header size, but a file size too small to hold it
+        assert!(
+            MediaType::from_bytes(
+                b"BM\x10\x00\x00\x00\x00\x00\x00\x00\x36\x00\x00\x00\x28\x00\x00\x00"
+            ) == MediaType::Unknown
+        );
+        assert!(MediaType::from_bytes(b"RIFF\x10\x00\x00\x00WAVE") == MediaType::Unknown);
+        assert!(MediaType::from_bytes(heic) == MediaType::Unknown);
+        assert!(MediaType::from_bytes(minor_only) == MediaType::Unknown);
+    }
+
     #[test]
     fn test_mediatype_as_extension_roundtrip() {
         let variants = [
@@ -347,6 +600,13 @@ mod tests {
         assert!(MediaType::Unknown.as_extension() == ".png");
     }
 
+    #[test]
+    fn test_mediatype_as_mime_type() {
+        assert!(MediaType::Jpg.as_mime_type() == "image/jpeg");
+        assert!(MediaType::Webp.as_mime_type() == "image/webp");
+        assert!(MediaType::Unknown.as_mime_type() == "application/octet-stream");
+    }
+
     #[test]
     fn test_mediatype_from_u8_all_known_values() {
         assert!(MediaType::from(0x01u8) == MediaType::Avif);
@@ -395,6 +655,23 @@ mod tests {
         assert!(std::mem::size_of::<BbfFooter>() == 256);
     }
 
+    #[test]
+    fn test_expansion_field_layout() {
+        let payload = [10, 11, 12, 13, 14, 15, 16, 17];
+        let exp = Expansion::new(0xABCD, 2, payload, BBF_EXPANSION_REQUIRED_FLAG);
+        let words = exp.exp_reserved;
+
+        assert!(words[0] == 0xABCD);
+        assert!(words[1] == 2);
+        assert!(words[2..] == payload);
+        assert!(exp.kind() == 0xABCD);
+        assert!(exp.version() == 2);
+        assert!(exp.payload() == payload);
+        assert!(exp.is_required());
+        assert!(!exp.is_registered());
+        assert!(exp.reserved == [0; 44]);
+    }
+
     #[test]
     fn test_format_constants_values() {
         assert!(MAGIC == b"BBF3");
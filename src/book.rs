@@ -0,0 +1,377 @@
+use {
+    crate::{
+        BbfError, BbfReader, MetadataMap, Result, SectionTree, SectionView, Storage, types::*,
+    },
+    std::{borrow::Cow, path::Path},
+};
+
+/// a high-level view of a BBF file
+///
+/// wraps a [`BbfReader`] and resolves its raw tables once up front: page entries are paired with
+/// their assets, sections are linked into a [`SectionTree`], and metadata entries are decoded
+/// into owned strings. after a successful [`Book::new`] every accessor is
+/// infallible except fetching asset data.
+pub struct Book<S: Storage = memmap2::Mmap> {
+    /// the reader the book was resolved from
+    reader: BbfReader<S>,
+    /// copy of the asset table
+    assets: Vec<AssetEntry>,
+    /// asset index and flags of every page, each index checked against the asset table
+    pages: Vec<(usize, u32)>,
+    /// sections linked into a tree
+    sections: SectionTree,
+    /// metadata entries in table order
+    metadata: Vec<MetadataEntry>,
+}
+
+impl<S: Storage> std::fmt::Debug for Book<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Book")
+            .field("pages", &self.pages.len())
+            .field("assets", &self.assets.len())
+            .field("sections", &self.sections)
+            .field("metadata", &self.metadata)
+            .finish_non_exhaustive()
+    }
+}
+
+/// a page of a [`Book`]
+pub struct Page<'a, S: Storage = memmap2::Mmap> {
+    /// zero-based page number
+    pub index: u64,
+    /// the asset displayed on this page
+    pub asset: &'a AssetEntry,
+    /// the media type stored for the asset
+    pub media_type: MediaType,
+    /// page-specific flags (see [`crate::format::BBF_PAGE_FRONT_COVER_FLAG`])
+    pub flags: u32,
+    /// the reader the asset data is read from
+    reader: &'a BbfReader<S>,
+}
+
+impl<'a, S: Storage> Page<'a, S> {
+    /// reads the page's image data
+    ///
+    /// # Returns
+    ///
+    /// the asset data, borrowed when the storage lends slices
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the asset lies outside the file or reading it fails
+    pub fn data(&self) -> Result<Cow<'a, [u8]>> {
+        self.reader.get_asset_data(self.asset)
+    }
+}
+
+impl<S: Storage> std::fmt::Debug for Page<'_, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Page")
+            .field("index", &self.index)
+            .field("asset", self.asset)
+            .field("media_type", &self.media_type)
+            .field("flags", &self.flags)
+            .finish_non_exhaustive()
+    }
+}
+
+/// what a metadata entry applies to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataScope {
+    /// the whole book
+    Book,
+    /// the section with this title
+    Section(String),
+}
+
+/// a decoded metadata entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataEntry {
+    /// the metadata key
+    pub key: String,
+    /// the metadata value
+    pub value: String,
+    /// what the entry applies to
+    pub scope: MetadataScope,
+}
+
+impl Book {
+    /// opens a BBF file and resolves it into a book
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path to the BBF file to open
+    ///
+    /// # Returns
+    ///
+    /// the resolved book
+    ///
+    /// # Errors
+    ///
+    /// returns an error if [`BbfReader::open`] or [`Book::new`] fails
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BbfReader::open(path)?)
+    }
+}
+
+impl<S: Storage> Book<S> {
+    /// resolves the tables of an opened reader
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - the reader to take ownership of
+    ///
+    /// # Returns
+    ///
+    /// the resolved book
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - any index table lies outside the file
+    /// - a page references an asset that doesn't exist
+    /// - a section title, metadata string, or parent title can't be read from the string pool
+    pub fn new(reader: BbfReader<S>) -> Result<Self> {
+        let assets = reader.assets()?.to_vec();
+
+        let pages = reader
+            .pages()?
+            .iter()
+            .enumerate()
+            .map(|(i, page)| {
+                usize::try_from(page.asset_index)
+                    .ok()
+                    .filter(|&index| index < assets.len())
+                    .map(|index| (index, page.flags))
+                    .ok_or_else(|| BbfError::InvalidOffset {
+                        description: format!("Page {} references missing asset {}", i, {
+                            page.asset_index
+                        }),
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let sections = SectionTree::build(&reader)?;
+
+        let metadata = reader
+            .metadata()?
+            .iter()
+            .map(|meta| {
+                Ok(MetadataEntry {
+                    key: reader.get_string(meta.key_offset)?.to_string(),
+                    value: reader.get_string(meta.value_offset)?.to_string(),
+                    scope: match meta.parent_offset {
+                        u64::MAX => MetadataScope::Book,
+                        offset => MetadataScope::Section(reader.get_string(offset)?.to_string()),
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            reader,
+            assets,
+            pages,
+            sections,
+            metadata,
+        })
+    }
+
+    /// returns the reader the book was resolved from
+    pub const fn reader(&self) -> &BbfReader<S> {
+        &self.reader
+    }
+
+    /// gives back the underlying reader
+    pub fn into_reader(self) -> BbfReader<S> {
+        self.reader
+    }
+
+    /// returns the number of pages
+    pub const fn page_count(&self) -> u64 {
+        self.pages.len() as u64
+    }
+
+    /// returns a single page
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - zero-based page number
+    ///
+    /// # Returns
+    ///
+    /// the page, or `None` if `index` is past the last page
+    pub fn page(&self, index: u64) -> Option<Page<'_, S>> {
+        let (asset_index, flags) = *self.pages.get(usize::try_from(index).ok()?)?;
+        let asset = &self.assets[asset_index];
+
+        Some(Page {
+            index,
+            asset,
+            media_type: MediaType::from(asset.media_type),
+            flags,
+            reader: &self.reader,
+        })
+    }
+
+    /// iterates over every page in reading order
+    pub fn pages(&self) -> impl ExactSizeIterator<Item = Page<'_, S>> {
+        self.pages
+            .iter()
+            .enumerate()
+            .map(|(i, &(asset_index, flags))| {
+                let asset = &self.assets[asset_index];
+                Page {
+                    index: i as u64,
+                    asset,
+                    media_type: MediaType::from(asset.media_type),
+                    flags,
+                    reader: &self.reader,
+                }
+            })
+    }
+
+    /// returns the sections linked into a tree
+    pub const fn section_tree(&self) -> &SectionTree {
+        &self.sections
+    }
+
+    /// iterates over every section in table order
+    pub fn sections(&self) -> impl ExactSizeIterator<Item = SectionView<'_>> {
+        self.sections.iter()
+    }
+
+    /// iterates over the sections that have no parent, in table order
+    pub fn root_sections(&self) -> impl Iterator<Item = SectionView<'_>> {
+        self.sections.roots()
+    }
+
+    /// finds the first section with the given title
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - the section title to look for
+    ///
+    /// # Returns
+    ///
+    /// the section, or `None` if no section has that title
+    pub fn section(&self, title: &str) -> Option<SectionView<'_>> {
+        self.sections.find(title)
+    }
+
+    /// returns every metadata entry in table order
+    pub fn metadata(&self) -> &[MetadataEntry] {
+        &self.metadata
+    }
+
+    /// indexes the metadata by key
+    pub fn metadata_map(&self) -> MetadataMap<'_> {
+        MetadataMap::new(self.metadata.iter().map(|entry| {
+            let section = match &entry.scope {
+                MetadataScope::Book => None,
+                MetadataScope::Section(title) => Some(title.as_str()),
+            };
+            (entry.key.as_str(), entry.value.as_str(), section)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, crate::BbfBuilder, assert2::check as assert, tempfile::NamedTempFile};
+
+    fn build_book() -> NamedTempFile {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        for byte in [1u8, 2, 1, 3, 4] {
+            builder
+                .add_page_bytes(&[byte; 100], MediaType::Png, 0, 0)
+                .unwrap();
+        }
+        builder.add_section("Volume 1", 0, None);
+        builder.add_section("Chapter 1", 0, Some("Volume 1"));
+        builder.add_section("Chapter 2", 2, Some("Volume 1"));
+        builder.add_section("Volume 2", 4, None);
+        builder.add_metadata("Title", "Test Book", None);
+        builder.add_metadata("Translator", "Someone", Some("Chapter 2"));
+        builder.finalize().unwrap();
+        temp_output
+    }
+
+    #[test]
+    fn test_pages_pair_entries_with_assets() {
+        let file = build_book();
+        let book = Book::open(file.path()).unwrap();
+
+        assert!(book.page_count() == 5);
+        let pages: Vec<_> = book.pages().collect();
+        assert!(pages.len() == 5);
+        assert!(pages[2].index == 2);
+        assert!(pages[2].media_type == MediaType::Png);
+        assert!({ pages[0].asset.file_offset } == { pages[2].asset.file_offset });
+        assert!(*pages[3].data().unwrap() == [3u8; 100]);
+        assert!(book.page(5).is_none());
+    }
+
+    #[test]
+    fn test_sections_resolve_hierarchy_and_ranges() {
+        let file = build_book();
+        let book = Book::open(file.path()).unwrap();
+
+        let volume = book.section("Volume 1").unwrap();
+        assert!(volume.parent().is_none());
+        assert!(volume.pages() == (0..4));
+        let children: Vec<_> = volume.children().map(|c| c.title()).collect();
+        assert!(children == ["Chapter 1", "Chapter 2"]);
+
+        let chapter = book.section("Chapter 1").unwrap();
+        assert!(chapter.parent().unwrap().title() == "Volume 1");
+        assert!(chapter.pages() == (0..2));
+        assert!(book.section("Chapter 2").unwrap().pages() == (2..4));
+        assert!(book.section("Volume 2").unwrap().pages() == (4..5));
+
+        let roots: Vec<_> = book.root_sections().map(|s| s.title()).collect();
+        assert!(roots == ["Volume 1", "Volume 2"]);
+    }
+
+    #[test]
+    fn test_metadata_is_decoded_with_scope() {
+        let file = build_book();
+        let book = Book::open(file.path()).unwrap();
+
+        assert!(
+            book.metadata()
+                == [
+                    MetadataEntry {
+                        key: "Title".into(),
+                        value: "Test Book".into(),
+                        scope: MetadataScope::Book,
+                    },
+                    MetadataEntry {
+                        key: "Translator".into(),
+                        value: "Someone".into(),
+                        scope: MetadataScope::Section("Chapter 2".into()),
+                    },
+                ]
+        );
+    }
+
+    #[test]
+    #[macroni_n_cheese::mathinator2000]
+    fn test_rejects_page_with_missing_asset() {
+        let file = build_book();
+        let reader = BbfReader::open(file.path()).unwrap();
+        let page_offset = reader.footer().page_offset as usize;
+        drop(reader);
+
+        let mut data = std::fs::read(file.path()).unwrap();
+        data[page_offset..page_offset + 8].copy_from_slice(&99u64.to_le_bytes());
+
+        let reader = BbfReader::from_bytes(data).unwrap();
+        assert!(matches!(
+            Book::new(reader),
+            Err(BbfError::InvalidOffset { .. })
+        ));
+    }
+}
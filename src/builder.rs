@@ -1,10 +1,10 @@
 use {
-    crate::prelude::*,
+    crate::{codec::encode_table, metadata::keys_match, prelude::*},
     hashbrown::HashMap,
     miette::IntoDiagnostic,
     std::{
-        fs::File,
-        io::{BufWriter, Seek, Write},
+        fs::{File, OpenOptions},
+        io::{BufWriter, Read, Seek, SeekFrom, Write},
         path::Path,
     },
     xxhash_rust::xxh3::{Xxh3, xxh3_128},
@@ -28,10 +28,12 @@ pub struct BbfBuilder {
     sections: Vec<Section>,
     /// collection of metadata k-v pairs
     metadata: Vec<Metadata>,
+    /// collection of expansion records (typed format extensions)
+    expansions: Vec<Expansion>,
     /// pooled null-terminated strings for efficient storage
     string_pool: Vec<u8>,
-    /// maps asset hashes to their indices for deduplication
-    dedupe_map: HashMap<u128, u64>,
+    /// maps asset hashes and flags to their indices for deduplication
+    dedupe_map: HashMap<(u128, u32), u64>,
     /// maps strings to their offsets in the string pool
     string_map: HashMap<String, u64>,
     /// alignment exponent for guard alignment (actual bytes = 1 << guard_value)
@@ -40,6 +42,16 @@ pub struct BbfBuilder {
     ream_value: u8,
     /// flags for header config
     header_flags: u32,
+    /// whether entries were removed, leaving unreferenced strings in the pool
+    strings_dirty: bool,
+    /// length of a file opened for editing, which it is cut back to if the builder is dropped
+    /// without being finalized
+    original_len: Option<u64>,
+    /// offset of the index of a file opened for editing, which the new index is moved back to if
+    /// no asset data was appended
+    old_index_offset: Option<u64>,
+    /// the `(declared, sniffed)` media types of the last page added, if they disagreed
+    last_media_mismatch: Option<(MediaType, MediaType)>,
 }
 
 impl BbfBuilder {
@@ -115,12 +127,17 @@ impl BbfBuilder {
             pages: Vec::new(),
             sections: Vec::new(),
             metadata: Vec::new(),
+            expansions: Vec::new(),
             string_pool: Vec::new(),
             dedupe_map: HashMap::new(),
             string_map: HashMap::new(),
             guard_value: alignment,
             ream_value: ream_size,
             header_flags: flags,
+            strings_dirty: false,
+            original_len: None,
+            old_index_offset: None,
+            last_media_mismatch: None,
         })
     }
 
@@ -153,10 +170,185 @@ impl BbfBuilder {
         .map_err(|e| e.into())
     }
 
-    /// writes a struct directly to the buffered writer as raw bytes
+    /// opens an existing BBF file for editing
     ///
-    /// converts the struct to its raw byte representation and writes it to the file. this is used
-    /// for writing fixed-size binary structures like headers and footers.
+    /// loads the tables of an existing file (expansion records of unknown kinds included), so the
+    /// asset data already in the file is reused as-is. the old index and footer are left where
+    /// they are: new pages are appended after the end of the file, sections and metadata can be
+    /// added, replaced, or removed, and [`BbfBuilder::finalize`] writes a fresh index and footer
+    /// after the new assets before patching the header to point at them. alignment, ream size,
+    /// and header flags are taken from the existing header.
+    ///
+    /// until the header is patched it still points at the old footer, so the file stays readable
+    /// as it was if the process dies mid-edit. each edit leaves the previous index behind as
+    /// unused space between the assets. if the builder is dropped without being finalized, the
+    /// appended data is cut off again.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path to the BBF file to edit
+    ///
+    /// # Returns
+    ///
+    /// a [`BbfBuilder`] pre-populated with the file's assets, pages, sections, and metadata
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - opening or parsing the file fails
+    /// - the file has the petrification flag set (see [`BbfBuilder::force_open_existing`])
+    /// - an asset lies outside the asset region
+    /// - opening or seeking the file fails
+    pub fn open_existing<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_existing_impl(path.as_ref(), false)
+    }
+
+    /// opens an existing BBF file for editing, even if it is petrified
+    ///
+    /// this is the explicit override for [`BbfBuilder::open_existing`]. the petrification flag
+    /// is kept as-is, so the edited file stays petrified unless the caller clears it with
+    /// [`BbfBuilder::set_header_flags`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path to the BBF file to edit
+    ///
+    /// # Returns
+    ///
+    /// a [`BbfBuilder`] pre-populated with the file's assets, pages, sections, and metadata
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - opening or parsing the file fails
+    /// - an asset lies outside the asset region
+    /// - opening or seeking the file fails
+    pub fn force_open_existing<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_existing_impl(path.as_ref(), true)
+    }
+
+    /// shared implementation of [`BbfBuilder::open_existing`] and
+    /// [`BbfBuilder::force_open_existing`]
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path to the BBF file to edit
+    /// * `allow_petrified` - whether petrified files may be opened
+    ///
+    /// # Returns
+    ///
+    /// a [`BbfBuilder`] pre-populated with the file's assets, pages, sections, and metadata
+    ///
+    /// # Errors
+    ///
+    /// see [`BbfBuilder::open_existing`]
+    #[macroni_n_cheese::mathinator2000]
+    fn open_existing_impl(path: &Path, allow_petrified: bool) -> Result<Self> {
+        let reader = crate::BbfReader::open(path)?;
+        let header = *reader.header();
+
+        if reader.is_petrified() && !allow_petrified {
+            return Err(BbfError::Petrified);
+        }
+
+        let index_start = reader.footer().asset_offset;
+        let assets = reader.assets()?.to_vec();
+        let mut dedupe_map = HashMap::new();
+
+        for (i, asset) in assets.iter().enumerate() {
+            let end = asset
+                .file_offset
+                .checked_add(asset.file_size)
+                .ok_or_else(|| BbfError::IntegerOverflow {
+                    description: format!("Asset {} offset + size", i),
+                })?;
+
+            if asset.file_offset < header.header_len as u64 || end > index_start {
+                return Err(BbfError::InvalidOffset {
+                    description: format!("Asset {} lies outside the asset region", i),
+                });
+            }
+
+            let hash = asset.asset_hash;
+            dedupe_map.insert(
+                ((hash[0] as u128) | ((hash[1] as u128) << 64), asset.flags),
+                i as u64,
+            );
+        }
+
+        let pages = reader.pages()?.to_vec();
+        let expansions = reader.expansions()?.to_vec();
+
+        let optional_string = |offset: u64| -> Result<Option<String>> {
+            if offset == u64::MAX {
+                Ok(None)
+            } else {
+                Ok(Some(reader.get_string(offset)?.to_string()))
+            }
+        };
+
+        let mut sections = Vec::new();
+        for section in reader.sections()? {
+            sections.push((
+                reader.get_string(section.section_title_offset)?.to_string(),
+                section.section_start_index,
+                optional_string(section.section_parent_offset)?,
+            ));
+        }
+
+        let mut metadata = Vec::new();
+        for meta in reader.metadata()? {
+            metadata.push((
+                reader.get_string(meta.key_offset)?.to_string(),
+                reader.get_string(meta.value_offset)?.to_string(),
+                optional_string(meta.parent_offset)?,
+            ));
+        }
+
+        drop(reader);
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .into_diagnostic()?;
+        let original_len = file.seek(SeekFrom::End(0)).into_diagnostic()?;
+
+        let mut builder = Self {
+            writer: BufWriter::new(file),
+            current_offset: original_len,
+            assets,
+            pages,
+            sections: Vec::new(),
+            metadata: Vec::new(),
+            expansions,
+            string_pool: Vec::new(),
+            dedupe_map,
+            string_map: HashMap::new(),
+            guard_value: header.alignment,
+            ream_value: header.ream_size,
+            header_flags: header.flags,
+            strings_dirty: false,
+            original_len: Some(original_len),
+            old_index_offset: Some(index_start),
+            last_media_mismatch: None,
+        };
+
+        for (title, start, parent) in &sections {
+            builder.add_section(title, *start, parent.as_deref());
+        }
+
+        for (key, value, parent) in &metadata {
+            builder.add_metadata(key, value, parent.as_deref());
+        }
+
+        Ok(builder)
+    }
+
+    /// writes a struct to the buffered writer in its on-disk encoding
+    ///
+    /// encodes the struct field by field as little-endian bytes and writes it to the file. this is
+    /// used for writing fixed-size binary structures like headers and footers.
     ///
     /// # Arguments
     ///
@@ -172,16 +364,8 @@ impl BbfBuilder {
     /// returns an error if:
     /// - writing bytes to the writer fails
     ///
-    /// # Safety
-    ///
-    /// uses unsafe to create a raw byte slice from the struct ptr. this is safe because the
-    /// struct is #[repr(c, packed)] and all fields are simple types with no padding or refs.
-    fn write_struct<T>(writer: &mut BufWriter<File>, data: &T) -> Result<()> {
-        unsafe {
-            let bytes =
-                std::slice::from_raw_parts(data as *const T as *const u8, std::mem::size_of::<T>());
-            writer.write_all(bytes).into_diagnostic()?;
-        }
+    fn write_struct<T: ToLeBytes>(writer: &mut BufWriter<File>, data: &T) -> Result<()> {
+        writer.write_all(&data.to_le_bytes()).into_diagnostic()?;
         Ok(())
     }
 
@@ -254,7 +438,9 @@ impl BbfBuilder {
     ///
     /// reads the image file, calculates its hash, and either reuses an existing asset
     /// (deduplication) or adds a new asset entry. applies appropriate alignment based on file
-    /// size and configuration. creates a page entry that references the asset.
+    /// size and configuration. creates a page entry that references the asset. the stored media
+    /// type is sniffed from the file contents, falling back to the file extension; see
+    /// [`BbfBuilder::last_media_mismatch`] to find out whether the two disagreed.
     ///
     /// # Arguments
     ///
@@ -262,10 +448,6 @@ impl BbfBuilder {
     /// * `page_flags` - flags for page-specific configuration
     /// * `asset_flags` - flags for asset-specific configuration
     ///
-    /// # Returns
-    ///
-    /// unit type on success, indicating the page was added
-    ///
     /// # Errors
     ///
     /// returns an error if:
@@ -273,7 +455,6 @@ impl BbfBuilder {
     /// - arithmetic operations overflow (protected by macroni_n_cheese::mathinator2000)
     /// - writing image data to the buffer fails
     /// - aligning padding fails
-    #[macroni_n_cheese::mathinator2000]
     pub fn add_page<P: AsRef<Path>>(
         &mut self,
         image_path: P,
@@ -281,58 +462,188 @@ impl BbfBuilder {
         asset_flags: u32,
     ) -> Result<()> {
         let data = std::fs::read(image_path.as_ref()).into_diagnostic()?;
-        let hash_128 = Self::calculate_hash_128(&data);
+        let media_type = MediaType::from_extension(
+            image_path
+                .as_ref()
+                .extension()
+                .and_then(|s| s.to_str())
+                .unwrap_or(""),
+        );
+
+        self.add_page_bytes(&data, media_type, page_flags, asset_flags)
+    }
 
-        let asset_index = if let Some(&idx) = self.dedupe_map.get(&hash_128) {
-            idx
-        } else {
-            let alignment_bytes = 1u64 << self.guard_value;
-            let threshold_bytes = 1u64 << self.ream_value;
+    /// adds a page (image) to the book from an in-memory buffer
+    ///
+    /// behaves exactly like [`BbfBuilder::add_page`] (same deduplication and alignment rules) but
+    /// takes the image data directly, so pages can come from archives, network buffers, or
+    /// generated images without touching the disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - the raw image data
+    /// * `media_type` - the declared media type of the image data, used only if the contents
+    ///   can't be sniffed (see [`MediaType::from_bytes`])
+    /// * `page_flags` - flags for page-specific configuration
+    /// * `asset_flags` - flags for asset-specific configuration
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - arithmetic operations overflow (protected by macroni_n_cheese::mathinator2000)
+    /// - writing image data to the buffer fails
+    /// - aligning padding fails
+    pub fn add_page_bytes(
+        &mut self,
+        data: &[u8],
+        media_type: MediaType,
+        page_flags: u32,
+        asset_flags: u32,
+    ) -> Result<()> {
+        let (stored, mismatch) = resolve_media_type(data, media_type);
+        let asset_index = self.add_asset(data, stored, asset_flags)?;
 
-            let variable_align = self.header_flags & BBF_VARIABLE_REAM_SIZE_FLAG != 0;
+        self.pages.push(PageEntry {
+            asset_index,
+            flags: page_flags,
+            reserved: [0; 4],
+        });
+        self.last_media_mismatch = mismatch;
 
-            let actual_alignment = if variable_align && (data.len() as u64) < threshold_bytes {
-                8
-            } else {
-                alignment_bytes
-            };
+        Ok(())
+    }
 
-            self.align_padding(actual_alignment).into_diagnostic()?;
+    /// returns whether the media type of the last page added disagreed with its contents
+    ///
+    /// the sniffed type is what gets stored, so this is only useful for warning about mislabeled
+    /// files (e.g. a PNG named `page.jpg`).
+    ///
+    /// # Returns
+    ///
+    /// `Some((declared, sniffed))` if the declared media type (or file extension) of the last
+    /// page added disagreed with its sniffed contents, otherwise `None`
+    pub const fn last_media_mismatch(&self) -> Option<(MediaType, MediaType)> {
+        self.last_media_mismatch
+    }
 
-            let media_type = MediaType::from_extension(
-                image_path
-                    .as_ref()
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or(""),
-            );
+    /// returns the header flags that will be written on finalize
+    ///
+    /// # Returns
+    ///
+    /// the current header flags
+    pub const fn header_flags(&self) -> u32 {
+        self.header_flags
+    }
 
-            let asset = AssetEntry {
-                file_offset: self.current_offset,
-                asset_hash: [hash_128 as u64, (hash_128 >> 64) as u64],
-                file_size: data.len() as u64,
-                flags: asset_flags,
-                reserved_value: 0,
-                media_type: media_type as u8,
-                reserved: [0; 9],
-            };
+    /// replaces the header flags that will be written on finalize
+    ///
+    /// alignment decisions for assets that were already added are not revisited, so
+    /// [`BBF_VARIABLE_REAM_SIZE_FLAG`] should only be changed before adding pages.
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - the new header flags
+    pub const fn set_header_flags(&mut self, flags: u32) {
+        self.header_flags = flags;
+    }
+
+    /// marks the book as petrified (permanently read-only)
+    ///
+    /// sets [`BBF_PETRIFICATION_FLAG`] in the header written on finalize. once written, the file
+    /// can only be edited through [`BbfBuilder::force_open_existing`].
+    pub const fn petrify(&mut self) {
+        self.header_flags |= BBF_PETRIFICATION_FLAG;
+    }
+
+    /// adds a page (image) to the book from any reader
+    ///
+    /// drains the reader and adds the result via [`BbfBuilder::add_page_bytes`]. the data is
+    /// buffered in memory because its hash has to be known before anything is written, otherwise
+    /// duplicate assets could not be skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - the source of the image data
+    /// * `media_type` - the media type of the image data
+    /// * `page_flags` - flags for page-specific configuration
+    /// * `asset_flags` - flags for asset-specific configuration
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - reading from the reader fails
+    /// - adding the buffered data fails (see [`BbfBuilder::add_page_bytes`])
+    pub fn add_page_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+        media_type: MediaType,
+        page_flags: u32,
+        asset_flags: u32,
+    ) -> Result<()> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).into_diagnostic()?;
+        self.add_page_bytes(&data, media_type, page_flags, asset_flags)
+    }
+
+    /// writes asset data to the file, or finds an identical asset that was already written
+    ///
+    /// only assets with the same data and the same flags are shared, so a thumbnail never reuses
+    /// a page's asset (or the other way around).
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - the raw asset data
+    /// * `media_type` - the media type of the asset data
+    /// * `asset_flags` - flags for asset-specific configuration
+    ///
+    /// # Returns
+    ///
+    /// the index of the (possibly pre-existing) asset in the asset table
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - arithmetic operations overflow (protected by macroni_n_cheese::mathinator2000)
+    /// - writing asset data to the buffer fails
+    /// - aligning padding fails
+    #[macroni_n_cheese::mathinator2000]
+    fn add_asset(&mut self, data: &[u8], media_type: MediaType, asset_flags: u32) -> Result<u64> {
+        let hash_128 = Self::calculate_hash_128(data);
+
+        if let Some(&idx) = self.dedupe_map.get(&(hash_128, asset_flags)) {
+            return Ok(idx);
+        }
 
-            self.writer.write_all(&data).into_diagnostic()?;
-            self.current_offset += data.len() as u64;
+        let alignment_bytes = 1u64 << self.guard_value;
+        let threshold_bytes = 1u64 << self.ream_value;
 
-            let idx = self.assets.len() as u64;
-            self.assets.push(asset);
-            self.dedupe_map.insert(hash_128, idx);
-            idx
+        let variable_align = self.header_flags & BBF_VARIABLE_REAM_SIZE_FLAG != 0;
+
+        let actual_alignment = if variable_align && (data.len() as u64) < threshold_bytes {
+            8
+        } else {
+            alignment_bytes
         };
 
-        self.pages.push(PageEntry {
-            asset_index,
-            flags: page_flags,
-            reserved: [0; 4],
-        });
+        self.align_padding(actual_alignment).into_diagnostic()?;
 
-        Ok(())
+        let asset = AssetEntry {
+            file_offset: self.current_offset,
+            asset_hash: [hash_128 as u64, (hash_128 >> 64) as u64],
+            file_size: data.len() as u64,
+            flags: asset_flags,
+            reserved_value: 0,
+            media_type: media_type as u8,
+            reserved: [0; 9],
+        };
+
+        self.writer.write_all(data).into_diagnostic()?;
+        self.current_offset += data.len() as u64;
+
+        let idx = self.assets.len() as u64;
+        self.assets.push(asset);
+        self.dedupe_map.insert((hash_128, asset_flags), idx);
+        Ok(idx)
     }
 
     /// gets or adds a string to the string pool
@@ -391,7 +702,8 @@ impl BbfBuilder {
     /// adds a metadata key-val pair to the book
     ///
     /// stores arbitrary metadata like author, title, publisher, or isbn. metadata can optionally
-    /// be associated with a parent section.
+    /// be associated with a parent section. keys are stored exactly as given; lookups match
+    /// well-known keys (see [`crate::keys`]) regardless of case.
     ///
     /// # Arguments
     ///
@@ -417,13 +729,238 @@ impl BbfBuilder {
         });
     }
 
+    /// adds a typed expansion record to the book
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - the record to encode into the expansion table
+    pub fn add_expansion<E: ExpansionRecord>(&mut self, record: &E) {
+        self.expansions.push(record.to_expansion());
+    }
+
+    /// adds a raw expansion entry to the book
+    ///
+    /// useful for copying entries of kinds this implementation doesn't understand.
+    ///
+    /// # Arguments
+    ///
+    /// * `expansion` - the entry to append to the expansion table
+    pub fn add_expansion_entry(&mut self, expansion: Expansion) {
+        self.expansions.push(expansion);
+    }
+
+    /// removes every expansion entry of the given kind
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - the kind id of the entries to remove
+    ///
+    /// # Returns
+    ///
+    /// the number of expansion entries removed
+    pub fn remove_expansions(&mut self, kind: u64) -> usize {
+        let before = self.expansions.len();
+        self.expansions.retain(|exp| exp.kind() != kind);
+        before.saturating_sub(self.expansions.len())
+    }
+
+    /// sets the book's thumbnail from prepared image data
+    ///
+    /// the data is stored as an asset flagged with [`BBF_ASSET_THUMBNAIL_FLAG`] and located
+    /// through a [`Thumbnail`] expansion record, replacing any previous thumbnail record. when
+    /// editing an existing file the old thumbnail asset is left in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - the encoded thumbnail image
+    /// * `media_type` - the thumbnail's media type
+    /// * `width` - the thumbnail width in pixels (0 if unknown)
+    /// * `height` - the thumbnail height in pixels (0 if unknown)
+    ///
+    /// # Errors
+    ///
+    /// returns an error if writing the asset data fails
+    pub fn set_thumbnail(
+        &mut self,
+        data: &[u8],
+        media_type: MediaType,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
+        let asset_index = self.add_asset(data, media_type, BBF_ASSET_THUMBNAIL_FLAG)?;
+
+        self.remove_expansions(THUMBNAIL_EXPANSION_KIND);
+        self.add_expansion(&Thumbnail {
+            asset_index,
+            width,
+            height,
+        });
+
+        Ok(())
+    }
+
+    /// generates the book's thumbnail from a full-size image
+    ///
+    /// scales the image down to fit within a `max_dimension` square and stores it as a jpeg via
+    /// [`BbfBuilder::set_thumbnail`].
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - encoded image data, usually the cover page
+    /// * `max_dimension` - the bounding box size in pixels (see [`DEFAULT_THUMBNAIL_SIZE`])
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the source can't be decoded or writing the asset data fails
+    #[cfg(feature = "thumbnail")]
+    pub fn generate_thumbnail(&mut self, source: &[u8], max_dimension: u32) -> Result<()> {
+        let (data, width, height) = crate::thumbnail::render(source, max_dimension)?;
+        self.set_thumbnail(&data, MediaType::Jpg, width, height)
+    }
+
+    /// removes every section with the given title, along with the sections nested in it
+    ///
+    /// section-scoped metadata of the removed sections goes too, so nothing is left pointing at a
+    /// missing section. parents are referenced by title, so children and metadata of a title that
+    /// another (surviving) section still carries are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - the title of the section(s) to remove
+    ///
+    /// # Returns
+    ///
+    /// the number of sections removed, nested ones included
+    pub fn remove_section(&mut self, title: &str) -> usize {
+        let before = self.sections.len();
+        let pool = &self.string_pool;
+        let parent_in = |offset: u64, titles: &[&[u8]]| {
+            offset != u64::MAX && titles.contains(&pooled_string(pool, offset))
+        };
+
+        let mut gone = vec![title.as_bytes()];
+        loop {
+            let mut removed = Vec::new();
+            self.sections.retain(|section| {
+                let own = pooled_string(pool, section.section_title_offset);
+                let keep =
+                    own != title.as_bytes() && !parent_in(section.section_parent_offset, &gone);
+                if !keep {
+                    removed.push(own);
+                }
+                keep
+            });
+
+            if removed.is_empty() {
+                break;
+            }
+            removed.retain(|removed_title| {
+                !self.sections.iter().any(|section| {
+                    pooled_string(pool, section.section_title_offset) == *removed_title
+                })
+            });
+            gone.extend(removed);
+        }
+
+        self.metadata
+            .retain(|meta| !parent_in(meta.parent_offset, &gone));
+
+        let removed = before.saturating_sub(self.sections.len());
+        self.strings_dirty |= removed > 0;
+        removed
+    }
+
+    /// removes every metadata entry with the given key and scope
+    ///
+    /// well-known keys match regardless of case.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the metadata key to remove
+    /// * `parent` - the section the entries belong to, or `None` for global entries
+    ///
+    /// # Returns
+    ///
+    /// the number of metadata entries removed
+    pub fn remove_metadata(&mut self, key: &str, parent: Option<&str>) -> usize {
+        let before = self.metadata.len();
+        let pool = &self.string_pool;
+        self.metadata.retain(|meta| {
+            let same_parent = match parent {
+                Some(p) => {
+                    meta.parent_offset != u64::MAX
+                        && pooled_string(pool, meta.parent_offset) == p.as_bytes()
+                }
+                None => meta.parent_offset == u64::MAX,
+            };
+            !(same_parent
+                && std::str::from_utf8(pooled_string(pool, meta.key_offset))
+                    .is_ok_and(|stored| keys_match(stored, key)))
+        });
+
+        let removed = before.saturating_sub(self.metadata.len());
+        self.strings_dirty |= removed > 0;
+        removed
+    }
+
+    /// sets a metadata key-val pair, replacing any existing entries with the same key and scope
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the metadata key (stored in string pool)
+    /// * `val` - the metadata val (stored in string pool)
+    /// * `parent` - optional parent section for section-specific metadata
+    pub fn set_metadata(&mut self, key: &str, val: &str, parent: Option<&str>) {
+        self.remove_metadata(key, parent);
+        self.add_metadata(key, val, parent);
+    }
+
+    /// rebuilds the string pool so it only holds strings that are still referenced
+    ///
+    /// only needed after entries were removed; otherwise the pool is already minimal.
+    fn compact_string_pool(&mut self) {
+        let old_pool = std::mem::take(&mut self.string_pool);
+        self.string_map.clear();
+
+        let remap = |builder: &mut Self, offset: u64| {
+            if offset == u64::MAX {
+                offset
+            } else {
+                let s = String::from_utf8_lossy(pooled_string(&old_pool, offset)).into_owned();
+                builder.get_or_add_string(&s)
+            }
+        };
+
+        let mut sections = std::mem::take(&mut self.sections);
+        for section in &mut sections {
+            section.section_title_offset = remap(self, section.section_title_offset);
+            section.section_parent_offset = remap(self, section.section_parent_offset);
+        }
+
+        let mut metadata = std::mem::take(&mut self.metadata);
+        for meta in &mut metadata {
+            meta.key_offset = remap(self, meta.key_offset);
+            meta.value_offset = remap(self, meta.value_offset);
+            meta.parent_offset = remap(self, meta.parent_offset);
+        }
+
+        self.sections = sections;
+        self.metadata = metadata;
+        self.strings_dirty = false;
+    }
+
     /// finalizes the book file and writes all indices
     ///
-    /// writes all asset, page, section, and metadata tables to the file, followed by the string
-    /// pool. calculates an integrity hash over all index data, writes the footer with all
+    /// writes all asset, page, section, metadata, and expansion tables to the file, followed by
+    /// the string pool. calculates an integrity hash over all index data, writes the footer with all
     /// offsets and counts, then seeks back to the beginning to update the header with final
     /// values. flushes and syncs all data to disk.
     ///
+    /// when an edit appended no asset data, the new index is then written a second time over the
+    /// old one and the file is cut back, so repeated edits don't leave dead indices behind. the
+    /// header only ever points at a fully written index, so the file stays readable if the process
+    /// dies halfway through.
+    ///
     /// # Returns
     ///
     /// unit type on success, indicating the BBF file is complete and ready for use
@@ -439,92 +976,130 @@ impl BbfBuilder {
     /// - extracting the inner file from bufwriter fails
     /// - seeking to the start of the file fails
     /// - writing the updated header fails
+    /// - cutting the file back fails
     #[macroni_n_cheese::mathinator2000]
     pub fn finalize(mut self) -> Result<()> {
+        if self.strings_dirty {
+            self.compact_string_pool();
+        }
+
+        let reclaim_at = self
+            .old_index_offset
+            .filter(|_| self.original_len == Some(self.current_offset));
+
+        let Some(old_index_offset) = reclaim_at else {
+            self.commit_index(self.current_offset)?;
+            self.original_len = None;
+            return Ok(());
+        };
+
+        // the first copy must not overlap the spot the second one is written to
+        let index_len = self.encode_index(0)?.0.len() as u64;
+        let first_copy_at = self.current_offset.max(old_index_offset + index_len);
+        self.commit_index(first_copy_at)?;
+        self.original_len = None;
+
+        // nothing points at the old index anymore, so it can be overwritten
+        self.commit_index(old_index_offset)?;
+        let file = self.writer.get_mut();
+        file.set_len(old_index_offset + index_len)
+            .into_diagnostic()?;
+        file.sync_all().into_diagnostic()?;
+        Ok(())
+    }
+
+    /// encodes the index tables, string pool, and footer for an index starting at `offset`
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - the file offset the index will be written at
+    ///
+    /// # Returns
+    ///
+    /// the encoded index and the offset of its footer
+    ///
+    /// # Errors
+    ///
+    /// returns an error if arithmetic operations overflow (protected by
+    /// macroni_n_cheese::mathinator2000)
+    #[macroni_n_cheese::mathinator2000]
+    fn encode_index(&self, offset: u64) -> Result<(Vec<u8>, u64)> {
         let mut hasher = Xxh3::new();
+        let mut index = Vec::new();
 
-        let assets_bytes = unsafe {
-            std::slice::from_raw_parts(
-                self.assets.as_ptr() as *const u8,
-                self.assets.len() * std::mem::size_of::<AssetEntry>(),
-            )
-        };
-        let offset_assets = self.current_offset;
-        self.writer.write_all(assets_bytes).into_diagnostic()?;
-        hasher.update(assets_bytes);
-        self.current_offset += assets_bytes.len() as u64;
-
-        let pages_bytes = unsafe {
-            std::slice::from_raw_parts(
-                self.pages.as_ptr() as *const u8,
-                self.pages.len() * std::mem::size_of::<PageEntry>(),
-            )
-        };
-        let offset_pages = self.current_offset;
-        self.writer.write_all(pages_bytes).into_diagnostic()?;
-        hasher.update(pages_bytes);
-        self.current_offset += pages_bytes.len() as u64;
-
-        let sections_bytes = unsafe {
-            std::slice::from_raw_parts(
-                self.sections.as_ptr() as *const u8,
-                self.sections.len() * std::mem::size_of::<Section>(),
-            )
-        };
-        let offset_sections = self.current_offset;
-        self.writer.write_all(sections_bytes).into_diagnostic()?;
-        hasher.update(sections_bytes);
-        self.current_offset += sections_bytes.len() as u64;
-
-        let metadata_bytes = unsafe {
-            std::slice::from_raw_parts(
-                self.metadata.as_ptr() as *const u8,
-                self.metadata.len() * std::mem::size_of::<Metadata>(),
-            )
+        let offset_assets = offset + index.len() as u64;
+        index.extend_from_slice(&encode_table(&self.assets));
+
+        let offset_pages = offset + index.len() as u64;
+        index.extend_from_slice(&encode_table(&self.pages));
+
+        let offset_sections = offset + index.len() as u64;
+        index.extend_from_slice(&encode_table(&self.sections));
+
+        let offset_meta = offset + index.len() as u64;
+        index.extend_from_slice(&encode_table(&self.metadata));
+
+        let offset_expansions = if self.expansions.is_empty() {
+            0
+        } else {
+            offset + index.len() as u64
         };
-        let offset_meta = self.current_offset;
-        self.writer.write_all(metadata_bytes).into_diagnostic()?;
-        hasher.update(metadata_bytes);
-        self.current_offset += metadata_bytes.len() as u64;
-
-        let offset_strings = self.current_offset;
-        let str_pool_size = self.string_pool.len() as u64;
-        if str_pool_size > 0 {
-            self.writer.write_all(&self.string_pool)?;
-            hasher.update(&self.string_pool);
-            self.current_offset += str_pool_size;
-        }
+        index.extend_from_slice(&encode_table(&self.expansions));
+
+        let offset_strings = offset + index.len() as u64;
+        index.extend_from_slice(&self.string_pool);
 
-        let index_hash = hasher.digest();
-        let footer_offset = self.current_offset;
+        hasher.update(&index);
+        let footer_offset = offset + index.len() as u64;
 
         let footer = BbfFooter {
             asset_offset: offset_assets,
             page_offset: offset_pages,
             section_offset: offset_sections,
             meta_offset: offset_meta,
-            expansion_offset: 0,
+            expansion_offset: offset_expansions,
             string_pool_offset: offset_strings,
-            string_pool_size: str_pool_size,
+            string_pool_size: self.string_pool.len() as u64,
             asset_count: self.assets.len() as u64,
             page_count: self.pages.len() as u64,
             section_count: self.sections.len() as u64,
             meta_count: self.metadata.len() as u64,
-            expansion_count: 0,
+            expansion_count: self.expansions.len() as u64,
             flags: 0,
             footer_len: std::mem::size_of::<BbfFooter>() as u8,
             padding: [0; 3],
-            footer_hash: index_hash,
+            footer_hash: hasher.digest(),
             reserved: [0; 144],
         };
+        index.extend_from_slice(&footer.to_le_bytes());
+
+        Ok((index, footer_offset))
+    }
 
-        Self::write_struct(&mut self.writer, &footer).into_diagnostic()?;
+    /// writes the index at `offset` and points the header at it
+    ///
+    /// the index is synced to disk before the header is patched, so a crash in between leaves the
+    /// header pointing at the previous (still intact) index.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - the file offset to write the index at
+    ///
+    /// # Errors
+    ///
+    /// returns an error if encoding, writing, or syncing the index or header fails
+    fn commit_index(&mut self, offset: u64) -> Result<()> {
+        let (index, footer_offset) = self.encode_index(offset)?;
 
+        self.writer
+            .seek(SeekFrom::Start(offset))
+            .into_diagnostic()?;
+        self.writer.write_all(&index).into_diagnostic()?;
         self.writer.flush().into_diagnostic()?;
-        self.writer.get_mut().sync_all().into_diagnostic()?;
 
-        let mut file = self.writer.into_inner().into_diagnostic()?;
-        file.seek(std::io::SeekFrom::Start(0)).into_diagnostic()?;
+        let file = self.writer.get_mut();
+        file.sync_all().into_diagnostic()?;
+        file.seek(SeekFrom::Start(0)).into_diagnostic()?;
 
         let header = BbfHeader {
             magic: *MAGIC,
@@ -538,14 +1113,7 @@ impl BbfBuilder {
             reserved: [0; 40],
         };
 
-        unsafe {
-            let bytes = std::slice::from_raw_parts(
-                &header as *const BbfHeader as *const u8,
-                std::mem::size_of::<BbfHeader>(),
-            );
-            file.write_all(bytes).into_diagnostic()?;
-        }
-
+        file.write_all(&header.to_le_bytes()).into_diagnostic()?;
         file.sync_all().into_diagnostic()?;
         Ok(())
     }
@@ -577,6 +1145,15 @@ impl BbfBuilder {
         self.sections.len()
     }
 
+    /// returns the current number of expansion entries
+    ///
+    /// # Returns
+    ///
+    /// count of expansion records added
+    pub const fn expansion_count(&self) -> usize {
+        self.expansions.len()
+    }
+
     /// returns the current number of metadata entries
     ///
     /// # Returns
@@ -587,6 +1164,60 @@ impl BbfBuilder {
     }
 }
 
+impl Drop for BbfBuilder {
+    /// cuts off the data appended by an edit that was abandoned before finalizing
+    fn drop(&mut self) {
+        if let Some(len) = self.original_len.take() {
+            let _ = self.writer.flush();
+            let _ = self.writer.get_mut().set_len(len);
+        }
+    }
+}
+
+/// picks the media type to store for a piece of asset data
+///
+/// the sniffed type wins whenever the contents are recognized, so a mislabeled file is still
+/// stored with its real type.
+///
+/// # Arguments
+///
+/// * `data` - the raw asset data
+/// * `declared` - the media type claimed by the caller
+///
+/// # Returns
+///
+/// the media type to store (the sniffed type, or `declared` if the contents aren't recognized)
+/// and, if a known declared type disagrees with the contents, `(declared, sniffed)`
+fn resolve_media_type(
+    data: &[u8],
+    declared: MediaType,
+) -> (MediaType, Option<(MediaType, MediaType)>) {
+    let sniffed = MediaType::from_bytes(data);
+    if sniffed == MediaType::Unknown {
+        return (declared, None);
+    }
+
+    let mismatch =
+        (declared != MediaType::Unknown && declared != sniffed).then_some((declared, sniffed));
+    (sniffed, mismatch)
+}
+
+/// returns the null-terminated string at an offset in a string pool, without the terminator
+///
+/// # Arguments
+///
+/// * `pool` - the string pool bytes
+/// * `offset` - byte offset of the string within the pool
+///
+/// # Returns
+///
+/// the string's bytes, or an empty slice if the offset is out of range
+fn pooled_string(pool: &[u8], offset: u64) -> &[u8] {
+    pool.get(offset as usize..)
+        .and_then(|rest| rest.split(|&b| b == 0).next())
+        .unwrap_or(&[])
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(unused, clippy::missing_panics_doc)]
@@ -617,6 +1248,24 @@ mod tests {
         assert!(builder.page_count() == 0);
     }
 
+    #[test]
+    #[macroni_n_cheese::mathinator2000]
+    fn test_finalize_writes_little_endian_header() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let builder = BbfBuilder::new(temp_output.path(), 12, 16, 0).unwrap();
+        builder.finalize().unwrap();
+
+        let data = std::fs::read(temp_output.path()).unwrap();
+        assert!(data[..14] == *b"BBF3\x03\x00\x40\x00\x00\x00\x00\x00\x0C\x10");
+
+        let header = BbfHeader::from_le_bytes(&data).unwrap();
+        let footer_offset = header.footer_offset as usize;
+        let footer = BbfFooter::from_le_bytes(&data[footer_offset..]).unwrap();
+        assert!({ footer.asset_offset } == 64);
+        let asset_offset_bytes = &data[footer_offset..footer_offset + 8];
+        assert!(*asset_offset_bytes == 64u64.to_le_bytes());
+    }
+
     #[test]
     fn test_builder_rejects_excessive_alignment() {
         let temp_output = NamedTempFile::new().unwrap();
@@ -698,6 +1347,88 @@ mod tests {
         assert!(builder.asset_count() == 2);
     }
 
+    #[test]
+    fn test_add_page_bytes_roundtrip() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+
+        builder
+            .add_page_bytes(&[7u8; 300], MediaType::Webp, 0, 0)
+            .unwrap();
+        builder.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        let assets = reader.assets().unwrap();
+        assert!(assets.len() == 1);
+        assert!(MediaType::from(assets[0].media_type) == MediaType::Webp);
+        assert!(*reader.get_asset_data(&assets[0]).unwrap() == [7u8; 300]);
+    }
+
+    #[test]
+    fn test_add_page_bytes_deduplicates_against_file_pages() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let test_image = create_test_image("png", 1024);
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+
+        builder.add_page(test_image.path(), 0, 0).unwrap();
+        builder
+            .add_page_bytes(&[0u8; 1024], MediaType::Png, 0, 0)
+            .unwrap();
+
+        assert!(builder.page_count() == 2);
+        assert!(builder.asset_count() == 1);
+    }
+
+    #[test]
+    fn test_add_page_reader() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+
+        let data = vec![9u8; 4096];
+        builder
+            .add_page_reader(std::io::Cursor::new(&data), MediaType::Jpg, 0, 0)
+            .unwrap();
+        builder
+            .add_page_reader(data.as_slice(), MediaType::Jpg, 0, 0)
+            .unwrap();
+        builder.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.page_count() == 2);
+        assert!(reader.asset_count() == 1);
+        let assets = reader.assets().unwrap();
+        assert!(reader.get_asset_data(&assets[0]).unwrap() == data.as_slice());
+    }
+
+    #[test]
+    fn test_add_page_sniffs_mislabeled_content() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut png_as_jpg = NamedTempFile::with_suffix(".jpg").unwrap();
+        png_as_jpg
+            .write_all(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0DIHDR")
+            .unwrap();
+        png_as_jpg.flush().unwrap();
+
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder.add_page(png_as_jpg.path(), 0, 0).unwrap();
+        assert!(builder.last_media_mismatch() == Some((MediaType::Jpg, MediaType::Png)));
+        builder
+            .add_page_bytes(&[0xFF, 0xD8, 0xFF, 0xE0, 1, 2], MediaType::Unknown, 0, 0)
+            .unwrap();
+        assert!(builder.last_media_mismatch().is_none());
+        builder
+            .add_page_bytes(&[1, 2, 3, 4], MediaType::Bmp, 0, 0)
+            .unwrap();
+        assert!(builder.last_media_mismatch().is_none());
+        builder.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        let assets = reader.assets().unwrap();
+        assert!(MediaType::from(assets[0].media_type) == MediaType::Png);
+        assert!(MediaType::from(assets[1].media_type) == MediaType::Jpg);
+        assert!(MediaType::from(assets[2].media_type) == MediaType::Bmp);
+    }
+
     #[test]
     fn test_string_pooling() {
         let temp_output = NamedTempFile::new().unwrap();
@@ -735,6 +1466,25 @@ mod tests {
         assert!(builder.metadata_count() == 2);
     }
 
+    #[test]
+    fn test_set_metadata_replaces_well_known_keys_regardless_of_case() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+
+        builder.add_metadata("title", "Old", None);
+        builder.set_metadata("TITLE", "New", None);
+        builder.add_metadata("colorist", "Dee", None);
+        assert!(builder.remove_metadata("Colorist", None) == 0);
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(temp_output.path()).unwrap();
+        let metadata = reader.metadata().unwrap();
+        assert!(metadata.len() == 2);
+        assert!(reader.get_string(metadata[0].key_offset).unwrap() == "TITLE");
+        assert!(reader.get_string(metadata[0].value_offset).unwrap() == "New");
+        assert!(reader.get_string(metadata[1].key_offset).unwrap() == "colorist");
+    }
+
     #[test]
     fn test_media_type_from_extension() {
         assert!(MediaType::from_extension("png") == MediaType::Png);
@@ -845,4 +1595,437 @@ mod tests {
         let header = reader.header();
         assert!(header.flags & BBF_PETRIFICATION_FLAG != 0);
     }
+
+    fn build_editable_book(path: &Path) {
+        let mut builder = BbfBuilder::with_defaults(path).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 2000], MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&[2u8; 3000], MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_section("Chapter 1", 0, None);
+        builder.add_section("Chapter 2", 1, None);
+        builder.add_metadata("Title", "Tpyo", None);
+        builder.add_metadata("Translator", "Bob", Some("Chapter 2"));
+        builder.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_open_existing_appends_pages_and_sections() {
+        let temp_output = NamedTempFile::new().unwrap();
+        build_editable_book(temp_output.path());
+
+        let mut editor = BbfBuilder::open_existing(temp_output.path()).unwrap();
+        assert!(editor.page_count() == 2);
+        assert!(editor.asset_count() == 2);
+
+        editor
+            .add_page_bytes(&[3u8; 500], MediaType::Png, 0, 0)
+            .unwrap();
+        editor
+            .add_page_bytes(&[1u8; 2000], MediaType::Png, 0, 0)
+            .unwrap();
+        editor.add_section("Chapter 3", 2, None);
+        editor.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+        assert!(reader.page_count() == 4);
+        assert!(reader.asset_count() == 3);
+
+        let assets = reader.assets().unwrap();
+        assert!(*reader.get_asset_data(&assets[0]).unwrap() == [1u8; 2000]);
+        assert!(*reader.get_asset_data(&assets[1]).unwrap() == [2u8; 3000]);
+        assert!(*reader.get_asset_data(&assets[2]).unwrap() == [3u8; 500]);
+
+        let sections = reader.sections().unwrap();
+        assert!(sections.len() == 3);
+        assert!(reader.get_string(sections[2].section_title_offset).unwrap() == "Chapter 3");
+    }
+
+    #[test]
+    fn test_open_existing_edits_and_removes_metadata() {
+        let temp_output = NamedTempFile::new().unwrap();
+        build_editable_book(temp_output.path());
+
+        let mut editor = BbfBuilder::open_existing(temp_output.path()).unwrap();
+        editor.set_metadata("Title", "Typo", None);
+        assert!(editor.remove_metadata("Translator", None) == 0);
+        assert!(editor.remove_metadata("Translator", Some("Chapter 2")) == 1);
+        assert!(editor.remove_section("Chapter 1") == 1);
+        editor.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+
+        let meta = reader.metadata().unwrap();
+        assert!(meta.len() == 1);
+        assert!(reader.get_string(meta[0].key_offset).unwrap() == "Title");
+        assert!(reader.get_string(meta[0].value_offset).unwrap() == "Typo");
+
+        let sections = reader.sections().unwrap();
+        assert!(sections.len() == 1);
+        assert!(reader.get_string(sections[0].section_title_offset).unwrap() == "Chapter 2");
+
+        let pool_size =
+            unsafe { std::ptr::addr_of!(reader.footer().string_pool_size).read_unaligned() };
+        assert!(pool_size == ("Chapter 2\0Title\0Typo\0".len() as u64));
+    }
+
+    #[test]
+    fn test_open_existing_rejects_petrified_file() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let builder = BbfBuilder::new(temp_output.path(), 12, 16, BBF_PETRIFICATION_FLAG).unwrap();
+        builder.finalize().unwrap();
+
+        let result = BbfBuilder::open_existing(temp_output.path());
+        assert!(matches!(result, Err(BbfError::Petrified)));
+    }
+
+    #[test]
+    fn test_force_open_existing_edits_petrified_file() {
+        let temp_output = NamedTempFile::new().unwrap();
+        build_editable_book(temp_output.path());
+
+        let mut editor = BbfBuilder::open_existing(temp_output.path()).unwrap();
+        editor.petrify();
+        editor.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.is_petrified());
+        assert!(reader.verify_integrity().unwrap());
+        drop(reader);
+
+        assert!(BbfBuilder::open_existing(temp_output.path()).is_err());
+
+        let mut editor = BbfBuilder::force_open_existing(temp_output.path()).unwrap();
+        assert!(editor.header_flags() & BBF_PETRIFICATION_FLAG != 0);
+        editor.set_header_flags(editor.header_flags() & !BBF_PETRIFICATION_FLAG);
+        editor.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(!reader.is_petrified());
+        assert!(reader.page_count() == 2);
+    }
+
+    #[test]
+    fn test_open_existing_restores_file_when_not_finalized() {
+        let temp_output = NamedTempFile::new().unwrap();
+        build_editable_book(temp_output.path());
+        let original = std::fs::read(temp_output.path()).unwrap();
+
+        {
+            let mut editor = BbfBuilder::open_existing(temp_output.path()).unwrap();
+            editor
+                .add_page_bytes(&[9u8; 10000], MediaType::Png, 0, 0)
+                .unwrap();
+            editor.add_metadata("Author", "Someone", None);
+        }
+
+        assert!(std::fs::read(temp_output.path()).unwrap() == original);
+    }
+
+    #[test]
+    fn test_open_existing_leaves_file_readable_if_edit_dies() {
+        let temp_output = NamedTempFile::new().unwrap();
+        build_editable_book(temp_output.path());
+
+        let mut editor = BbfBuilder::open_existing(temp_output.path()).unwrap();
+        editor
+            .add_page_bytes(&[9u8; 10000], MediaType::Png, 0, 0)
+            .unwrap();
+        editor.writer.flush().unwrap();
+        // a killed process never runs the destructor
+        std::mem::forget(editor);
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+        assert!(reader.page_count() == 2);
+    }
+
+    #[test]
+    fn test_open_existing_reopens_edited_file() {
+        let temp_output = NamedTempFile::new().unwrap();
+        build_editable_book(temp_output.path());
+
+        for byte in [7u8, 8] {
+            let mut editor = BbfBuilder::open_existing(temp_output.path()).unwrap();
+            editor
+                .add_page_bytes(&[byte; 700], MediaType::Png, 0, 0)
+                .unwrap();
+            editor.finalize().unwrap();
+        }
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+        assert!(reader.validate_deep().is_clean());
+        assert!(reader.page_count() == 4);
+        let assets = reader.assets().unwrap();
+        assert!(*reader.get_asset_data(&assets[3]).unwrap() == [8u8; 700]);
+    }
+
+    #[test]
+    fn test_remove_section_removes_nested_sections_and_their_metadata() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_section("Part 1", 0, None);
+        builder.add_section("Chapter 1", 0, Some("Part 1"));
+        builder.add_section("Scene", 0, Some("Chapter 1"));
+        builder.add_section("Notes", 0, Some("Part 1"));
+        builder.add_section("Part 2", 0, None);
+        builder.add_section("Notes", 0, Some("Part 2"));
+        builder.add_metadata("Title", "Book", None);
+        builder.add_metadata("Translator", "Bob", Some("Chapter 1"));
+        builder.add_metadata("Editor", "Eve", Some("Notes"));
+        builder.add_metadata("Translator", "Ann", Some("Part 2"));
+
+        assert!(builder.remove_section("Part 1") == 4);
+        builder.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        let titles: Vec<_> = reader
+            .sections()
+            .unwrap()
+            .iter()
+            .map(|section| reader.get_string(section.section_title_offset).unwrap())
+            .collect();
+        assert!(titles == ["Part 2", "Notes"]);
+
+        let meta = reader.metadata_map().unwrap();
+        assert!(meta.get("Title") == Some("Book"));
+        assert!(meta.get_in_section("Chapter 1", "Translator").is_none());
+        assert!(meta.get_in_section("Notes", "Editor") == Some("Eve"));
+        assert!(meta.get_in_section("Part 2", "Translator") == Some("Ann"));
+        assert!(reader.validate_deep().is_clean());
+    }
+
+    #[test]
+    fn test_open_existing_reuses_the_old_index_space() {
+        let temp_output = NamedTempFile::new().unwrap();
+        build_editable_book(temp_output.path());
+        let original_len = std::fs::metadata(temp_output.path()).unwrap().len();
+
+        for title in ["Short", "A much longer title than before", "Short"] {
+            let mut editor = BbfBuilder::open_existing(temp_output.path()).unwrap();
+            editor.set_metadata("Title", title, None);
+            editor.finalize().unwrap();
+
+            let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+            assert!(reader.verify_integrity().unwrap());
+            assert!(reader.validate_deep().is_clean());
+            assert!(reader.metadata_map().unwrap().get("Title") == Some(title));
+        }
+
+        let len = std::fs::metadata(temp_output.path()).unwrap().len();
+        // "Short" is one byte longer than the "Tpyo" it replaced
+        assert!(len.checked_sub(original_len) == Some(1));
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct ReadingProgress {
+        page: u64,
+        total: u64,
+    }
+
+    impl ExpansionRecord for ReadingProgress {
+        const KIND: u64 = 0xfeed_0001;
+        const VERSION: u64 = 1;
+
+        fn to_payload(&self) -> [u64; 8] {
+            [self.page, self.total, 0, 0, 0, 0, 0, 0]
+        }
+
+        fn from_payload(version: u64, payload: [u64; 8]) -> Option<Self> {
+            let [page, total, ..] = payload;
+            (version == 1).then_some(Self { page, total })
+        }
+    }
+
+    #[test]
+    fn test_expansion_records_roundtrip() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_expansion(&ReadingProgress { page: 3, total: 10 });
+        builder.add_expansion_entry(Expansion::new(0xdead, 1, [7; 8], 0));
+        builder.add_expansion(&ReadingProgress { page: 5, total: 10 });
+        assert!(builder.expansion_count() == 3);
+        builder.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+        assert!(reader.expansions().unwrap().len() == 3);
+
+        let records = reader.expansion_records::<ReadingProgress>().unwrap();
+        assert!(
+            records
+                == vec![
+                    ReadingProgress { page: 3, total: 10 },
+                    ReadingProgress { page: 5, total: 10 },
+                ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_required_expansion_is_rejected() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder.add_expansion_entry(Expansion::new(
+            0xdead,
+            1,
+            [0; 8],
+            BBF_EXPANSION_REQUIRED_FLAG,
+        ));
+        builder.finalize().unwrap();
+
+        let result = crate::BbfReader::open(temp_output.path());
+        assert!(matches!(
+            result,
+            Err(BbfError::UnsupportedExpansion { kind: 0xdead })
+        ));
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Watermark(u64);
+
+    impl ExpansionRecord for Watermark {
+        const KIND: u64 = 0xfeed_0002;
+        const VERSION: u64 = 1;
+        const REQUIRED: bool = true;
+
+        fn to_payload(&self) -> [u64; 8] {
+            [self.0, 0, 0, 0, 0, 0, 0, 0]
+        }
+
+        fn from_payload(version: u64, payload: [u64; 8]) -> Option<Self> {
+            (version == 1).then_some(Self(payload[0]))
+        }
+    }
+
+    #[test]
+    fn test_required_expansion_opens_when_kind_is_known() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder.add_expansion(&Watermark(42));
+        builder.finalize().unwrap();
+
+        assert!(crate::BbfReader::open(temp_output.path()).is_err());
+
+        let reader =
+            crate::BbfReader::open_with_expansions(temp_output.path(), &[Watermark::KIND]).unwrap();
+        assert!(reader.expansion_records::<Watermark>().unwrap() == vec![Watermark(42)]);
+
+        let data = std::fs::read(temp_output.path()).unwrap();
+        assert!(crate::BbfReader::from_storage_with_expansions(data, &[Watermark::KIND]).is_ok());
+    }
+
+    #[test]
+    fn test_open_existing_preserves_expansions() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder.add_expansion(&ReadingProgress { page: 1, total: 2 });
+        builder.add_expansion_entry(Expansion::new(0xdead, 4, [9; 8], 0));
+        builder.finalize().unwrap();
+
+        let mut editor = BbfBuilder::open_existing(temp_output.path()).unwrap();
+        assert!(editor.expansion_count() == 2);
+        assert!(editor.remove_expansions(ReadingProgress::KIND) == 1);
+        editor.add_expansion(&ReadingProgress { page: 2, total: 2 });
+        editor.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+
+        let expansions = reader.expansions().unwrap();
+        assert!(expansions.len() == 2);
+        assert!(expansions[0].kind() == 0xdead);
+        assert!(expansions[0].version() == 4);
+        assert!(expansions[0].payload() == [9; 8]);
+        assert!(
+            reader.expansion_records::<ReadingProgress>().unwrap()
+                == vec![ReadingProgress { page: 2, total: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_set_thumbnail_replaces_previous_record() {
+        let temp_output = NamedTempFile::new().unwrap();
+        build_editable_book(temp_output.path());
+
+        let mut editor = BbfBuilder::open_existing(temp_output.path()).unwrap();
+        editor
+            .set_thumbnail(&[4u8; 16], MediaType::Png, 2, 2)
+            .unwrap();
+        editor
+            .set_thumbnail(&[5u8; 16], MediaType::Png, 2, 2)
+            .unwrap();
+        assert!(editor.expansion_count() == 1);
+        assert!(editor.page_count() == 2);
+        editor.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        assert!(*reader.thumbnail().unwrap().unwrap().1 == [5u8; 16]);
+    }
+
+    #[test]
+    fn test_thumbnail_and_page_with_same_bytes_keep_separate_assets() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[6u8; 32], MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .set_thumbnail(&[6u8; 32], MediaType::Png, 2, 2)
+            .unwrap();
+        builder
+            .set_thumbnail(&[7u8; 32], MediaType::Png, 2, 2)
+            .unwrap();
+        builder
+            .add_page_bytes(&[7u8; 32], MediaType::Png, 0, 0)
+            .unwrap();
+        assert!(builder.asset_count() == 4);
+        builder.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        let assets = reader.assets().unwrap();
+        let pages = reader.pages().unwrap();
+        for page in pages {
+            assert!({ assets[page.asset_index as usize].flags } & BBF_ASSET_THUMBNAIL_FLAG == 0);
+        }
+        assert!({ assets[1].flags } & BBF_ASSET_THUMBNAIL_FLAG != 0);
+    }
+
+    #[cfg(feature = "thumbnail")]
+    #[test]
+    fn test_generate_thumbnail() {
+        let mut cover = Vec::new();
+        image::DynamicImage::new_rgb8(600, 900)
+            .write_to(
+                &mut std::io::Cursor::new(&mut cover),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&cover, MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .generate_thumbnail(&cover, DEFAULT_THUMBNAIL_SIZE)
+            .unwrap();
+        builder.finalize().unwrap();
+
+        let reader = crate::BbfReader::open(temp_output.path()).unwrap();
+        let record = reader.thumbnail_record().unwrap().unwrap();
+        assert!(record.width == 171);
+        assert!(record.height == 256);
+        assert!(reader.thumbnail().unwrap().unwrap().0 == MediaType::Jpg);
+    }
 }
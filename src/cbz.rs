@@ -0,0 +1,280 @@
+use {
+    crate::{Book, ComicInfo, Result, SectionView, Storage},
+    std::{
+        fs::File,
+        io::{BufWriter, Seek, Write},
+        path::{Path, PathBuf},
+    },
+    zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions},
+};
+
+/// writes a book, or one of its sections, as a CBZ archive
+///
+/// pages are stored uncompressed in reading order as `p0001.png`, `p0002.jpg`, ... (padded to
+/// at least four digits so they sort correctly by name), preceded by a `ComicInfo.xml`
+/// generated with [`ComicInfo::from_book`].
+///
+/// # Arguments
+///
+/// * `book` - the book to export
+/// * `section` - the section to export, or `None` for every page
+/// * `writer` - where to write the archive
+///
+/// # Returns
+///
+/// the writer, after the archive has been finished
+///
+/// # Errors
+///
+/// returns an error if a page's data can't be read or the archive can't be written
+#[macroni_n_cheese::mathinator2000]
+pub fn write_cbz<S: Storage, W: Write + Seek>(
+    book: &Book<S>,
+    section: Option<SectionView<'_>>,
+    writer: W,
+) -> Result<W> {
+    let info = ComicInfo::from_book(book, section);
+    let range = section.map_or(0..book.page_count(), |section| section.pages());
+    let width = info.pages.len().to_string().len().max(4);
+
+    let mut zip = ZipWriter::new(writer);
+
+    zip.start_file(ComicInfo::FILE_NAME, SimpleFileOptions::default())?;
+    zip.write_all(info.to_xml().as_bytes())?;
+
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (i, page) in book
+        .pages()
+        .skip(usize::try_from(range.start).unwrap_or(usize::MAX))
+        .take_while(|page| range.contains(&page.index))
+        .enumerate()
+    {
+        let number = i + 1;
+        let name = format!("p{number:0width$}{}", page.media_type.as_extension());
+        zip.start_file(name, stored)?;
+        zip.write_all(&page.data()?)?;
+    }
+
+    Ok(zip.finish()?)
+}
+
+/// exports a whole book as a CBZ file
+///
+/// # Arguments
+///
+/// * `book` - the book to export
+/// * `output` - path of the CBZ file to create, replacing any existing file
+///
+/// # Errors
+///
+/// returns an error if the file can't be created or [`write_cbz`] fails
+pub fn to_cbz<S: Storage, P: AsRef<Path>>(book: &Book<S>, output: P) -> Result<()> {
+    let file = BufWriter::new(File::create(output)?);
+    write_cbz(book, None, file)?.into_inner()?;
+    Ok(())
+}
+
+/// exports every top-level section of a book as its own CBZ file
+///
+/// files are named `<n> - <title>.cbz` after the position and title of the section, with
+/// characters that aren't allowed in file names replaced by `_`. pages that come before the
+/// first top-level section belong to no archive.
+///
+/// # Arguments
+///
+/// * `book` - the book to export
+/// * `output_dir` - the directory to write the archives into, created if missing
+///
+/// # Returns
+///
+/// the paths of the written archives in section order; empty if the book has no sections
+///
+/// # Errors
+///
+/// returns an error if the directory or a file can't be created or [`write_cbz`] fails
+#[macroni_n_cheese::mathinator2000]
+pub fn to_cbz_split<S: Storage, P: AsRef<Path>>(
+    book: &Book<S>,
+    output_dir: P,
+) -> Result<Vec<PathBuf>> {
+    let output_dir = output_dir.as_ref();
+    std::fs::create_dir_all(output_dir)?;
+
+    let roots: Vec<_> = book.root_sections().collect();
+    let width = roots.len().to_string().len();
+    let mut written = Vec::with_capacity(roots.len());
+
+    for (i, section) in roots.into_iter().enumerate() {
+        let number = i + 1;
+        let title = file_name_safe(section.title());
+        let path = output_dir.join(format!("{number:0width$} - {title}.cbz"));
+
+        let file = BufWriter::new(File::create(&path)?);
+        write_cbz(book, Some(section), file)?.into_inner()?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// replaces characters that aren't allowed in file names on common platforms
+fn file_name_safe(title: &str) -> String {
+    let safe: String = title
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    if safe.is_empty() {
+        "Untitled".to_string()
+    } else {
+        safe
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {
+        super::*,
+        crate::{BbfBuilder, format::BBF_PAGE_FRONT_COVER_FLAG, types::MediaType},
+        assert2::check as assert,
+        std::io::{Cursor, Read},
+        tempfile::NamedTempFile,
+        zip::ZipArchive,
+    };
+
+    fn build_book() -> NamedTempFile {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 64], MediaType::Png, BBF_PAGE_FRONT_COVER_FLAG, 0)
+            .unwrap();
+        for byte in [2u8, 3, 4, 5] {
+            builder
+                .add_page_bytes(&[byte; 64], MediaType::Jpg, 0, 0)
+                .unwrap();
+        }
+        builder.add_section("Volume 1", 0, None);
+        builder.add_section("Chapter 1", 0, Some("Volume 1"));
+        builder.add_section("Chapter 2", 2, Some("Volume 1"));
+        builder.add_section("Volume 2: The End", 3, None);
+        builder.add_metadata("Title", "Saga", None);
+        builder.add_metadata("Author", "Jane Doe", None);
+        builder.add_metadata("Author", "John Roe", None);
+        builder.add_metadata("Date", "2020-07-01", None);
+        builder.add_metadata("Colorist", "Dee", Some("Volume 2: The End"));
+        builder.add_metadata("Converted-From", "CBZ", None);
+        builder.finalize().unwrap();
+        temp_output
+    }
+
+    fn entries(data: Vec<u8>) -> Vec<(String, Vec<u8>)> {
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut file = archive.by_index(i).unwrap();
+                let mut data = Vec::new();
+                file.read_to_end(&mut data).unwrap();
+                (file.name().to_string(), data)
+            })
+            .collect()
+    }
+
+    fn comic_info(entries: &[(String, Vec<u8>)]) -> ComicInfo {
+        let xml = &entries
+            .iter()
+            .find(|(name, _)| name == "ComicInfo.xml")
+            .unwrap()
+            .1;
+        ComicInfo::parse(std::str::from_utf8(xml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_write_cbz_stores_pages_in_order_with_comic_info() {
+        let file = build_book();
+        let book = Book::open(file.path()).unwrap();
+
+        let entries = entries(
+            write_cbz(&book, None, Cursor::new(Vec::new()))
+                .unwrap()
+                .into_inner(),
+        );
+        let names: Vec<_> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert!(
+            names
+                == [
+                    "ComicInfo.xml",
+                    "p0001.png",
+                    "p0002.jpg",
+                    "p0003.jpg",
+                    "p0004.jpg",
+                    "p0005.jpg",
+                ]
+        );
+        assert!(entries[3].1 == [3u8; 64]);
+
+        let info = comic_info(&entries);
+        let metadata: Vec<_> = info
+            .metadata
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert!(
+            metadata
+                == [
+                    ("Title", "Saga"),
+                    ("Author", "Jane Doe, John Roe"),
+                    ("Date", "2020-07-01"),
+                ]
+        );
+        assert!(info.pages.len() == 5);
+        assert!(info.page_flags(0) == BBF_PAGE_FRONT_COVER_FLAG);
+        assert!(
+            info.bookmarks().collect::<Vec<_>>()
+                == [(0, "Volume 1"), (2, "Chapter 2"), (3, "Volume 2: The End")]
+        );
+    }
+
+    #[test]
+    fn test_to_cbz_split_writes_one_archive_per_top_level_section() {
+        let file = build_book();
+        let book = Book::open(file.path()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let written = to_cbz_split(&book, dir.path()).unwrap();
+        let names: Vec<_> = written
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert!(names == ["1 - Volume 1.cbz", "2 - Volume 2_ The End.cbz"]);
+
+        let first = entries(std::fs::read(&written[0]).unwrap());
+        assert!(first.len() == 4);
+        let info = comic_info(&first);
+        assert!(info.bookmarks().collect::<Vec<_>>() == [(0, "Chapter 1"), (2, "Chapter 2")]);
+
+        let second = entries(std::fs::read(&written[1]).unwrap());
+        assert!(second[1] == ("p0001.jpg".to_string(), vec![4u8; 64]));
+        let info = comic_info(&second);
+        let metadata: Vec<_> = info
+            .metadata
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert!(
+            metadata
+                == [
+                    ("Title", "Volume 2: The End"),
+                    ("Series", "Saga"),
+                    ("Author", "Jane Doe, John Roe"),
+                    ("Colorist", "Dee"),
+                    ("Date", "2020-07-01"),
+                ]
+        );
+    }
+}
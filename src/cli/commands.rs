@@ -1,7 +1,14 @@
 pub mod complete;
+mod convert;
 pub mod create;
 pub mod extract;
-pub mod from_cbz;
+pub mod from_archive;
+pub mod from_epub;
+pub mod from_pdf;
 pub mod info;
+pub mod petrify;
 pub mod read;
+pub mod repair;
+pub mod to_cbz;
+pub mod to_epub;
 pub mod verify;
@@ -0,0 +1,161 @@
+use {
+    boundbook::{BbfBuilder, keys::canonical_key},
+    std::{borrow::Borrow, collections::HashMap, hash::Hash},
+};
+
+/// parses a `Key:Value[:Parent]` metadata argument
+pub fn parse_metadata(s: &str) -> Option<(String, String, Option<String>)> {
+    let parts: Vec<&str> = s.splitn(3, ':').collect();
+    if parts.len() >= 2 {
+        let parent = if parts.len() >= 3 {
+            Some(parts[2].trim().to_string())
+        } else {
+            None
+        };
+        Some((
+            parts[0].trim().to_string(),
+            parts[1].trim().to_string(),
+            parent,
+        ))
+    } else {
+        None
+    }
+}
+
+/// the metadata given on the command line of an import
+///
+/// it wins over the metadata the imported file carries: a book-wide key set on the command
+/// line is never also taken from the file
+pub struct UserMetadata {
+    /// the book-wide keys that were set, canonicalized
+    keys: Vec<String>,
+}
+
+impl UserMetadata {
+    /// parses every `--meta` argument and adds it to the book
+    pub fn add(builder: &mut BbfBuilder, metadata: &[String]) -> Self {
+        let mut keys = Vec::new();
+        for meta_str in metadata {
+            if let Some((key, value, parent)) = parse_metadata(meta_str) {
+                builder.add_metadata(&key, &value, parent.as_deref());
+                if parent.is_none() {
+                    keys.push(canonical_key(&key).to_string());
+                }
+            }
+        }
+
+        Self { keys }
+    }
+
+    /// adds metadata read from the imported file, unless the command line already set its key
+    pub fn add_from_source(
+        &self,
+        builder: &mut BbfBuilder,
+        key: &str,
+        value: &str,
+        parent: Option<&str>,
+    ) {
+        if parent.is_none() && self.keys.iter().any(|k| k == canonical_key(key)) {
+            return;
+        }
+        builder.add_metadata(key, value, parent);
+    }
+}
+
+/// maps the pages (or documents) of an imported file to the pages of the book
+///
+/// pages without an image are skipped, so a section pointing at one starts on the next page that
+/// is added
+pub struct PageMap<K> {
+    /// the book page each source page starts on
+    first_pages: HashMap<K, u64>,
+    /// the number of pages added so far
+    added: u64,
+}
+
+impl<K: Hash + Eq> PageMap<K> {
+    pub fn new() -> Self {
+        Self {
+            first_pages: HashMap::new(),
+            added: 0,
+        }
+    }
+
+    /// records that a source page starts at the next page added, if it wasn't seen before
+    pub fn visit(&mut self, key: K) {
+        self.first_pages.entry(key).or_insert(self.added);
+    }
+
+    /// counts a page added to the book
+    pub const fn page_added(&mut self) {
+        self.added = self.added.saturating_add(1);
+    }
+
+    /// the number of pages added to the book
+    pub const fn added(&self) -> u64 {
+        self.added
+    }
+
+    /// the book page a section pointing at a source page starts on, if any page follows it
+    pub fn section_start<Q>(&self, key: &Q) -> Option<u64>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.first_pages
+            .get(key)
+            .copied()
+            .filter(|&page| page < self.added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {
+        super::*,
+        assert2::check as assert,
+        boundbook::{BbfReader, types::MediaType},
+    };
+
+    #[test]
+    fn test_command_line_metadata_wins_over_the_source() {
+        let output = tempfile::NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(output.path()).unwrap();
+        builder
+            .add_page_bytes(&[3u8; 64], MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_section("Chapter 1", 0, None);
+
+        let user = UserMetadata::add(&mut builder, &["title:Mine".into(), "bad".into()]);
+        user.add_from_source(&mut builder, "Title", "Theirs", None);
+        user.add_from_source(&mut builder, "Title", "Chapter", Some("Chapter 1"));
+        user.add_from_source(&mut builder, "Author", "Them", None);
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(output.path()).unwrap();
+        let map = reader.metadata_map().unwrap();
+        assert!(map.get_all("Title").collect::<Vec<_>>() == ["Mine"]);
+        assert!(map.get_in_section("Chapter 1", "Title") == Some("Chapter"));
+        assert!(map.get("Author") == Some("Them"));
+    }
+
+    #[test]
+    fn test_sections_on_skipped_pages_start_on_the_next_page() {
+        let mut pages = PageMap::new();
+        pages.visit("cover");
+        pages.page_added();
+        pages.visit("blank");
+        pages.visit("chapter");
+        pages.visit("cover");
+        pages.page_added();
+        pages.visit("afterword");
+
+        assert!(pages.added() == 2);
+        assert!(pages.section_start("cover") == Some(0));
+        assert!(pages.section_start("blank") == Some(1));
+        assert!(pages.section_start("chapter") == Some(1));
+        assert!(pages.section_start("afterword").is_none());
+        assert!(pages.section_start("missing").is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use {
+    crate::cli::sort::PageSort,
     boundbook::prelude::*,
     clap::Args,
     hashbrown::HashMap,
@@ -6,7 +7,9 @@ use {
     miette::{Context, IntoDiagnostic, miette},
     std::{
         fs,
+        io::Read,
         path::{Path, PathBuf},
+        time::SystemTime,
     },
 };
 
@@ -49,9 +52,33 @@ pub struct CreateArgs {
     #[arg(short = 'v', long)]
     variable_ream_size: bool,
 
+    /// How to order pages that aren't placed by the order file
+    #[arg(long, value_enum, default_value_t = PageSort::Natural)]
+    sort: PageSort,
+
     /// Auto-detect subdirectories with images and create sections from directory names
     #[arg(short = 'd', long)]
     auto_detect_sections: bool,
+
+    /// Warn when an image's file extension doesn't match its contents
+    #[arg(long)]
+    warn_media_mismatch: bool,
+
+    /// Overwrite the output file even if it is petrified
+    #[arg(long)]
+    force: bool,
+
+    /// Embed a thumbnail generated from the first page
+    #[arg(long)]
+    thumbnail: bool,
+
+    /// Embed a thumbnail generated from this image instead of the first page
+    #[arg(long, value_name = "IMAGE")]
+    thumbnail_from: Option<PathBuf>,
+
+    /// Maximum thumbnail width/height in pixels
+    #[arg(long, default_value_t = DEFAULT_THUMBNAIL_SIZE)]
+    thumbnail_size: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +87,7 @@ struct PagePlan {
     filename: String,
     order: i32,
     section: Option<String>,
+    modified: Option<SystemTime>,
 }
 
 impl PagePlan {
@@ -69,12 +97,14 @@ impl PagePlan {
             .and_then(|n| n.to_str())
             .unwrap_or("")
             .to_string();
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
 
         Self {
             path,
             filename,
             order: 0,
             section,
+            modified,
         }
     }
 }
@@ -94,16 +124,16 @@ struct MetadataRequest {
     parent: Option<String>,
 }
 
-fn compare_pages(a: &PagePlan, b: &PagePlan) -> std::cmp::Ordering {
+fn compare_pages(a: &PagePlan, b: &PagePlan, sort: PageSort) -> std::cmp::Ordering {
     use std::cmp::Ordering;
 
     match (&a.section, &b.section) {
-        (Some(a_sec), Some(b_sec)) if a_sec != b_sec => a_sec.cmp(b_sec),
+        (Some(a_sec), Some(b_sec)) if a_sec != b_sec => sort.compare_names(a_sec, b_sec),
         _ => match (a.order, b.order) {
             (a_ord, b_ord) if a_ord > 0 && b_ord > 0 => a_ord.cmp(&b_ord),
             (a_ord, _) if a_ord > 0 => Ordering::Less,
             (_, b_ord) if b_ord > 0 => Ordering::Greater,
-            _ => a.filename.cmp(&b.filename),
+            _ => sort.compare((&a.filename, a.modified), (&b.filename, b.modified)),
         },
     }
 }
@@ -306,12 +336,24 @@ fn collect_image_files(
     Ok(manifest)
 }
 
+/// Check if a file is an image, by extension or (failing that) by its leading magic bytes
 fn is_image_file(path: &Path) -> bool {
-    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-        !matches!(MediaType::from_extension(ext), MediaType::Unknown)
-    } else {
-        false
+    let by_extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(MediaType::from_extension)
+        .unwrap_or(MediaType::Unknown);
+
+    if by_extension != MediaType::Unknown {
+        return true;
     }
+
+    let mut head = [0u8; 32];
+    let read = fs::File::open(path)
+        .and_then(|mut file| file.read(&mut head))
+        .unwrap_or(0);
+
+    MediaType::from_bytes(&head[..read]) != MediaType::Unknown
 }
 
 fn resolve_section_target(
@@ -342,6 +384,8 @@ fn resolve_section_target(
 }
 
 pub fn execute(args: CreateArgs) -> Result<()> {
+    super::petrify::ensure_not_petrified(&args.output, args.force)?;
+
     let order_map = if let Some(path) = &args.order {
         load_order_file(path)?
     } else {
@@ -367,7 +411,7 @@ pub fn execute(args: CreateArgs) -> Result<()> {
         .collect();
 
     let mut manifest = collect_image_files(&args.inputs, &order_map, args.auto_detect_sections)?;
-    manifest.sort_by(compare_pages);
+    manifest.sort_by(|a, b| compare_pages(a, b, args.sort));
 
     let flags = if args.variable_ream_size {
         BBF_VARIABLE_REAM_SIZE_FLAG
@@ -397,6 +441,16 @@ pub fn execute(args: CreateArgs) -> Result<()> {
             .add_page(&page.path, 0, 0)
             .into_diagnostic()
             .with_context(|| format!("Failed to add page: {}", page.path.display()))?;
+        if let Some((declared, sniffed)) = builder.last_media_mismatch()
+            && args.warn_media_mismatch
+        {
+            pb.println(format!(
+                "Warning: {} is labeled as {:?} but its contents are {:?}",
+                page.path.display(),
+                declared,
+                sniffed
+            ));
+        }
         pb.inc(1);
         file_to_page.insert(page.filename.clone(), i as u64);
 
@@ -426,6 +480,22 @@ pub fn execute(args: CreateArgs) -> Result<()> {
         builder.add_metadata(&meta.key, &meta.value, meta.parent.as_deref());
     }
 
+    let thumbnail_source = match &args.thumbnail_from {
+        Some(path) => Some(path.as_path()),
+        None if args.thumbnail => manifest.first().map(|page| page.path.as_path()),
+        None => None,
+    };
+
+    if let Some(source) = thumbnail_source {
+        let data = fs::read(source)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to read thumbnail source: {}", source.display()))?;
+        builder
+            .generate_thumbnail(&data, args.thumbnail_size)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to generate thumbnail from {}", source.display()))?;
+    }
+
     builder.finalize()?;
 
     println!(
@@ -436,3 +506,113 @@ pub fn execute(args: CreateArgs) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {
+        super::*,
+        assert2::check as assert,
+        std::time::{Duration, UNIX_EPOCH},
+    };
+
+    /// a page found on disk, `age` seconds after the epoch
+    fn plan(name: &str, section: Option<&str>, order: i32, age: u64) -> PagePlan {
+        PagePlan {
+            path: PathBuf::from(name),
+            filename: name.to_string(),
+            order,
+            section: section.map(str::to_string),
+            modified: UNIX_EPOCH.checked_add(Duration::from_secs(age)),
+        }
+    }
+
+    fn sorted(sort: PageSort, mut pages: Vec<PagePlan>) -> Vec<String> {
+        pages.sort_by(|a, b| compare_pages(a, b, sort));
+        pages.into_iter().map(|page| page.filename).collect()
+    }
+
+    #[test]
+    fn test_natural_order_puts_ordered_pages_first() {
+        let pages = vec![
+            plan("page10.png", None, 0, 1),
+            plan("page2.png", None, 0, 2),
+            plan("cover.png", None, 2, 3),
+            plan("page1.png", None, 0, 4),
+            plan("credits.png", None, 1, 5),
+        ];
+
+        assert!(
+            sorted(PageSort::Natural, pages)
+                == [
+                    "credits.png",
+                    "cover.png",
+                    "page1.png",
+                    "page2.png",
+                    "page10.png"
+                ]
+        );
+    }
+
+    #[test]
+    fn test_natural_order_groups_sections() {
+        let mut pages = [
+            plan("p2.png", Some("Chapter 10"), 0, 1),
+            plan("p10.png", Some("Chapter 2"), 0, 2),
+            plan("p1.png", Some("Chapter 10"), 0, 3),
+            plan("p1.png", Some("Chapter 2"), 0, 4),
+        ];
+
+        pages.sort_by(|a, b| compare_pages(a, b, PageSort::Natural));
+        let order: Vec<_> = pages
+            .iter()
+            .map(|page| (page.section.as_deref().unwrap(), page.filename.as_str()))
+            .collect();
+
+        assert!(
+            order
+                == [
+                    ("Chapter 2", "p1.png"),
+                    ("Chapter 2", "p10.png"),
+                    ("Chapter 10", "p1.png"),
+                    ("Chapter 10", "p2.png"),
+                ]
+        );
+    }
+
+    #[test]
+    fn test_mtime_order_sorts_by_age_within_sections() {
+        let pages = vec![
+            plan("a.png", Some("Part 2"), 0, 1),
+            plan("c.png", Some("Part 1"), 0, 5),
+            plan("b.png", Some("Part 1"), 0, 3),
+            plan("d.png", Some("Part 1"), 0, 3),
+            plan("z.png", Some("Part 2"), 0, 0),
+        ];
+
+        assert!(sorted(PageSort::Mtime, pages) == ["b.png", "d.png", "c.png", "z.png", "a.png"]);
+    }
+
+    #[test]
+    fn test_mtime_order_puts_unknown_times_first() {
+        let mut unknown = plan("b.png", None, 0, 0);
+        unknown.modified = None;
+        let pages = vec![plan("a.png", None, 0, 1), unknown];
+
+        assert!(sorted(PageSort::Mtime, pages) == ["b.png", "a.png"]);
+    }
+
+    #[test]
+    fn test_no_order_keeps_discovery_order_after_ordered_pages() {
+        let pages = vec![
+            plan("page10.png", Some("Chapter 2"), 0, 3),
+            plan("page2.png", Some("Chapter 1"), 0, 2),
+            plan("page1.png", Some("Chapter 2"), 0, 1),
+            plan("cover.png", None, 1, 0),
+        ];
+
+        assert!(
+            sorted(PageSort::None, pages) == ["cover.png", "page10.png", "page2.png", "page1.png"]
+        );
+    }
+}
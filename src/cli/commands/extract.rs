@@ -1,8 +1,11 @@
 use {
-    boundbook::{BbfReader, Result, types::MediaType},
+    boundbook::{BbfReader, Book, Result, SectionTree},
     clap::Args,
     miette::{Context, IntoDiagnostic, miette},
-    std::{fs, path::PathBuf},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
 };
 
 #[derive(Args)]
@@ -26,58 +29,40 @@ pub struct ExtractArgs {
     /// Extract a specific page range (e.g., 1-10 or 5)
     #[arg(long, conflicts_with = "section")]
     range: Option<String>,
+
+    /// Extract only the embedded thumbnail
+    #[arg(long, conflicts_with_all = ["section", "range"])]
+    thumbnail: bool,
 }
 
 #[macroni_n_cheese::mathinator2000]
 fn extract_section_range(
-    reader: &BbfReader,
+    tree: &SectionTree,
     section_name: &str,
     until_pattern: Option<&str>,
+    page_count: u64,
 ) -> Result<(usize, usize, String)> {
-    let sections = reader.sections()?;
-
-    let section_idx = sections
-        .iter()
-        .position(|s| reader.get_string(s.section_title_offset).unwrap_or("") == section_name)
+    let section = tree
+        .find(section_name)
         .ok_or_else(|| miette!("Section '{}' not found", section_name))?;
 
-    let start = sections[section_idx].section_start_index as usize;
-
-    let end = if let Some(pattern) = until_pattern {
-        find_section_end(reader, section_idx, Some(pattern))?
-    } else {
-        find_section_end(reader, section_idx, None)?
+    let end = match until_pattern {
+        Some(pattern) => tree
+            .iter()
+            .skip(section.index() + 1)
+            .find(|other| other.title().contains(pattern) && other.start() >= section.start())
+            .map_or(page_count, |other| other.start()),
+        None => section.end(),
     };
 
-    let start = start + 1;
-    let description = format!("Section '{}' (pages {}-{})", section_name, start, end);
+    let start = section.start() as usize;
+    let end = end as usize;
+    let first_page = start + 1;
+    let description = format!("Section '{}' (pages {}-{})", section_name, first_page, end);
 
     Ok((start, end, description))
 }
 
-#[macroni_n_cheese::mathinator2000]
-fn find_section_end(
-    reader: &BbfReader,
-    current_idx: usize,
-    pattern: Option<&str>,
-) -> Result<usize> {
-    let sections = reader.sections()?;
-    let start_page = sections[current_idx].section_start_index;
-
-    for section in sections.iter().skip(current_idx + 1) {
-        if let Some(pat) = pattern {
-            let title = reader.get_string(section.section_title_offset)?;
-            if title.contains(pat) {
-                return Ok(section.section_start_index as usize);
-            }
-        } else if section.section_start_index > start_page {
-            return Ok(section.section_start_index as usize);
-        }
-    }
-
-    Ok(reader.page_count() as usize)
-}
-
 #[macroni_n_cheese::mathinator2000]
 fn parse_page_range(range: &str, max_pages: usize) -> Result<(usize, usize)> {
     if let Some((start_str, end_str)) = range.split_once('-') {
@@ -120,11 +105,27 @@ fn parse_page_range(range: &str, max_pages: usize) -> Result<(usize, usize)> {
     }
 }
 
+fn extract_thumbnail(reader: &BbfReader, output: &Path) -> Result<()> {
+    let (media_type, data) = reader
+        .thumbnail()?
+        .ok_or_else(|| miette!("This book has no embedded thumbnail"))?;
+
+    let output_path = output.join(format!("thumbnail{}", media_type.as_extension()));
+    fs::write(&output_path, data)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to write {}", output_path.display()))?;
+
+    println!("Extracted thumbnail to {}", output_path.display());
+
+    Ok(())
+}
+
 #[macroni_n_cheese::mathinator2000]
 pub fn execute(args: ExtractArgs) -> Result<()> {
-    let reader = BbfReader::open(&args.input)
+    let book = Book::open(&args.input)
         .into_diagnostic()
         .with_context(|| format!("Failed to open BBF file: {}", args.input.display()))?;
+    let reader = book.reader();
 
     fs::create_dir_all(&args.output)
         .into_diagnostic()
@@ -135,31 +136,38 @@ pub fn execute(args: ExtractArgs) -> Result<()> {
             )
         })?;
 
-    let pages = reader.pages()?;
-    let assets = reader.assets()?;
+    if args.thumbnail {
+        return extract_thumbnail(reader, &args.output);
+    }
+
+    let page_count = book.page_count() as usize;
 
     let (start, end, description) = if let Some(section_name) = &args.section {
-        extract_section_range(&reader, section_name, args.until.as_deref())?
+        extract_section_range(
+            book.section_tree(),
+            section_name,
+            args.until.as_deref(),
+            book.page_count(),
+        )?
     } else if let Some(range_str) = &args.range {
-        let (start, end) = parse_page_range(range_str, pages.len())?;
-        let start = start + 1;
-        (start, end, format!("Pages {}-{}", start, end))
+        let (start, end) = parse_page_range(range_str, page_count)?;
+        let first_page = start + 1;
+        (start, end, format!("Pages {}-{}", first_page, end))
     } else {
-        (0, pages.len(), "All pages".to_string())
+        (0, page_count, "All pages".to_string())
     };
 
     println!("Extracting: {}", description);
     println!("Output directory: {}", args.output.display());
 
-    for (i, page) in pages.iter().enumerate().take(end).skip(start) {
-        let asset = &assets[page.asset_index as usize];
-        let media_type = MediaType::from(asset.media_type);
-        let extension = media_type.as_extension();
+    for page in book.pages().take(end).skip(start) {
+        let i = page.index as usize;
+        let extension = page.media_type.as_extension();
         let ri = i + 1;
         let filename = format!("p{:04}{}", ri, extension);
         let output_path = args.output.join(&filename);
 
-        let data = reader.get_asset_data(asset)?;
+        let data = page.data()?;
         fs::write(&output_path, data)
             .into_diagnostic()
             .with_context(|| format!("Failed to write {}", output_path.display()))?;
@@ -187,6 +195,53 @@ mod tests {
     #![allow(unused, clippy::missing_panics_doc)]
     use {super::*, assert2::check as assert};
 
+    fn section_tree() -> SectionTree {
+        let output = tempfile::NamedTempFile::new().unwrap();
+        let mut builder = boundbook::BbfBuilder::with_defaults(output.path()).unwrap();
+        for byte in 0..8u8 {
+            builder
+                .add_page_bytes(&[byte; 16], boundbook::types::MediaType::Png, 0, 0)
+                .unwrap();
+        }
+        builder.add_section("Volume 1", 0, None);
+        builder.add_section("Chapter 1", 0, Some("Volume 1"));
+        builder.add_section("Chapter 2", 3, Some("Volume 1"));
+        builder.add_section("Volume 2", 6, None);
+        builder.finalize().unwrap();
+
+        BbfReader::open(output.path())
+            .unwrap()
+            .section_tree()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_extract_section_range_covers_nested_sections() {
+        let tree = section_tree();
+
+        let (start, end, _) = extract_section_range(&tree, "Volume 1", None, 8).unwrap();
+        assert!((start, end) == (0, 6));
+
+        let (start, end, _) = extract_section_range(&tree, "Chapter 2", None, 8).unwrap();
+        assert!((start, end) == (3, 6));
+    }
+
+    #[test]
+    fn test_extract_section_range_until_pattern() {
+        let tree = section_tree();
+
+        let (start, end, _) = extract_section_range(&tree, "Chapter 1", Some("Volume"), 8).unwrap();
+        assert!((start, end) == (0, 6));
+
+        let (start, end, _) = extract_section_range(&tree, "Chapter 2", Some("Nope"), 8).unwrap();
+        assert!((start, end) == (3, 8));
+    }
+
+    #[test]
+    fn test_extract_section_range_unknown_section() {
+        assert!(extract_section_range(&section_tree(), "Chapter 9", None, 8).is_err());
+    }
+
     #[test]
     fn test_parse_page_range_single_page() {
         let (start, end) = parse_page_range("5", 10).unwrap();
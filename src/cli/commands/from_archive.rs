@@ -0,0 +1,902 @@
+use {
+    super::convert::UserMetadata,
+    crate::cli::sort::{PageSort, split_folder},
+    boundbook::{BbfBuilder, ComicInfo, Result, types::MediaType},
+    clap::Args,
+    miette::{Context, IntoDiagnostic, miette},
+    rayon::iter::{IntoParallelRefIterator, ParallelIterator},
+    std::{
+        collections::HashMap,
+        fs,
+        io::Read,
+        path::{Path, PathBuf},
+    },
+};
+
+mod cb7;
+#[cfg(feature = "rar")]
+mod cbr;
+mod cbt;
+mod cbz;
+
+/// number of pages decompressed in parallel before they're handed to the builder in order
+///
+/// bounds how many decoded pages are held in memory at once
+const DECOMPRESS_BATCH: usize = 32;
+
+#[derive(Args)]
+#[command(author = "The Motherfucking Bearodactyl")]
+pub struct FromArchiveArgs {
+    /// Input archive (CBZ, CBT, CB7 or CBR) or directory containing archives
+    input: PathBuf,
+
+    /// Output BBF file
+    #[arg(short = 'o', long)]
+    output: PathBuf,
+
+    /// Add metadata (format: Key:Value[:Parent])
+    #[arg(short = 'm', long = "meta")]
+    metadata: Vec<String>,
+
+    /// No longer has any effect: pages are read straight from the archive
+    #[arg(short = 'k', long, hide = true)]
+    keep_temp: bool,
+
+    /// Process directory of archives as chapters
+    #[arg(short = 'd', long)]
+    directory_mode: bool,
+
+    /// Overwrite the output file even if it is petrified
+    #[arg(long)]
+    force: bool,
+
+    /// Ignore ComicInfo.xml files in the archives
+    #[arg(long)]
+    no_comic_info: bool,
+
+    /// How to order the pages inside each archive
+    #[arg(long, value_enum, default_value_t = PageSort::Natural)]
+    sort: PageSort,
+}
+
+/// a file stored in an archive
+#[derive(Debug, Clone)]
+struct ArchiveEntry {
+    /// position of the entry, as understood by the backend that listed it
+    index: usize,
+    /// path of the entry inside the archive, with `/` separators
+    name: String,
+    media_type: MediaType,
+    /// modification time, only comparable with other entries of the same archive
+    modified: Option<u64>,
+}
+
+impl ArchiveEntry {
+    fn new(index: usize, name: &str, modified: Option<u64>) -> Self {
+        let name = name.replace('\\', "/");
+        let name = name.trim_start_matches("./").to_string();
+        let ext = Path::new(&name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+
+        Self {
+            index,
+            media_type: MediaType::from_extension(ext),
+            name,
+            modified,
+        }
+    }
+
+    /// checks whether the entry is a hidden file or operating system clutter
+    fn is_hidden(&self) -> bool {
+        self.name.starts_with('.') || self.name.starts_with("__MACOSX") || self.name.contains("/.")
+    }
+}
+
+/// receives the data of archive entries, in the order they were asked for
+type Sink<'a> = dyn FnMut(&ArchiveEntry, Vec<u8>) -> Result<()> + 'a;
+
+/// reads the files stored in one kind of archive
+trait ArchiveBackend {
+    /// lists every regular file in the archive, in the order they're stored
+    fn entries(&mut self) -> Result<Vec<ArchiveEntry>>;
+
+    /// reads entries listed by [`ArchiveBackend::entries`], handing them to `sink` in the
+    /// order given
+    fn read_entries(&mut self, entries: &[ArchiveEntry], sink: &mut Sink<'_>) -> Result<()>;
+}
+
+/// the archive formats pages can be imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    SevenZip,
+    Rar,
+}
+
+impl ArchiveKind {
+    /// guesses the format from a file extension
+    fn from_extension(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "cbz" | "zip" => Some(Self::Zip),
+            "cbt" | "tar" => Some(Self::Tar),
+            "cb7" | "7z" => Some(Self::SevenZip),
+            "cbr" | "rar" => Some(Self::Rar),
+            _ => None,
+        }
+    }
+
+    /// recognizes the format from the first bytes of a file
+    fn from_magic(header: &[u8]) -> Option<Self> {
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            Some(Self::Zip)
+        } else if header.starts_with(b"Rar!\x1a\x07") {
+            Some(Self::Rar)
+        } else if header.starts_with(&[0x37, 0x7a, 0xbc, 0xaf, 0x27, 0x1c]) {
+            Some(Self::SevenZip)
+        } else if header.get(257..262) == Some(b"ustar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
+    /// works out the format of an archive
+    ///
+    /// the file's contents win over its extension, since comic archives are often renamed
+    /// (a `.cbr` that is really a zip file is common)
+    fn detect(path: &Path) -> Result<Self> {
+        let mut header = Vec::with_capacity(512);
+        fs::File::open(path)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to open archive: {}", path.display()))?
+            .take(512)
+            .read_to_end(&mut header)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to read archive: {}", path.display()))?;
+
+        Self::from_magic(&header)
+            .or_else(|| Self::from_extension(path))
+            .ok_or_else(|| miette!("Unsupported archive format: {}", path.display()).into())
+    }
+
+    /// the comic archive name of the format, recorded as `Converted-From`
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Zip => "CBZ",
+            Self::Tar => "CBT",
+            Self::SevenZip => "CB7",
+            Self::Rar => "CBR",
+        }
+    }
+
+    fn open(self, path: &Path) -> Result<Box<dyn ArchiveBackend>> {
+        match self {
+            Self::Zip => Ok(Box::new(cbz::Cbz::open(path)?)),
+            Self::Tar => Ok(Box::new(cbt::Cbt::new(path))),
+            Self::SevenZip => Ok(Box::new(cb7::Cb7::new(path))),
+            #[cfg(feature = "rar")]
+            Self::Rar => Ok(Box::new(cbr::Cbr::new(path))),
+            #[cfg(not(feature = "rar"))]
+            Self::Rar => Err(miette!(
+                "{} is a RAR archive; reading it needs boundbook built with the `rar` feature",
+                path.display()
+            )
+            .into()),
+        }
+    }
+}
+
+/// hands entries that are read in archive order to a sink in the order they were asked for
+///
+/// entries that turn up early are held in memory until every entry before them has been
+/// handed over, so backends that can only read their archive front to back make one pass
+struct InOrder<'e, 's> {
+    entries: &'e [ArchiveEntry],
+    /// position in `entries` of every wanted entry, by entry index
+    positions: HashMap<usize, usize>,
+    /// data read ahead of its turn, by position
+    pending: HashMap<usize, Vec<u8>>,
+    /// position of the next entry to hand over
+    next: usize,
+    sink: &'s mut Sink<'s>,
+}
+
+impl<'e, 's> InOrder<'e, 's> {
+    fn new(entries: &'e [ArchiveEntry], sink: &'s mut Sink<'s>) -> Self {
+        Self {
+            entries,
+            positions: entries
+                .iter()
+                .enumerate()
+                .map(|(position, entry)| (entry.index, position))
+                .collect(),
+            pending: HashMap::new(),
+            next: 0,
+            sink,
+        }
+    }
+
+    /// checks whether the entry at an index still has to be read
+    fn wants(&self, index: usize) -> bool {
+        self.positions
+            .get(&index)
+            .is_some_and(|&position| position >= self.next && !self.pending.contains_key(&position))
+    }
+
+    /// checks whether every entry has been handed over
+    const fn is_done(&self) -> bool {
+        self.next >= self.entries.len()
+    }
+
+    #[macroni_n_cheese::mathinator2000]
+    fn push(&mut self, index: usize, data: Vec<u8>) -> Result<()> {
+        let Some(&position) = self.positions.get(&index) else {
+            return Ok(());
+        };
+        self.pending.insert(position, data);
+
+        while let Some(data) = self.pending.remove(&self.next) {
+            (self.sink)(&self.entries[self.next], data)?;
+            self.next += 1;
+        }
+
+        Ok(())
+    }
+
+    /// fails if the archive ended before every entry was read
+    fn finish(self) -> Result<()> {
+        match self.entries.get(self.next) {
+            Some(entry) => Err(miette!("{} is missing from the archive", entry.name).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// reads entries in parallel batches, handing them to `sink` in the order given
+///
+/// for backends that can read any entry on its own; `init` sets up the state each worker
+/// thread reads with
+fn read_batched<T, I, R>(
+    entries: &[ArchiveEntry],
+    sink: &mut Sink<'_>,
+    init: I,
+    read: R,
+) -> Result<()>
+where
+    I: Fn() -> T + Sync + Send,
+    R: Fn(&mut T, &ArchiveEntry) -> Result<Vec<u8>> + Sync + Send,
+{
+    for batch in entries.chunks(DECOMPRESS_BATCH) {
+        let pages = batch
+            .par_iter()
+            .map_init(&init, &read)
+            .collect::<Result<Vec<_>>>()?;
+
+        for (entry, data) in batch.iter().zip(pages) {
+            sink(entry, data)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// reads the `ComicInfo.xml` closest to the root of an archive, if it has one
+fn read_comic_info(
+    backend: &mut dyn ArchiveBackend,
+    entries: &[ArchiveEntry],
+) -> Result<Option<ComicInfo>> {
+    let Some(entry) = entries
+        .iter()
+        .filter(|entry| {
+            split_folder(&entry.name)
+                .1
+                .eq_ignore_ascii_case(ComicInfo::FILE_NAME)
+        })
+        .min_by_key(|entry| entry.name.matches('/').count())
+    else {
+        return Ok(None);
+    };
+
+    let mut xml = Vec::new();
+    backend
+        .read_entries(std::slice::from_ref(entry), &mut |_, data| {
+            xml = data;
+            Ok(())
+        })
+        .context("Failed to read ComicInfo.xml")?;
+
+    let parsed = String::from_utf8(xml)
+        .map_err(|err| err.to_string())
+        .and_then(|xml| ComicInfo::parse(&xml).map_err(|err| err.to_string()));
+    match parsed {
+        Ok(info) => Ok(Some(info)),
+        Err(err) => {
+            println!("  Ignoring ComicInfo.xml: {}", err);
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ChapterInfo {
+    name: String,
+    source: PathBuf,
+    kind: ArchiveKind,
+    entries: Vec<ArchiveEntry>,
+    comic_info: Option<ComicInfo>,
+}
+
+/// turns the folders pages are nested in into sections
+///
+/// folders every page shares (such as a single top-level folder wrapping the whole archive) are
+/// skipped. each remaining folder becomes a section starting at its first page, nested under
+/// the section of its parent folder.
+///
+/// # Returns
+///
+/// `(title, first page within the chapter, parent folder title)` for every folder, in page order
+fn folder_sections(entries: &[ArchiveEntry]) -> Vec<(String, u64, Option<String>)> {
+    let folders: Vec<Vec<&str>> = entries
+        .iter()
+        .map(|entry| {
+            split_folder(&entry.name)
+                .0
+                .split('/')
+                .filter(|c| !c.is_empty())
+                .collect()
+        })
+        .collect();
+
+    let common = folders.first().map_or(0, |first| {
+        folders
+            .iter()
+            .map(|folder| first.iter().zip(folder).take_while(|(a, b)| a == b).count())
+            .min()
+            .unwrap_or(0)
+    });
+
+    let mut sections = Vec::new();
+    let mut previous: &[&str] = &[];
+
+    for (page, folder) in folders.iter().enumerate() {
+        let folder = &folder[common..];
+        let shared = previous
+            .iter()
+            .zip(folder)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        for depth in shared..folder.len() {
+            let parent = folder[..depth].last().map(|p| p.to_string());
+            sections.push((folder[depth].to_string(), page as u64, parent));
+        }
+        previous = folder;
+    }
+
+    sections
+}
+
+fn is_archive_file(path: &Path) -> bool {
+    ArchiveKind::from_extension(path).is_some()
+}
+
+fn collect_archive_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut archive_files = Vec::new();
+
+    for entry in fs::read_dir(dir)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+    {
+        let entry = entry.into_diagnostic()?;
+        let path = entry.path();
+
+        if path.is_file() && is_archive_file(&path) {
+            archive_files.push(path);
+        }
+    }
+
+    alphanumeric_sort::sort_path_slice(&mut archive_files);
+
+    Ok(archive_files)
+}
+
+#[macroni_n_cheese::mathinator2000]
+fn process_archive_to_chapter(
+    archive_path: &Path,
+    chapter_index: usize,
+    use_comic_info: bool,
+    sort: PageSort,
+) -> Result<ChapterInfo> {
+    let next_chapter = chapter_index + 1;
+    let chapter_name = archive_path
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or(&format!("Chapter {}", next_chapter))
+        .to_string();
+
+    println!("Processing: {} ...", chapter_name);
+
+    let kind = ArchiveKind::detect(archive_path)?;
+    let mut backend = kind.open(archive_path)?;
+    let files = backend
+        .entries()
+        .with_context(|| format!("Failed to list {}", archive_path.display()))?;
+
+    let mut entries: Vec<ArchiveEntry> = files
+        .iter()
+        .filter(|entry| !entry.is_hidden() && !matches!(entry.media_type, MediaType::Unknown))
+        .cloned()
+        .collect();
+    entries.sort_by(|a, b| sort.compare_paths((&a.name, a.modified), (&b.name, b.modified)));
+
+    println!("  Found {} image pages", entries.len());
+
+    let comic_info = if use_comic_info {
+        read_comic_info(backend.as_mut(), &files)?
+    } else {
+        None
+    };
+    if comic_info.is_some() {
+        println!("  Found {}", ComicInfo::FILE_NAME);
+    }
+
+    Ok(ChapterInfo {
+        name: chapter_name,
+        source: archive_path.to_path_buf(),
+        kind,
+        entries,
+        comic_info,
+    })
+}
+
+fn process_directory_of_archives(
+    input_dir: &Path,
+    use_comic_info: bool,
+    sort: PageSort,
+) -> Result<Vec<ChapterInfo>> {
+    let archive_files = collect_archive_files(input_dir)?;
+
+    if archive_files.is_empty() {
+        return Err(miette!("No archives found in directory: {}", input_dir.display()).into());
+    }
+
+    println!("Found {} archives to process", archive_files.len());
+    println!();
+
+    let mut chapters = Vec::new();
+
+    for (index, archive_path) in archive_files.iter().enumerate() {
+        let chapter = process_archive_to_chapter(archive_path, index, use_comic_info, sort)?;
+        chapters.push(chapter);
+    }
+
+    Ok(chapters)
+}
+
+#[macroni_n_cheese::mathinator2000]
+pub fn execute(args: FromArchiveArgs) -> Result<()> {
+    super::petrify::ensure_not_petrified(&args.output, args.force)?;
+
+    let chapters = if args.input.is_dir() || args.directory_mode {
+        if !args.input.is_dir() {
+            return Err(miette!("Input is not a directory: {}", args.input.display()).into());
+        }
+        println!(
+            "Converting directory of archives to BBF: {}",
+            args.input.display()
+        );
+        println!();
+        process_directory_of_archives(&args.input, !args.no_comic_info, args.sort)?
+    } else {
+        println!("Converting archive to BBF: {}", args.input.display());
+        vec![process_archive_to_chapter(
+            &args.input,
+            0,
+            !args.no_comic_info,
+            args.sort,
+        )?]
+    };
+
+    let mut builder = BbfBuilder::with_defaults(&args.output)
+        .into_diagnostic()
+        .context("Failed to create BBF builder")?;
+
+    let user_metadata = UserMetadata::add(&mut builder, &args.metadata);
+
+    if chapters.len() == 1 {
+        if let Some(filename) = args.input.file_name().and_then(|n| n.to_str()) {
+            builder.add_metadata("Source", filename, None);
+        }
+    } else {
+        if let Some(dirname) = args.input.file_name().and_then(|n| n.to_str()) {
+            builder.add_metadata("Source", dirname, None);
+        }
+
+        builder.add_metadata("Chapters", &chapters.len().to_string(), None);
+    }
+
+    let mut formats: Vec<&str> = Vec::new();
+    for chapter in &chapters {
+        if !formats.contains(&chapter.kind.label()) {
+            formats.push(chapter.kind.label());
+        }
+    }
+    builder.add_metadata("Converted-From", &formats.join(", "), None);
+
+    println!();
+    println!("Building BBF file...");
+
+    let mut total_pages: u64 = 0;
+    let mut section_pages: Vec<(String, u64, Option<String>)> = Vec::new();
+    let as_sections = chapters.len() > 1;
+
+    for (chapter_idx, chapter) in chapters.iter().enumerate() {
+        let first_page_of_chapter = total_pages;
+        let parent = as_sections.then_some(chapter.name.as_str());
+
+        if let Some(info) = &chapter.comic_info {
+            for (key, value) in &info.metadata {
+                user_metadata.add_from_source(&mut builder, key, value, parent);
+            }
+        }
+
+        let next_chapter = chapter_idx + 1;
+        println!(
+            "  Adding Chapter {}/{}: {} ({} pages)",
+            next_chapter,
+            chapters.len(),
+            chapter.name,
+            chapter.entries.len()
+        );
+
+        let mut page_idx: u64 = 0;
+        chapter.kind.open(&chapter.source)?.read_entries(
+            &chapter.entries,
+            &mut |entry, data| {
+                let page_flags = chapter
+                    .comic_info
+                    .as_ref()
+                    .map_or(0, |info| info.page_flags(page_idx));
+                builder
+                    .add_page_bytes(&data, entry.media_type, page_flags, 0)
+                    .into_diagnostic()
+                    .with_context(|| {
+                        format!(
+                            "Failed to add page {} from chapter {}",
+                            entry.name, chapter.name
+                        )
+                    })?;
+
+                page_idx += 1;
+                Ok(())
+            },
+        )?;
+        total_pages += page_idx;
+
+        if as_sections {
+            section_pages.push((chapter.name.clone(), first_page_of_chapter, None));
+        }
+
+        for (title, first_page, folder_parent) in folder_sections(&chapter.entries) {
+            section_pages.push((
+                title,
+                first_page_of_chapter + first_page,
+                folder_parent.or_else(|| parent.map(str::to_string)),
+            ));
+        }
+
+        if let Some(info) = &chapter.comic_info {
+            for (image, bookmark) in info.bookmarks() {
+                if image < chapter.entries.len() as u64 {
+                    section_pages.push((
+                        bookmark.to_string(),
+                        first_page_of_chapter + image,
+                        parent.map(str::to_string),
+                    ));
+                }
+            }
+        }
+    }
+
+    for (section_name, first_page, parent) in section_pages {
+        builder.add_section(&section_name, first_page, parent.as_deref());
+    }
+
+    builder.finalize().into_diagnostic()?;
+
+    println!();
+    println!(
+        "Successfully converted to {} ({} pages, {} chapters)",
+        args.output.display(),
+        total_pages,
+        chapters.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {
+        super::*,
+        assert2::check as assert,
+        boundbook::{BbfReader, format::BBF_PAGE_FRONT_COVER_FLAG},
+        std::io::Write,
+        zip::{ZipWriter, write::SimpleFileOptions},
+    };
+
+    /// the files every test archive holds, out of order and with a nested folder
+    const FILES: &[(&str, u8)] = &[
+        ("Ch 2/p1.png", 3),
+        ("p10.png", 2),
+        ("p9.png", 1),
+        ("Ch 2/p2.png", 4),
+    ];
+
+    const COMIC_INFO: &str = r#"<?xml version="1.0"?>
+<ComicInfo>
+  <Title>Issue One</Title>
+  <Series>Example</Series>
+  <Writer>Jane Doe</Writer>
+  <Pages>
+    <Page Image="0" Type="FrontCover" />
+    <Page Image="2" Bookmark="Part 2" />
+  </Pages>
+</ComicInfo>"#;
+
+    fn write_cbz(path: &Path) {
+        let mut zip = ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, byte) in [("p1.png", 1u8), ("p2.png", 2), ("p3.png", 3)] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(&[byte; 32]).unwrap();
+        }
+        zip.start_file("ComicInfo.xml", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(COMIC_INFO.as_bytes()).unwrap();
+        zip.finish().unwrap();
+    }
+
+    fn convert(input: PathBuf, output: PathBuf, metadata: Vec<String>) -> BbfReader {
+        execute(FromArchiveArgs {
+            input,
+            output: output.clone(),
+            metadata,
+            keep_temp: false,
+            directory_mode: false,
+            force: false,
+            no_comic_info: false,
+            sort: PageSort::Natural,
+        })
+        .unwrap();
+        BbfReader::open(output).unwrap()
+    }
+
+    fn metadata_of(reader: &BbfReader) -> Vec<(String, String, Option<String>)> {
+        reader
+            .metadata_map()
+            .unwrap()
+            .iter()
+            .map(|(k, v, s)| (k.to_string(), v.to_string(), s.map(str::to_string)))
+            .collect()
+    }
+
+    #[test]
+    fn test_pages_are_added_in_archive_order_across_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let cbz = dir.path().join("long.cbz");
+        let page_count = DECOMPRESS_BATCH * 2 + 5;
+
+        let mut zip = ZipWriter::new(fs::File::create(&cbz).unwrap());
+        for i in (0..page_count).rev() {
+            zip.start_file(format!("{i:03}.png"), SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(&[i as u8; 48]).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let reader = convert(cbz, dir.path().join("long.bbf"), Vec::new());
+        let pages = reader.pages().unwrap();
+        assert!(pages.len() == page_count);
+
+        let assets = reader.assets().unwrap();
+        for (i, page) in pages.iter().enumerate() {
+            let data = reader
+                .get_asset_data(&assets[page.asset_index as usize])
+                .unwrap();
+            assert!(*data == [i as u8; 48], "page {i}");
+        }
+    }
+
+    #[test]
+    fn test_nested_folders_become_sub_sections_in_natural_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let cbz = dir.path().join("nested.cbz");
+
+        let mut zip = ZipWriter::new(fs::File::create(&cbz).unwrap());
+        for (name, byte) in [
+            ("Book/Vol 1/Ch 10/p1.jpg", 4u8),
+            ("Book/Vol 1/Ch 2/p10.jpg", 3),
+            ("Book/cover.jpg", 1),
+            ("Book/Vol 1/Ch 2/p2.jpg", 2),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(&[byte; 16]).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let reader = convert(cbz, dir.path().join("nested.bbf"), Vec::new());
+        let assets = reader.assets().unwrap();
+        let order: Vec<u8> = reader
+            .pages()
+            .unwrap()
+            .iter()
+            .map(|page| {
+                reader
+                    .get_asset_data(&assets[page.asset_index as usize])
+                    .unwrap()[0]
+            })
+            .collect();
+        assert!(order == [1, 2, 3, 4]);
+
+        let tree = reader.section_tree().unwrap();
+        let sections: Vec<_> = tree
+            .iter()
+            .map(|s| (s.title(), s.start(), s.parent().map(|p| p.title())))
+            .collect();
+        assert!(
+            sections
+                == [
+                    ("Vol 1", 1, None),
+                    ("Ch 2", 1, Some("Vol 1")),
+                    ("Ch 10", 3, Some("Vol 1")),
+                ]
+        );
+    }
+
+    #[test]
+    fn test_comic_info_is_imported_as_book_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let cbz = dir.path().join("issue.cbz");
+        write_cbz(&cbz);
+
+        let reader = convert(
+            cbz,
+            dir.path().join("issue.bbf"),
+            vec!["Title:Overridden".into()],
+        );
+
+        assert!(reader.get_metadata("Title").unwrap() == Some("Overridden"));
+        assert!(reader.get_metadata("Series").unwrap() == Some("Example"));
+        assert!(reader.get_metadata("Author").unwrap() == Some("Jane Doe"));
+        assert!(
+            metadata_of(&reader)
+                .iter()
+                .filter(|(k, _, _)| k == "Title")
+                .count()
+                == 1
+        );
+
+        let pages = reader.pages().unwrap();
+        assert!({ pages[0].flags } == BBF_PAGE_FRONT_COVER_FLAG);
+        assert!({ pages[1].flags } == 0);
+
+        let tree = reader.section_tree().unwrap();
+        let bookmark = tree.find("Part 2").unwrap();
+        assert!(bookmark.pages() == (2..3));
+        assert!(bookmark.parent().is_none());
+    }
+
+    #[test]
+    fn test_comic_info_is_scoped_to_chapters_in_directory_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let chapters = dir.path().join("chapters");
+        fs::create_dir(&chapters).unwrap();
+        write_cbz(&chapters.join("Chapter 1.cbz"));
+        write_cbz(&chapters.join("Chapter 2.cbz"));
+
+        let reader = convert(chapters, dir.path().join("series.bbf"), Vec::new());
+
+        assert!(reader.get_metadata("Series").unwrap().is_none());
+        assert!(
+            reader
+                .metadata_map()
+                .unwrap()
+                .get_in_section("Chapter 2", "Series")
+                == Some("Example")
+        );
+
+        let pages = reader.pages().unwrap();
+        assert!({ pages[3].flags } == BBF_PAGE_FRONT_COVER_FLAG);
+
+        let tree = reader.section_tree().unwrap();
+        let bookmarks: Vec<_> = tree
+            .iter()
+            .filter(|s| s.title() == "Part 2")
+            .map(|s| (s.parent().unwrap().title(), s.start()))
+            .collect();
+        assert!(bookmarks == [("Chapter 1", 2), ("Chapter 2", 5)]);
+    }
+
+    fn page_bytes(reader: &BbfReader) -> Vec<u8> {
+        let assets = reader.assets().unwrap();
+        reader
+            .pages()
+            .unwrap()
+            .iter()
+            .map(|page| {
+                reader
+                    .get_asset_data(&assets[page.asset_index as usize])
+                    .unwrap()[0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tar_archives_share_page_order_and_comic_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let cbt = dir.path().join("issue.cbt");
+
+        let mut tar = tar::Builder::new(fs::File::create(&cbt).unwrap());
+        let mut files: Vec<(&str, Vec<u8>)> = FILES
+            .iter()
+            .map(|&(name, byte)| (name, vec![byte; 16]))
+            .collect();
+        files.push(("ComicInfo.xml", COMIC_INFO.as_bytes().to_vec()));
+        for (name, data) in &files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, data.as_slice()).unwrap();
+        }
+        tar.into_inner().unwrap();
+
+        let reader = convert(cbt, dir.path().join("issue.bbf"), Vec::new());
+        assert!(page_bytes(&reader) == [1, 2, 3, 4]);
+        assert!(reader.get_metadata("Series").unwrap() == Some("Example"));
+        assert!(reader.get_metadata("Converted-From").unwrap() == Some("CBT"));
+        assert!({ reader.pages().unwrap()[0].flags } == BBF_PAGE_FRONT_COVER_FLAG);
+
+        let tree = reader.section_tree().unwrap();
+        assert!(tree.find("Ch 2").unwrap().start() == 2);
+    }
+
+    #[test]
+    fn test_7z_archives_share_page_order_and_comic_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        fs::create_dir_all(source.join("Ch 2")).unwrap();
+        for &(name, byte) in FILES {
+            fs::write(source.join(name), [byte; 16]).unwrap();
+        }
+        fs::write(source.join("ComicInfo.xml"), COMIC_INFO).unwrap();
+        let cb7 = dir.path().join("issue.cb7");
+        sevenz_rust::compress_to_path(&source, &cb7).unwrap();
+
+        let reader = convert(cb7, dir.path().join("issue.bbf"), Vec::new());
+        assert!(page_bytes(&reader) == [1, 2, 3, 4]);
+        assert!(reader.get_metadata("Series").unwrap() == Some("Example"));
+        assert!(reader.get_metadata("Converted-From").unwrap() == Some("CB7"));
+        assert!({ reader.pages().unwrap()[0].flags } == BBF_PAGE_FRONT_COVER_FLAG);
+    }
+
+    #[test]
+    fn test_archive_contents_win_over_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let cbz = dir.path().join("misnamed.cbr");
+        write_cbz(&cbz);
+
+        assert!(ArchiveKind::detect(&cbz).unwrap() == ArchiveKind::Zip);
+        assert!(ArchiveKind::from_extension(Path::new("a.CB7")) == Some(ArchiveKind::SevenZip));
+        assert!(ArchiveKind::from_extension(Path::new("a.pdf")).is_none());
+
+        let reader = convert(cbz, dir.path().join("misnamed.bbf"), Vec::new());
+        assert!(reader.get_metadata("Converted-From").unwrap() == Some("CBZ"));
+        assert!(reader.pages().unwrap().len() == 3);
+    }
+}
@@ -0,0 +1,93 @@
+use {
+    super::{ArchiveBackend, ArchiveEntry, InOrder, Sink},
+    boundbook::Result,
+    miette::miette,
+    sevenz_rust::{Archive, Password, SevenZReader},
+    std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+    },
+};
+
+/// reads 7z archives (`.cb7`)
+///
+/// solid 7z archives can only be decompressed front to back, so entries are read in a single
+/// pass over the archive
+pub struct Cb7 {
+    path: PathBuf,
+}
+
+impl Cb7 {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl ArchiveBackend for Cb7 {
+    fn entries(&mut self) -> Result<Vec<ArchiveEntry>> {
+        let archive = Archive::open(&self.path).map_err(|e| {
+            miette!(
+                "Failed to read CB7 archive {} - file may be corrupted: {}",
+                self.path.display(),
+                e
+            )
+        })?;
+
+        Ok(archive
+            .files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| !file.is_directory)
+            .map(|(i, file)| {
+                let modified = file
+                    .has_last_modified_date
+                    .then(|| file.last_modified_date.to_raw());
+                ArchiveEntry::new(i, &file.name, modified)
+            })
+            .collect())
+    }
+
+    fn read_entries(&mut self, entries: &[ArchiveEntry], sink: &mut Sink<'_>) -> Result<()> {
+        let mut reader = SevenZReader::open(&self.path, Password::empty())
+            .map_err(|e| miette!("Failed to open CB7 file {}: {}", self.path.display(), e))?;
+
+        // the reader hands out entries without their position, so they're matched up by name
+        let indices: HashMap<String, usize> = reader
+            .archive()
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| (ArchiveEntry::new(i, &file.name, None).name, i))
+            .collect();
+
+        let mut in_order = InOrder::new(entries, sink);
+        let mut failed = None;
+
+        reader
+            .for_each_entries(|file, data| {
+                let Some(&i) = indices.get(&ArchiveEntry::new(0, &file.name, None).name) else {
+                    return Ok(true);
+                };
+                if !in_order.wants(i) {
+                    return Ok(true);
+                }
+
+                let mut buffer = Vec::new();
+                data.read_to_end(&mut buffer)?;
+                if let Err(err) = in_order.push(i, buffer) {
+                    failed = Some(err);
+                    return Ok(false);
+                }
+
+                Ok(!in_order.is_done())
+            })
+            .map_err(|e| miette!("Failed to decompress {}: {}", self.path.display(), e))?;
+
+        match failed {
+            Some(err) => Err(err),
+            None => in_order.finish(),
+        }
+    }
+}
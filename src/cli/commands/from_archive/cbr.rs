@@ -0,0 +1,210 @@
+use {
+    super::{ArchiveBackend, ArchiveEntry, InOrder, Sink},
+    boundbook::Result,
+    miette::miette,
+    std::path::{Path, PathBuf},
+    unrar::Archive,
+};
+
+/// reads RAR archives (`.cbr`)
+///
+/// solid RAR archives can only be decompressed front to back, so entries are read in a single
+/// pass over the archive
+pub struct Cbr {
+    path: PathBuf,
+}
+
+impl Cbr {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl ArchiveBackend for Cbr {
+    fn entries(&mut self) -> Result<Vec<ArchiveEntry>> {
+        let archive = Archive::new(&self.path).open_for_listing().map_err(|e| {
+            miette!(
+                "Failed to read CBR archive {} - file may be corrupted: {}",
+                self.path.display(),
+                e
+            )
+        })?;
+
+        let mut entries = Vec::new();
+        for (i, header) in archive.enumerate() {
+            let header =
+                header.map_err(|e| miette!("Failed to list {}: {}", self.path.display(), e))?;
+            if header.is_file() {
+                // dos timestamps put the date above the time, so they compare chronologically
+                let modified = Some(u64::from(header.file_time));
+                entries.push(ArchiveEntry::new(
+                    i,
+                    &header.filename.to_string_lossy(),
+                    modified,
+                ));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn read_entries(&mut self, entries: &[ArchiveEntry], sink: &mut Sink<'_>) -> Result<()> {
+        let mut archive = Archive::new(&self.path)
+            .open_for_processing()
+            .map_err(|e| miette!("Failed to open CBR file {}: {}", self.path.display(), e))?;
+
+        let mut in_order = InOrder::new(entries, sink);
+        let mut i: usize = 0;
+
+        while !in_order.is_done() {
+            let Some(entry) = archive
+                .read_header()
+                .map_err(|e| miette!("Failed to read {}: {}", self.path.display(), e))?
+            else {
+                break;
+            };
+
+            archive = if in_order.wants(i) {
+                let name = entry.entry().filename.display().to_string();
+                let (data, rest) = entry
+                    .read()
+                    .map_err(|e| miette!("Failed to decompress {}: {}", name, e))?;
+                in_order.push(i, data)?;
+                rest
+            } else {
+                entry
+                    .skip()
+                    .map_err(|e| miette!("Failed to read {}: {}", self.path.display(), e))?
+            };
+            i = i.saturating_add(1);
+        }
+
+        in_order.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, assert2::check as assert};
+
+    /// crc-32 (ieee), as used by rar 4 headers and file checksums
+    #[macroni_n_cheese::mathinator2000]
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in data {
+            crc ^= u32::from(byte);
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    /// a rar 4 block: the header crc, then the header itself
+    fn block(header: &[u8]) -> Vec<u8> {
+        let mut block = (crc32(header) as u16).to_le_bytes().to_vec();
+        block.extend_from_slice(header);
+        block
+    }
+
+    /// a rar 4 archive storing (not compressing) each file; `None` data makes a folder
+    #[macroni_n_cheese::mathinator2000]
+    fn stored_rar(files: &[(&str, Option<&[u8]>)]) -> Vec<u8> {
+        let mut rar = b"Rar!\x1a\x07\x00".to_vec();
+        rar.extend(block(&[0x73, 0, 0, 13, 0, 0, 0, 0, 0, 0, 0]));
+
+        for (time, (name, data)) in files.iter().enumerate() {
+            let contents = data.unwrap_or_default();
+            let size = (contents.len() as u32).to_le_bytes();
+            let (flags, attributes): (u16, u32) = match data {
+                Some(_) => (0x8000, 0x20),
+                None => (0x80E0, 0x10),
+            };
+
+            let mut header = vec![0x74];
+            header.extend_from_slice(&flags.to_le_bytes());
+            header.extend_from_slice(&(32u16 + name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&size);
+            header.extend_from_slice(&size);
+            header.push(0);
+            header.extend_from_slice(&crc32(contents).to_le_bytes());
+            // 2024-01-02, one second apart
+            let dos_time = (44u32 << 25) | (1u32 << 21) | (2u32 << 16) | time as u32;
+            header.extend_from_slice(&dos_time.to_le_bytes());
+            header.extend_from_slice(&[20, 0x30]);
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&attributes.to_le_bytes());
+            header.extend_from_slice(name.as_bytes());
+
+            rar.extend(block(&header));
+            rar.extend_from_slice(contents);
+        }
+
+        rar.extend(block(&[0x7B, 0x00, 0x40, 7, 0]));
+        rar
+    }
+
+    #[test]
+    fn test_reads_entries_in_the_order_asked_for() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("comic.cbr");
+        std::fs::write(
+            &path,
+            stored_rar(&[
+                ("Chapter 1", None),
+                ("Chapter 1/p1.jpg", Some(b"first")),
+                ("Chapter 1/p2.jpg", Some(b"second")),
+                ("ComicInfo.xml", Some(b"<ComicInfo/>")),
+            ]),
+        )
+        .unwrap();
+
+        let mut cbr = Cbr::new(&path);
+        let entries = cbr.entries().unwrap();
+        let names: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.index, entry.name.as_str()))
+            .collect();
+        assert!(
+            names
+                == [
+                    (1, "Chapter 1/p1.jpg"),
+                    (2, "Chapter 1/p2.jpg"),
+                    (3, "ComicInfo.xml")
+                ]
+        );
+        assert!(entries[0].modified < entries[1].modified);
+
+        let wanted = [entries[1].clone(), entries[0].clone()];
+        let mut read = Vec::new();
+        cbr.read_entries(&wanted, &mut |entry, data| {
+            read.push((entry.name.clone(), data));
+            Ok(())
+        })
+        .unwrap();
+        assert!(
+            read == [
+                ("Chapter 1/p2.jpg".to_string(), b"second".to_vec()),
+                ("Chapter 1/p1.jpg".to_string(), b"first".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_entries_are_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("comic.cbr");
+        std::fs::write(&path, stored_rar(&[("p1.jpg", Some(b"first"))])).unwrap();
+
+        let mut cbr = Cbr::new(&path);
+        let wanted = [ArchiveEntry::new(5, "p9.jpg", None)];
+        assert!(cbr.read_entries(&wanted, &mut |_, _| Ok(())).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use {
+    super::{ArchiveBackend, ArchiveEntry, InOrder, Sink},
+    boundbook::Result,
+    miette::{Context, IntoDiagnostic},
+    std::{
+        fs::File,
+        io::Read,
+        path::{Path, PathBuf},
+    },
+};
+
+/// reads tar archives (`.cbt`)
+///
+/// tar has no index, so entries are read in a single pass from the start of the archive
+pub struct Cbt {
+    path: PathBuf,
+}
+
+impl Cbt {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    fn archive(&self) -> Result<tar::Archive<File>> {
+        let file = File::open(&self.path)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to open CBT file: {}", self.path.display()))?;
+        Ok(tar::Archive::new(file))
+    }
+}
+
+impl ArchiveBackend for Cbt {
+    fn entries(&mut self) -> Result<Vec<ArchiveEntry>> {
+        let mut archive = self.archive()?;
+        let mut entries = Vec::new();
+
+        for (i, entry) in archive
+            .entries()
+            .into_diagnostic()
+            .context("Failed to read CBT archive - file may be corrupted")?
+            .enumerate()
+        {
+            let entry = entry
+                .into_diagnostic()
+                .with_context(|| format!("Failed to read archive entry {}", i))?;
+
+            if entry.header().entry_type().is_file() {
+                let name = entry.path().into_diagnostic()?;
+                entries.push(ArchiveEntry::new(
+                    i,
+                    &name.to_string_lossy(),
+                    entry.header().mtime().ok(),
+                ));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn read_entries(&mut self, entries: &[ArchiveEntry], sink: &mut Sink<'_>) -> Result<()> {
+        let mut archive = self.archive()?;
+        let mut in_order = InOrder::new(entries, sink);
+
+        for (i, entry) in archive
+            .entries()
+            .into_diagnostic()
+            .context("Failed to read CBT archive - file may be corrupted")?
+            .enumerate()
+        {
+            if in_order.is_done() {
+                break;
+            }
+            if !in_order.wants(i) {
+                continue;
+            }
+
+            let mut entry = entry
+                .into_diagnostic()
+                .with_context(|| format!("Failed to read archive entry {}", i))?;
+            let mut buffer = Vec::new();
+            entry
+                .read_to_end(&mut buffer)
+                .into_diagnostic()
+                .with_context(|| format!("Failed to read archive entry {}", i))?;
+
+            in_order.push(i, buffer)?;
+        }
+
+        in_order.finish()
+    }
+}
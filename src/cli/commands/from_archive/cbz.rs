@@ -0,0 +1,96 @@
+use {
+    super::{ArchiveBackend, ArchiveEntry, Sink, read_batched},
+    boundbook::Result,
+    miette::{Context, IntoDiagnostic, miette},
+    std::{
+        fs::File,
+        io::Read,
+        path::{Path, PathBuf},
+    },
+    zip::ZipArchive,
+};
+
+/// reads zip archives (`.cbz`)
+///
+/// entries are decompressed in parallel, with every worker thread opening its own handle on
+/// the archive since a [`ZipArchive`] can only read one entry at a time
+pub struct Cbz {
+    path: PathBuf,
+    archive: ZipArchive<File>,
+}
+
+impl Cbz {
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            archive: open_archive(path)?,
+        })
+    }
+}
+
+fn open_archive(path: &Path) -> Result<ZipArchive<File>> {
+    let file = File::open(path)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to open CBZ file: {}", path.display()))?;
+
+    Ok(ZipArchive::new(file)
+        .into_diagnostic()
+        .context("Failed to read CBZ archive - file may be corrupted")?)
+}
+
+/// packs a zip timestamp into a number that sorts the same way
+fn modified_key(date: zip::DateTime) -> u64 {
+    let [d0, d1] = date.datepart().to_be_bytes();
+    let [t0, t1] = date.timepart().to_be_bytes();
+    u64::from(u32::from_be_bytes([d0, d1, t0, t1]))
+}
+
+impl ArchiveBackend for Cbz {
+    fn entries(&mut self) -> Result<Vec<ArchiveEntry>> {
+        let mut entries = Vec::new();
+
+        for i in 0..self.archive.len() {
+            let file = self
+                .archive
+                .by_index(i)
+                .into_diagnostic()
+                .with_context(|| format!("Failed to read archive entry {}", i))?;
+
+            if !file.is_dir() {
+                entries.push(ArchiveEntry::new(
+                    i,
+                    file.name(),
+                    file.last_modified().map(modified_key),
+                ));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn read_entries(&mut self, entries: &[ArchiveEntry], sink: &mut Sink<'_>) -> Result<()> {
+        let path = &self.path;
+        read_batched(
+            entries,
+            sink,
+            || open_archive(path),
+            |archive, entry| {
+                let archive = archive
+                    .as_mut()
+                    .map_err(|e| miette!("Failed to open {}: {}", path.display(), e))?;
+
+                let mut file = archive
+                    .by_index(entry.index)
+                    .into_diagnostic()
+                    .with_context(|| format!("Failed to read entry at index {}", entry.index))?;
+
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer)
+                    .into_diagnostic()
+                    .with_context(|| format!("Failed to decompress {}", entry.name))?;
+
+                Ok(buffer)
+            },
+        )
+    }
+}
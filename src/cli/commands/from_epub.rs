@@ -0,0 +1,735 @@
+use {
+    super::convert::{PageMap, UserMetadata},
+    crate::cli::sort::split_folder,
+    boundbook::{BbfBuilder, Result, format::BBF_PAGE_FRONT_COVER_FLAG, keys, types::MediaType},
+    clap::Args,
+    miette::{Context, IntoDiagnostic, miette},
+    roxmltree::{Node, ParsingOptions},
+    std::{collections::HashMap, fs, io::Read, path::PathBuf},
+    zip::ZipArchive,
+};
+
+/// the namespace of `epub:type` attributes
+const OPS_NAMESPACE: &str = "http://www.idpf.org/2007/ops";
+/// the namespace of `opf:scheme` attributes on EPUB 2 identifiers
+const OPF_NAMESPACE: &str = "http://www.idpf.org/2007/opf";
+/// the namespace of `xlink:href` attributes on SVG images
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+
+#[derive(Args)]
+#[command(author = "The Motherfucking Bearodactyl")]
+pub struct FromEpubArgs {
+    /// Input EPUB file
+    input: PathBuf,
+
+    /// Output BBF file
+    #[arg(short = 'o', long)]
+    output: PathBuf,
+
+    /// Add metadata (format: Key:Value[:Parent])
+    #[arg(short = 'm', long = "meta")]
+    metadata: Vec<String>,
+
+    /// Overwrite the output file even if it is petrified
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Debug)]
+struct ManifestItem {
+    /// path of the item inside the EPUB
+    path: String,
+    media_type: String,
+    properties: String,
+}
+
+/// what the package document says about the book
+#[derive(Debug, Default)]
+struct Package {
+    metadata: Vec<(&'static str, String)>,
+    /// manifest items by id
+    manifest: HashMap<String, ManifestItem>,
+    /// manifest ids in reading order, followed by the `linear="no"` (auxiliary) items
+    spine: Vec<String>,
+    /// manifest id of the EPUB 2 table of contents
+    ncx: Option<String>,
+    /// manifest id of the cover image, from EPUB 2 `<meta name="cover">`
+    cover: Option<String>,
+}
+
+/// a table of contents entry
+#[derive(Debug, PartialEq, Eq)]
+struct TocEntry {
+    title: String,
+    /// the document it points at, without a fragment
+    target: String,
+    /// title of the enclosing entry
+    parent: Option<String>,
+}
+
+fn parse_xml(text: &str) -> std::result::Result<roxmltree::Document<'_>, roxmltree::Error> {
+    roxmltree::Document::parse_with_options(
+        text,
+        ParsingOptions {
+            allow_dtd: true,
+            ..ParsingOptions::default()
+        },
+    )
+}
+
+/// decodes `%xx` escapes in a URL path
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while let Some(&byte) = bytes.get(i) {
+        let escaped = (byte == b'%')
+            .then(|| bytes.get(i.saturating_add(1)..i.saturating_add(3)))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(value) => {
+                decoded.push(value);
+                i = i.saturating_add(3);
+            }
+            None => {
+                decoded.push(byte);
+                i = i.saturating_add(1);
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// resolves a link found in the document at `base` to a path inside the EPUB
+///
+/// fragments are dropped, escapes decoded, and `.` and `..` segments folded away
+fn resolve_href(base: &str, href: &str) -> String {
+    let href = href.split(['#', '?']).next().unwrap_or_default();
+    let href = percent_decode(href);
+    let dir = if href.starts_with('/') {
+        ""
+    } else {
+        split_folder(base).0
+    };
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in dir.split('/').chain(href.split('/')) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// concatenates the text inside a node, collapsing runs of whitespace
+fn node_text(node: Node<'_, '_>) -> String {
+    node.descendants()
+        .filter(Node::is_text)
+        .filter_map(|text| text.text())
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn read_file(archive: &mut ZipArchive<fs::File>, path: &str) -> Result<Vec<u8>> {
+    let mut file = archive
+        .by_name(path)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to find {} in the EPUB", path))?;
+
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read {}", path))?;
+    Ok(data)
+}
+
+fn read_text(archive: &mut ZipArchive<fs::File>, path: &str) -> Result<String> {
+    String::from_utf8(read_file(archive, path)?)
+        .into_diagnostic()
+        .with_context(|| format!("{} isn't valid UTF-8", path))
+        .map_err(Into::into)
+}
+
+/// finds the package document through `META-INF/container.xml`
+fn package_path(archive: &mut ZipArchive<fs::File>) -> Result<String> {
+    let container = read_text(archive, "META-INF/container.xml")?;
+    let document = parse_xml(&container)
+        .into_diagnostic()
+        .context("Failed to parse META-INF/container.xml")?;
+
+    document
+        .descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|node| node.attribute("full-path"))
+        .map(percent_decode)
+        .ok_or_else(|| miette!("META-INF/container.xml names no package document").into())
+}
+
+/// reads the metadata, manifest and spine of a package document
+fn parse_package(xml: &str, opf_path: &str) -> Result<Package> {
+    let document = parse_xml(xml)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to parse {}", opf_path))?;
+    let mut package = Package::default();
+
+    let mut series_id = None;
+    let mut volume = None;
+    let mut non_linear = Vec::new();
+    for node in document.descendants().filter(Node::is_element) {
+        let text = || node_text(node);
+        match node.tag_name().name() {
+            "title" if !package.metadata.iter().any(|(k, _)| *k == keys::TITLE) => {
+                package.metadata.push((keys::TITLE, text()));
+            }
+            "creator" => package.metadata.push((keys::AUTHOR, text())),
+            "language" => package.metadata.push((keys::LANGUAGE, text())),
+            "publisher" => package.metadata.push((keys::PUBLISHER, text())),
+            "date" => package.metadata.push((keys::DATE, text())),
+            "identifier" => {
+                let value = text();
+                let scheme = node
+                    .attribute((OPF_NAMESPACE, "scheme"))
+                    .unwrap_or_default();
+                if let Some(isbn) = value.strip_prefix("urn:isbn:") {
+                    package.metadata.push((keys::ISBN, isbn.to_string()));
+                } else if scheme.eq_ignore_ascii_case("isbn") {
+                    package.metadata.push((keys::ISBN, value));
+                }
+            }
+            "meta" => match (node.attribute("property"), node.attribute("name")) {
+                (Some("belongs-to-collection"), _) if series_id.is_none() => {
+                    package.metadata.push((keys::SERIES, text()));
+                    series_id = Some(node.attribute("id").unwrap_or_default());
+                }
+                (Some("group-position"), _) => {
+                    let refines = node.attribute("refines").unwrap_or_default();
+                    volume = volume.or(Some((refines.trim_start_matches('#'), text())));
+                }
+                (_, Some("calibre:series")) if series_id.is_none() => {
+                    let series = node.attribute("content").unwrap_or_default();
+                    package.metadata.push((keys::SERIES, series.to_string()));
+                    series_id = Some("");
+                }
+                (_, Some("calibre:series_index")) => {
+                    let index = node.attribute("content").unwrap_or_default();
+                    volume = volume.or(Some(("", index.to_string())));
+                }
+                (_, Some("cover")) => {
+                    package.cover = node.attribute("content").map(str::to_string);
+                }
+                _ => {}
+            },
+            "item" => {
+                if let (Some(id), Some(href)) = (node.attribute("id"), node.attribute("href")) {
+                    package.manifest.insert(
+                        id.to_string(),
+                        ManifestItem {
+                            path: resolve_href(opf_path, href),
+                            media_type: node.attribute("media-type").unwrap_or_default().into(),
+                            properties: node.attribute("properties").unwrap_or_default().into(),
+                        },
+                    );
+                }
+            }
+            "spine" => {
+                package.ncx = node.attribute("toc").map(str::to_string);
+                if let Some(direction @ ("rtl" | "ltr")) =
+                    node.attribute("page-progression-direction")
+                {
+                    package
+                        .metadata
+                        .push((keys::READING_DIRECTION, direction.to_string()));
+                }
+            }
+            "itemref" => {
+                if let Some(id) = node.attribute("idref") {
+                    if node.attribute("linear") == Some("no") {
+                        non_linear.push(id.to_string());
+                    } else {
+                        package.spine.push(id.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let (Some(series_id), Some((refines, position))) = (series_id, volume)
+        && (refines.is_empty() || refines == series_id)
+    {
+        package.metadata.push((keys::VOLUME, position));
+    }
+    package.metadata.retain(|(_, value)| !value.is_empty());
+    package.spine.append(&mut non_linear);
+
+    Ok(package)
+}
+
+/// finds the image a fixed-layout page document displays
+///
+/// # Returns
+///
+/// the `src` of the first `<img>`, or the `href` of the first SVG `<image>`, as written
+fn page_image_href(xhtml: &str) -> Option<String> {
+    let Ok(document) = parse_xml(xhtml) else {
+        return scan_image_href(xhtml);
+    };
+
+    document
+        .descendants()
+        .find_map(|node| match node.tag_name().name() {
+            "img" => node.attribute("src"),
+            "image" => node
+                .attribute((XLINK_NAMESPACE, "href"))
+                .or_else(|| node.attribute("href")),
+            _ => None,
+        })
+        .map(str::to_string)
+}
+
+/// finds an image reference without parsing, for page documents that aren't well-formed XML
+/// (usually because they use HTML entities the XML parser doesn't know)
+fn scan_image_href(xhtml: &str) -> Option<String> {
+    let lower = xhtml.to_ascii_lowercase();
+    let tag = ["<img", "<image", "<svg:image"]
+        .iter()
+        .filter_map(|tag| lower.find(tag))
+        .min()?;
+    let end = lower.get(tag..)?.find('>')?.checked_add(tag)?;
+    let element = &xhtml[tag..end];
+    let lower = &lower[tag..end];
+
+    ["src=", "xlink:href=", "href="]
+        .iter()
+        .find_map(|attribute| {
+            let start = lower.find(attribute)?.checked_add(attribute.len())?;
+            let quote = element[start..].chars().next()?;
+            let value = element.get(start.checked_add(1)?..)?;
+            Some(value[..value.find(quote)?].to_string())
+        })
+}
+
+/// reads the table of contents from an EPUB 3 navigation document
+fn parse_nav(xhtml: &str, nav_path: &str) -> Result<Vec<TocEntry>> {
+    let document = parse_xml(xhtml)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to parse {}", nav_path))?;
+
+    let navs: Vec<_> = document
+        .descendants()
+        .filter(|node| node.has_tag_name("nav"))
+        .collect();
+    let nav = navs
+        .iter()
+        .find(|nav| {
+            nav.attribute((OPS_NAMESPACE, "type"))
+                .is_some_and(|kind| kind.split_whitespace().any(|kind| kind == "toc"))
+        })
+        .or(navs.first());
+
+    let mut entries = Vec::new();
+    if let Some(list) = nav.and_then(|nav| nav.children().find(|child| child.has_tag_name("ol"))) {
+        walk_nav_list(list, None, nav_path, &mut entries);
+    }
+    Ok(entries)
+}
+
+fn walk_nav_list(list: Node<'_, '_>, parent: Option<&str>, base: &str, out: &mut Vec<TocEntry>) {
+    for item in list.children().filter(|child| child.has_tag_name("li")) {
+        let label = item
+            .children()
+            .find(|child| child.has_tag_name("a") || child.has_tag_name("span"));
+        let title = label.map(node_text).unwrap_or_default();
+        let href = label.and_then(|label| label.attribute("href"));
+
+        let own_parent = match href {
+            Some(href) if !title.is_empty() => {
+                out.push(TocEntry {
+                    title: title.clone(),
+                    target: resolve_href(base, href),
+                    parent: parent.map(str::to_string),
+                });
+                Some(title.as_str())
+            }
+            _ => parent,
+        };
+
+        if let Some(children) = item.children().find(|child| child.has_tag_name("ol")) {
+            walk_nav_list(children, own_parent, base, out);
+        }
+    }
+}
+
+/// reads the table of contents from an EPUB 2 NCX document
+fn parse_ncx(xml: &str, ncx_path: &str) -> Result<Vec<TocEntry>> {
+    let document = parse_xml(xml)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to parse {}", ncx_path))?;
+
+    let mut entries = Vec::new();
+    if let Some(map) = document
+        .descendants()
+        .find(|node| node.has_tag_name("navMap"))
+    {
+        walk_nav_points(map, None, ncx_path, &mut entries);
+    }
+    Ok(entries)
+}
+
+fn walk_nav_points(node: Node<'_, '_>, parent: Option<&str>, base: &str, out: &mut Vec<TocEntry>) {
+    for point in node
+        .children()
+        .filter(|child| child.has_tag_name("navPoint"))
+    {
+        let title = point
+            .children()
+            .find(|child| child.has_tag_name("navLabel"))
+            .map(node_text)
+            .unwrap_or_default();
+        let src = point
+            .children()
+            .find(|child| child.has_tag_name("content"))
+            .and_then(|content| content.attribute("src"));
+
+        let own_parent = match src {
+            Some(src) if !title.is_empty() => {
+                out.push(TocEntry {
+                    title: title.clone(),
+                    target: resolve_href(base, src),
+                    parent: parent.map(str::to_string),
+                });
+                Some(title.as_str())
+            }
+            _ => parent,
+        };
+
+        walk_nav_points(point, own_parent, base, out);
+    }
+}
+
+/// reads the table of contents, preferring the EPUB 3 navigation document over the NCX
+fn read_toc(archive: &mut ZipArchive<fs::File>, package: &Package) -> Result<Vec<TocEntry>> {
+    let nav = package.manifest.values().find(|item| {
+        item.properties
+            .split_whitespace()
+            .any(|property| property == "nav")
+    });
+    if let Some(nav) = nav {
+        let entries = parse_nav(&read_text(archive, &nav.path)?, &nav.path)?;
+        if !entries.is_empty() {
+            return Ok(entries);
+        }
+    }
+
+    let ncx = package
+        .ncx
+        .as_ref()
+        .and_then(|id| package.manifest.get(id))
+        .or_else(|| {
+            package
+                .manifest
+                .values()
+                .find(|item| item.media_type == "application/x-dtbncx+xml")
+        });
+    match ncx {
+        Some(ncx) => parse_ncx(&read_text(archive, &ncx.path)?, &ncx.path),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[macroni_n_cheese::mathinator2000]
+pub fn execute(args: FromEpubArgs) -> Result<()> {
+    super::petrify::ensure_not_petrified(&args.output, args.force)?;
+
+    println!("Converting EPUB to BBF: {}", args.input.display());
+
+    let file = fs::File::open(&args.input)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to open EPUB file: {}", args.input.display()))?;
+    let mut archive = ZipArchive::new(file)
+        .into_diagnostic()
+        .context("Failed to read EPUB archive - file may be corrupted")?;
+
+    let opf_path = package_path(&mut archive)?;
+    let package = parse_package(&read_text(&mut archive, &opf_path)?, &opf_path)?;
+    println!("  Found {} spine items", package.spine.len());
+
+    let cover = package
+        .manifest
+        .iter()
+        .find(|(id, item)| {
+            item.properties
+                .split_whitespace()
+                .any(|property| property == "cover-image")
+                || package.cover.as_ref() == Some(*id)
+        })
+        .map(|(_, item)| item.path.as_str());
+
+    let mut builder = BbfBuilder::with_defaults(&args.output)
+        .into_diagnostic()
+        .context("Failed to create BBF builder")?;
+
+    let user_metadata = UserMetadata::add(&mut builder, &args.metadata);
+    for (key, value) in &package.metadata {
+        user_metadata.add_from_source(&mut builder, key, value, None);
+    }
+
+    if let Some(filename) = args.input.file_name().and_then(|n| n.to_str()) {
+        builder.add_metadata("Source", filename, None);
+    }
+    builder.add_metadata("Converted-From", "EPUB", None);
+
+    println!();
+    println!("Building BBF file...");
+
+    let mut document_pages = PageMap::new();
+
+    for id in &package.spine {
+        let Some(item) = package.manifest.get(id) else {
+            println!("  Skipping spine item {}: it isn't in the manifest", id);
+            continue;
+        };
+        document_pages.visit(item.path.as_str());
+
+        let image_path = if item.media_type.starts_with("image/") {
+            item.path.clone()
+        } else {
+            let xhtml = String::from_utf8_lossy(&read_file(&mut archive, &item.path)?).into_owned();
+            match page_image_href(&xhtml) {
+                Some(href) => resolve_href(&item.path, &href),
+                None => {
+                    println!("  Skipping {}: it has no image", item.path);
+                    continue;
+                }
+            }
+        };
+
+        let data = read_file(&mut archive, &image_path)?;
+        let media_type = match MediaType::from_bytes(&data) {
+            MediaType::Unknown => {
+                MediaType::from_extension(image_path.rsplit_once('.').map_or("", |(_, ext)| ext))
+            }
+            media_type => media_type,
+        };
+        if matches!(media_type, MediaType::Unknown) {
+            println!("  Skipping {}: unsupported image format", image_path);
+            continue;
+        }
+
+        let flags = if cover == Some(image_path.as_str()) {
+            BBF_PAGE_FRONT_COVER_FLAG
+        } else {
+            0
+        };
+        builder
+            .add_page_bytes(&data, media_type, flags, 0)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to add page {}", image_path))?;
+        document_pages.page_added();
+    }
+
+    if document_pages.added() == 0 {
+        return Err(miette!("{} has no page images", args.input.display()).into());
+    }
+
+    let mut sections: usize = 0;
+    for entry in read_toc(&mut archive, &package)? {
+        if let Some(page) = document_pages.section_start(entry.target.as_str()) {
+            builder.add_section(&entry.title, page, entry.parent.as_deref());
+            sections += 1;
+        }
+    }
+
+    builder.finalize().into_diagnostic()?;
+
+    println!();
+    println!(
+        "Successfully converted to {} ({} pages, {} sections)",
+        args.output.display(),
+        document_pages.added(),
+        sections
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {
+        super::*,
+        assert2::check as assert,
+        boundbook::BbfReader,
+        std::io::Write,
+        zip::{ZipWriter, write::SimpleFileOptions},
+    };
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\nnot really a png";
+    const JPEG: &[u8] = b"\xff\xd8\xffnot really a jpeg";
+
+    const OPF: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="id">urn:isbn:9781234567897</dc:identifier>
+    <dc:title>Sample Volume</dc:title>
+    <dc:creator>Jane Doe</dc:creator>
+    <dc:creator>John Roe</dc:creator>
+    <dc:language>ja</dc:language>
+    <meta property="belongs-to-collection" id="series">Sample</meta>
+    <meta refines="#series" property="group-position">3</meta>
+    <meta property="rendition:layout">pre-paginated</meta>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="p1" href="text/p1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="p2" href="text/p2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="p3" href="text/p3.xhtml" media-type="application/xhtml+xml"/>
+    <item id="cover" href="images/cover.jpg" media-type="image/jpeg" properties="cover-image"/>
+    <item id="i2" href="images/page%202.png" media-type="image/png"/>
+    <item id="i3" href="images/p3.png" media-type="image/png"/>
+  </manifest>
+  <spine page-progression-direction="rtl">
+    <itemref idref="p1"/>
+    <itemref idref="nav" linear="no"/>
+    <itemref idref="p2"/>
+    <itemref idref="p3"/>
+  </spine>
+</package>"##;
+
+    const NAV: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<body>
+  <nav epub:type="landmarks"><ol><li><a href="text/p3.xhtml">Ignored</a></li></ol></nav>
+  <nav epub:type="toc">
+    <ol>
+      <li><a href="text/p1.xhtml">Cover</a></li>
+      <li><a href="text/p2.xhtml#top">Story <em>Part</em></a>
+        <ol>
+          <li><a href="text/p2.xhtml">Chapter 1</a></li>
+          <li><a href="text/p3.xhtml">Chapter 2</a></li>
+        </ol>
+      </li>
+    </ol>
+  </nav>
+</body>
+</html>"#;
+
+    fn write_epub(path: &std::path::Path) {
+        let mut zip = ZipWriter::new(fs::File::create(path).unwrap());
+        let files: &[(&str, &[u8])] = &[
+            ("mimetype", b"application/epub+zip"),
+            (
+                "META-INF/container.xml",
+                br#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#,
+            ),
+            ("OEBPS/content.opf", OPF.as_bytes()),
+            ("OEBPS/nav.xhtml", NAV.as_bytes()),
+            (
+                "OEBPS/text/p1.xhtml",
+                br#"<html xmlns="http://www.w3.org/1999/xhtml"><body>&nbsp;<img src="../images/cover.jpg" alt=""/></body></html>"#,
+            ),
+            (
+                "OEBPS/text/p2.xhtml",
+                br#"<html xmlns="http://www.w3.org/1999/xhtml"><body>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+<image width="100" height="100" xlink:href="../images/page%202.png"/></svg></body></html>"#,
+            ),
+            (
+                "OEBPS/text/p3.xhtml",
+                br#"<html xmlns="http://www.w3.org/1999/xhtml"><body><img src="./../images/./p3.png"/></body></html>"#,
+            ),
+            ("OEBPS/images/cover.jpg", JPEG),
+            ("OEBPS/images/page 2.png", PNG),
+            ("OEBPS/images/p3.png", PNG),
+        ];
+        for (name, data) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_spine_pages_toc_and_metadata_are_imported() {
+        let dir = tempfile::tempdir().unwrap();
+        let epub = dir.path().join("volume.epub");
+        write_epub(&epub);
+        let output = dir.path().join("volume.bbf");
+
+        execute(FromEpubArgs {
+            input: epub,
+            output: output.clone(),
+            metadata: Vec::new(),
+            force: false,
+        })
+        .unwrap();
+
+        let reader = BbfReader::open(output).unwrap();
+        let pages = reader.pages().unwrap();
+        let assets = reader.assets().unwrap();
+        let media: Vec<_> = pages
+            .iter()
+            .map(|page| MediaType::from(assets[page.asset_index as usize].media_type))
+            .collect();
+        assert!(media == [MediaType::Jpg, MediaType::Png, MediaType::Png]);
+        assert!({ pages[0].flags } == BBF_PAGE_FRONT_COVER_FLAG);
+        assert!({ pages[1].flags } == 0);
+
+        let map = reader.metadata_map().unwrap();
+        assert!(map.get(keys::TITLE) == Some("Sample Volume"));
+        assert!(map.get_all(keys::AUTHOR).collect::<Vec<_>>() == ["Jane Doe", "John Roe"]);
+        assert!(map.get(keys::ISBN) == Some("9781234567897"));
+        assert!(map.get(keys::SERIES) == Some("Sample"));
+        assert!(map.get(keys::VOLUME) == Some("3"));
+        assert!(map.get(keys::READING_DIRECTION) == Some("rtl"));
+
+        let tree = reader.section_tree().unwrap();
+        let sections: Vec<_> = tree
+            .iter()
+            .map(|s| (s.title(), s.start(), s.parent().map(|p| p.title())))
+            .collect();
+        assert!(
+            sections
+                == [
+                    ("Cover", 0, None),
+                    ("Story Part", 1, None),
+                    ("Chapter 1", 1, Some("Story Part")),
+                    ("Chapter 2", 2, Some("Story Part")),
+                ]
+        );
+    }
+
+    #[test]
+    fn test_non_linear_spine_items_come_last() {
+        let package = parse_package(OPF, "OEBPS/content.opf").unwrap();
+        assert!(package.spine == ["p1", "p2", "p3", "nav"]);
+    }
+
+    #[test]
+    fn test_resolve_href_folds_relative_segments() {
+        assert!(
+            resolve_href("OEBPS/text/p1.xhtml", "../images/a%20b.png#x") == "OEBPS/images/a b.png"
+        );
+        assert!(resolve_href("content.opf", "./text/p1.xhtml") == "text/p1.xhtml");
+        assert!(resolve_href("OEBPS/content.opf", "/cover.jpg") == "cover.jpg");
+    }
+
+    #[test]
+    fn test_scan_image_href_handles_malformed_pages() {
+        let page = r#"<html><body>&nbsp;<IMG class='page' SRC='p1.jpg'></body></html>"#;
+        assert!(page_image_href(page) == Some("p1.jpg".to_string()));
+    }
+}
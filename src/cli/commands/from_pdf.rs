@@ -0,0 +1,857 @@
+use {
+    super::convert::{PageMap, UserMetadata},
+    boundbook::{BbfBuilder, Result, keys, types::MediaType},
+    clap::Args,
+    miette::{Context, IntoDiagnostic, miette},
+    object::{Dictionary, Document, Object, Stream, drawn_xobjects, text_string},
+    std::{
+        collections::{HashMap, HashSet},
+        fs,
+        io::Cursor,
+        path::PathBuf,
+    },
+};
+
+mod object;
+
+/// how deeply page trees, form XObjects and outlines are followed
+const MAX_DEPTH: u8 = 32;
+
+#[derive(Args)]
+#[command(author = "The Motherfucking Bearodactyl")]
+pub struct FromPdfArgs {
+    /// Input PDF file
+    input: PathBuf,
+
+    /// Output BBF file
+    #[arg(short = 'o', long)]
+    output: PathBuf,
+
+    /// Add metadata (format: Key:Value[:Parent])
+    #[arg(short = 'm', long = "meta")]
+    metadata: Vec<String>,
+
+    /// Overwrite the output file even if it is petrified
+    #[arg(long)]
+    force: bool,
+}
+
+/// a leaf of the page tree
+struct PdfPage<'d> {
+    /// object number of the page dictionary
+    id: u32,
+    /// the page's resources, possibly inherited from an ancestor
+    resources: Option<&'d Dictionary>,
+    /// the page's content stream, or array of content streams
+    contents: Option<&'d Object>,
+}
+
+/// flattens the page tree into reading order
+fn collect_pages<'d>(
+    doc: &'d Document,
+    node: &'d Object,
+    inherited: Option<&'d Dictionary>,
+    depth: u8,
+    visited: &mut HashSet<u32>,
+    pages: &mut Vec<PdfPage<'d>>,
+) {
+    let Object::Reference(id) = node else {
+        return;
+    };
+    if depth > MAX_DEPTH || !visited.insert(*id) {
+        return;
+    }
+    let Some(dict) = doc.resolve(node).as_dict() else {
+        return;
+    };
+
+    let resources = doc.get(dict, "Resources").as_dict().or(inherited);
+    match doc.get(dict, "Kids").as_array() {
+        Some(kids) => {
+            for kid in kids {
+                collect_pages(doc, kid, resources, depth.saturating_add(1), visited, pages);
+            }
+        }
+        None => pages.push(PdfPage {
+            id: *id,
+            resources,
+            contents: dict.get("Contents"),
+        }),
+    }
+}
+
+/// decodes a page's content, joining the streams of a `Contents` array
+///
+/// streams that can't be decoded are left out
+fn page_content(doc: &Document, contents: &Object) -> Vec<u8> {
+    let streams: Vec<&Object> = match doc.resolve(contents) {
+        Object::Array(parts) => parts.iter().map(|part| doc.resolve(part)).collect(),
+        stream => vec![stream],
+    };
+
+    let mut content = Vec::new();
+    for stream in streams.iter().filter_map(|stream| stream.as_stream()) {
+        if let Ok((data, None)) = doc.decode_stream(stream) {
+            content.extend_from_slice(&data);
+            content.push(b'\n');
+        }
+    }
+    content
+}
+
+/// finds the image XObjects a content stream draws, following the form XObjects it draws
+///
+/// `visited` holds the forms already followed, so a form drawn many times is read once
+fn collect_images<'d>(
+    doc: &'d Document,
+    content: &[u8],
+    resources: &'d Dictionary,
+    depth: u8,
+    visited: &mut HashSet<u32>,
+    images: &mut Vec<&'d Stream>,
+) {
+    let Some(xobjects) = doc.get(resources, "XObject").as_dict() else {
+        return;
+    };
+
+    for name in drawn_xobjects(content) {
+        let Some(xobject) = xobjects.get(&name) else {
+            continue;
+        };
+        let Some(stream) = doc.resolve(xobject).as_stream() else {
+            continue;
+        };
+        match doc.get(&stream.dict, "Subtype").as_name() {
+            Some("Image") => images.push(stream),
+            Some("Form") if depth < MAX_DEPTH => {
+                if let Object::Reference(id) = xobject
+                    && !visited.insert(*id)
+                {
+                    continue;
+                }
+                // forms without their own resources use the ones they're drawn with
+                let form_resources = doc
+                    .get(&stream.dict, "Resources")
+                    .as_dict()
+                    .unwrap_or(resources);
+                if let Ok((form, None)) = doc.decode_stream(stream) {
+                    let depth = depth.saturating_add(1);
+                    collect_images(doc, &form, form_resources, depth, visited, images);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// the number of pixels in an image XObject
+fn pixel_count(doc: &Document, image: &Stream) -> u64 {
+    let dimension = |key| {
+        doc.integer(&image.dict, key)
+            .and_then(|value| u64::try_from(value).ok())
+            .unwrap_or(0)
+    };
+    dimension("Width").saturating_mul(dimension("Height"))
+}
+
+/// the color components of an image, and the palette if it's indexed
+///
+/// # Returns
+///
+/// `(components per pixel after palette lookup, palette)`
+fn color_space(doc: &Document, space: &Object) -> Result<(usize, Option<Vec<u8>>)> {
+    let space = doc.resolve(space);
+    let (family, rest) = match space {
+        Object::Name(name) => (name.as_str(), &[][..]),
+        Object::Array(items) => match items.split_first() {
+            Some((first, rest)) => (doc.resolve(first).as_name().unwrap_or_default(), rest),
+            None => ("", &[][..]),
+        },
+        _ => ("", &[][..]),
+    };
+
+    match family {
+        "DeviceGray" | "G" | "CalGray" => Ok((1, None)),
+        "DeviceRGB" | "RGB" | "CalRGB" => Ok((3, None)),
+        "DeviceCMYK" | "CMYK" => Ok((4, None)),
+        "ICCBased" => {
+            let components = rest
+                .first()
+                .and_then(|profile| doc.resolve(profile).as_dict())
+                .and_then(|profile| doc.integer(profile, "N"))
+                .unwrap_or(3);
+            Ok((usize::try_from(components).into_diagnostic()?, None))
+        }
+        "Indexed" | "I" => {
+            let [base, _, lookup] = rest else {
+                return Err(miette!("Malformed indexed color space").into());
+            };
+            let (components, _) = color_space(doc, base)?;
+            let palette = match doc.resolve(lookup) {
+                Object::String(bytes) => bytes.clone(),
+                Object::Stream(stream) => doc.decode_stream(stream)?.0,
+                _ => return Err(miette!("Malformed indexed color space").into()),
+            };
+            Ok((components, Some(palette)))
+        }
+        other => Err(miette!("Unsupported color space {}", other).into()),
+    }
+}
+
+/// unpacks samples of 1, 2, 4, 8 or 16 bits into one byte each, keeping the top byte of
+/// 16-bit samples
+fn unpack_samples(data: &[u8], per_row: usize, rows: usize, bits: usize) -> Result<Vec<u8>> {
+    let too_large = || miette!("Image is too large");
+    let row_bytes = per_row.checked_mul(bits).ok_or_else(too_large)?.div_ceil(8);
+    let total = row_bytes.checked_mul(rows).ok_or_else(too_large)?;
+    if row_bytes == 0 || data.len() < total {
+        return Err(miette!("Image data is truncated").into());
+    }
+
+    let mut samples = Vec::with_capacity(per_row.saturating_mul(rows));
+    for row in data.chunks_exact(row_bytes).take(rows) {
+        match bits {
+            8 => samples.extend_from_slice(row),
+            16 => samples.extend(row.chunks_exact(2).map(|pair| pair[0])),
+            1 | 2 | 4 => {
+                let discard = 8u32.saturating_sub(u32::try_from(bits).unwrap_or(8));
+                samples.extend(
+                    row.iter()
+                        .flat_map(|&byte| {
+                            (0..8u32)
+                                .step_by(bits)
+                                .map(move |offset| byte.wrapping_shl(offset).wrapping_shr(discard))
+                        })
+                        .take(per_row),
+                );
+            }
+            _ => return Err(miette!("Unsupported bit depth {}", bits).into()),
+        }
+    }
+
+    Ok(samples)
+}
+
+/// re-encodes the raw samples of an image XObject as a PNG
+fn encode_png(doc: &Document, dict: &Dictionary, data: &[u8]) -> Result<Vec<u8>> {
+    let dimension = |key| {
+        doc.integer(dict, key)
+            .and_then(|value| u32::try_from(value).ok())
+            .filter(|&value| value > 0)
+            .ok_or_else(|| miette!("Image has no valid {}", key))
+    };
+    let width = dimension("Width")?;
+    let height = dimension("Height")?;
+
+    let mask = matches!(doc.get(dict, "ImageMask"), Object::Bool(true));
+    let bits = if mask {
+        1
+    } else {
+        doc.integer(dict, "BitsPerComponent").unwrap_or(8)
+    };
+    let bits = usize::try_from(bits).into_diagnostic()?;
+    let (components, palette) = if mask {
+        (1, None)
+    } else {
+        color_space(doc, doc.get(dict, "ColorSpace"))?
+    };
+
+    let per_pixel = if palette.is_some() { 1 } else { components };
+    let per_row = usize::try_from(width)
+        .into_diagnostic()?
+        .checked_mul(per_pixel)
+        .ok_or_else(|| miette!("Image is too large"))?;
+    let samples = unpack_samples(
+        data,
+        per_row,
+        usize::try_from(height).into_diagnostic()?,
+        bits,
+    )?;
+
+    let mut samples = match &palette {
+        Some(palette) => {
+            let mut colors = Vec::with_capacity(samples.len().saturating_mul(components));
+            for &index in &samples {
+                let color = usize::from(index)
+                    .checked_mul(components)
+                    .and_then(|start| palette.get(start..start.checked_add(components)?))
+                    .ok_or_else(|| miette!("Palette index {} is out of range", index))?;
+                colors.extend_from_slice(color);
+            }
+            colors
+        }
+        None => {
+            // stretch low bit depths to the full 0-255 range
+            let scale: u8 = match bits {
+                1 => 255,
+                2 => 85,
+                4 => 17,
+                _ => 1,
+            };
+            samples
+                .into_iter()
+                .map(|sample| sample.saturating_mul(scale))
+                .collect()
+        }
+    };
+
+    // a decode array of [1 0] (the usual case for image masks) inverts the samples
+    let inverted = match doc.get(dict, "Decode").as_array() {
+        Some([low, ..]) => matches!(doc.resolve(low), Object::Integer(1)),
+        _ => mask,
+    };
+    if inverted && palette.is_none() {
+        for sample in &mut samples {
+            *sample = !*sample;
+        }
+    }
+
+    let image = match components {
+        1 => image::GrayImage::from_raw(width, height, samples).map(image::DynamicImage::from),
+        3 => image::RgbImage::from_raw(width, height, samples).map(image::DynamicImage::from),
+        4 => {
+            let rgb = samples
+                .chunks_exact(4)
+                .flat_map(|cmyk| {
+                    let black = u16::from(!cmyk[3]);
+                    [0, 1, 2].map(|i| {
+                        let value = u16::from(!cmyk[i]).saturating_mul(black) / 255;
+                        u8::try_from(value).unwrap_or(u8::MAX)
+                    })
+                })
+                .collect();
+            image::RgbImage::from_raw(width, height, rgb).map(image::DynamicImage::from)
+        }
+        other => return Err(miette!("Unsupported number of color components {}", other).into()),
+    }
+    .ok_or_else(|| miette!("Image data doesn't match its size"))?;
+
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageFormat::Png)
+        .into_diagnostic()
+        .context("Failed to encode image as PNG")?;
+    Ok(png.into_inner())
+}
+
+/// extracts an image XObject as an image file
+///
+/// JPEG data is copied as is; raw samples are re-encoded as PNG
+fn extract_image(doc: &Document, image: &Stream) -> Result<(Vec<u8>, MediaType)> {
+    let (data, codec) = doc.decode_stream(image)?;
+    match codec.as_deref() {
+        Some("DCTDecode" | "DCT") => Ok((data, MediaType::Jpg)),
+        None => Ok((encode_png(doc, &image.dict, &data)?, MediaType::Png)),
+        Some(other) => Err(miette!("{} images aren't supported", other).into()),
+    }
+}
+
+/// collects named destinations from the catalog's `Dests` dictionary and `Dests` name tree
+fn named_destinations<'d>(
+    doc: &'d Document,
+    catalog: &'d Dictionary,
+) -> HashMap<Vec<u8>, &'d Object> {
+    let mut names = HashMap::new();
+
+    if let Some(dests) = doc.get(catalog, "Dests").as_dict() {
+        for (name, dest) in dests {
+            names.insert(name.as_bytes().to_vec(), dest);
+        }
+    }
+
+    let mut stack: Vec<(&Object, u8)> = doc
+        .get(catalog, "Names")
+        .as_dict()
+        .and_then(|tree| tree.get("Dests"))
+        .map(|root| (root, 0))
+        .into_iter()
+        .collect();
+    while let Some((node, depth)) = stack.pop() {
+        let Some(node) = doc.resolve(node).as_dict() else {
+            continue;
+        };
+        if let Some(pairs) = doc.get(node, "Names").as_array() {
+            for pair in pairs.chunks_exact(2) {
+                if let Some(name) = doc.resolve(&pair[0]).as_string() {
+                    names.insert(name.to_vec(), &pair[1]);
+                }
+            }
+        }
+        if depth < MAX_DEPTH
+            && let Some(kids) = doc.get(node, "Kids").as_array()
+        {
+            stack.extend(kids.iter().map(|kid| (kid, depth.saturating_add(1))));
+        }
+    }
+
+    names
+}
+
+/// works out which page a destination points at
+///
+/// # Returns
+///
+/// the object number of the page dictionary
+fn destination_page(
+    doc: &Document,
+    dest: &Object,
+    names: &HashMap<Vec<u8>, &Object>,
+    depth: u8,
+) -> Option<u32> {
+    if depth > 2 {
+        return None;
+    }
+    let depth = depth.saturating_add(1);
+
+    match doc.resolve(dest) {
+        Object::Array(items) => match items.first()? {
+            Object::Reference(page) => Some(*page),
+            _ => None,
+        },
+        Object::Name(name) => destination_page(doc, names.get(name.as_bytes())?, names, depth),
+        Object::String(name) => destination_page(doc, names.get(name)?, names, depth),
+        Object::Dictionary(dict) => destination_page(doc, dict.get("D")?, names, depth),
+        _ => None,
+    }
+}
+
+/// an outline item that points at a page
+struct OutlineItem {
+    title: String,
+    /// object number of the page dictionary
+    page: u32,
+    /// title of the nearest ancestor that points at a page
+    parent: Option<String>,
+}
+
+/// walks a level of the outline, starting at its first item
+fn collect_outline(
+    doc: &Document,
+    first: &Object,
+    parent: Option<&str>,
+    names: &HashMap<Vec<u8>, &Object>,
+    depth: u8,
+    visited: &mut HashSet<u32>,
+    items: &mut Vec<OutlineItem>,
+) {
+    let mut next = first;
+    while let Object::Reference(id) = next {
+        if depth > MAX_DEPTH || !visited.insert(*id) {
+            return;
+        }
+        let Some(item) = doc.resolve(next).as_dict() else {
+            return;
+        };
+
+        let title = doc
+            .get(item, "Title")
+            .as_string()
+            .map(|title| text_string(title).trim().to_string())
+            .unwrap_or_default();
+        let dest = match doc.get(item, "Dest") {
+            Object::Null => doc
+                .get(item, "A")
+                .as_dict()
+                .filter(|action| doc.get(action, "S").as_name() == Some("GoTo"))
+                .and_then(|action| action.get("D")),
+            dest => Some(dest),
+        };
+        let page = dest.and_then(|dest| destination_page(doc, dest, names, 0));
+
+        let own_parent = match page {
+            Some(page) if !title.is_empty() => {
+                items.push(OutlineItem {
+                    title: title.clone(),
+                    page,
+                    parent: parent.map(str::to_string),
+                });
+                Some(title.as_str())
+            }
+            _ => parent,
+        };
+
+        if let Some(child) = item.get("First") {
+            collect_outline(
+                doc,
+                child,
+                own_parent,
+                names,
+                depth.saturating_add(1),
+                visited,
+                items,
+            );
+        }
+
+        next = item.get("Next").unwrap_or(&Object::Null);
+    }
+}
+
+/// reads book metadata from the document information dictionary and catalog
+fn document_metadata(doc: &Document, catalog: &Dictionary) -> Vec<(&'static str, String)> {
+    let mut metadata = Vec::new();
+
+    if let Some(info) = doc.get(doc.trailer(), "Info").as_dict() {
+        for (key, field) in [(keys::TITLE, "Title"), (keys::AUTHOR, "Author")] {
+            if let Some(value) = doc.get(info, field).as_string() {
+                let value = text_string(value).trim().to_string();
+                if !value.is_empty() {
+                    metadata.push((key, value));
+                }
+            }
+        }
+    }
+
+    if let Some(language) = doc.get(catalog, "Lang").as_string() {
+        metadata.push((keys::LANGUAGE, text_string(language)));
+    }
+
+    let direction = doc
+        .get(catalog, "ViewerPreferences")
+        .as_dict()
+        .and_then(|prefs| doc.get(prefs, "Direction").as_name());
+    match direction {
+        Some("R2L") => metadata.push((keys::READING_DIRECTION, "rtl".to_string())),
+        Some("L2R") => metadata.push((keys::READING_DIRECTION, "ltr".to_string())),
+        _ => {}
+    }
+
+    metadata
+}
+
+#[macroni_n_cheese::mathinator2000]
+pub fn execute(args: FromPdfArgs) -> Result<()> {
+    super::petrify::ensure_not_petrified(&args.output, args.force)?;
+
+    println!("Converting PDF to BBF: {}", args.input.display());
+
+    let data = fs::read(&args.input)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to read PDF file: {}", args.input.display()))?;
+    let doc = Document::parse(data)
+        .with_context(|| format!("Failed to parse PDF file: {}", args.input.display()))?;
+    let catalog = doc.catalog()?;
+
+    let mut pdf_pages = Vec::new();
+    if let Some(root) = catalog.get("Pages") {
+        collect_pages(&doc, root, None, 0, &mut HashSet::new(), &mut pdf_pages);
+    }
+    if pdf_pages.is_empty() {
+        return Err(miette!("No pages found in {}", args.input.display()).into());
+    }
+    println!("  Found {} pages", pdf_pages.len());
+
+    let mut builder = BbfBuilder::with_defaults(&args.output)
+        .into_diagnostic()
+        .context("Failed to create BBF builder")?;
+
+    let user_metadata = UserMetadata::add(&mut builder, &args.metadata);
+    for (key, value) in document_metadata(&doc, catalog) {
+        user_metadata.add_from_source(&mut builder, key, &value, None);
+    }
+
+    if let Some(filename) = args.input.file_name().and_then(|n| n.to_str()) {
+        builder.add_metadata("Source", filename, None);
+    }
+    builder.add_metadata("Converted-From", "PDF", None);
+
+    println!();
+    println!("Building BBF file...");
+
+    let mut page_numbers = PageMap::new();
+
+    for (i, page) in pdf_pages.iter().enumerate() {
+        let number = i + 1;
+        page_numbers.visit(page.id);
+
+        let mut images = Vec::new();
+        if let (Some(resources), Some(contents)) = (page.resources, page.contents) {
+            let content = page_content(&doc, contents);
+            collect_images(
+                &doc,
+                &content,
+                resources,
+                0,
+                &mut HashSet::new(),
+                &mut images,
+            );
+        }
+        let Some(image) = images
+            .into_iter()
+            .max_by_key(|image| pixel_count(&doc, image))
+        else {
+            println!("  Skipping page {}: it has no embedded image", number);
+            continue;
+        };
+
+        // an image that can't be decoded only costs its page, like a page without one
+        let (data, media_type) = match extract_image(&doc, image) {
+            Ok(extracted) => extracted,
+            Err(err) => {
+                println!("  Skipping page {}: {}", number, err);
+                continue;
+            }
+        };
+        builder
+            .add_page_bytes(&data, media_type, 0, 0)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to add page {}", number))?;
+        page_numbers.page_added();
+    }
+
+    if page_numbers.added() == 0 {
+        return Err(miette!("{} has no embedded page images", args.input.display()).into());
+    }
+
+    let mut outline = Vec::new();
+    if let Some(first) = doc
+        .get(catalog, "Outlines")
+        .as_dict()
+        .and_then(|o| o.get("First"))
+    {
+        let names = named_destinations(&doc, catalog);
+        collect_outline(
+            &doc,
+            first,
+            None,
+            &names,
+            0,
+            &mut HashSet::new(),
+            &mut outline,
+        );
+    }
+
+    let mut sections: usize = 0;
+    for item in outline {
+        if let Some(page) = page_numbers.section_start(&item.page) {
+            builder.add_section(&item.title, page, item.parent.as_deref());
+            sections += 1;
+        }
+    }
+
+    builder.finalize().into_diagnostic()?;
+
+    println!();
+    println!(
+        "Successfully converted to {} ({} pages, {} sections)",
+        args.output.display(),
+        page_numbers.added(),
+        sections
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {
+        super::*,
+        assert2::check as assert,
+        boundbook::BbfReader,
+        flate2::{Compression, write::ZlibEncoder},
+        std::io::Write,
+    };
+
+    /// writes a PDF with a JPEG page (next to a larger image it doesn't draw), a blank page, and a
+    /// 2x1 RGB page drawn through a form
+    fn write_pdf(path: &std::path::Path) {
+        let jpeg = b"\xff\xd8\xff\xe0fake jpeg data\xff\xd9";
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[255, 0, 0, 0, 0, 255]).unwrap();
+        let rgb = encoder.finish().unwrap();
+
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R /Outlines 9 0 R /Lang (ja)
+                /ViewerPreferences << /Direction /R2L >> >>"
+                .to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R 12 0 R] /Count 3 >>".to_vec(),
+            b"<< /Type /Page /Parent 2 0 R /Contents 17 0 R
+                /Resources << /XObject << /Im1 4 0 R /Unused 5 0 R >> >> >>"
+                .to_vec(),
+        ];
+        let mut image = format!(
+            "<< /Type /XObject /Subtype /Image /Width 600 /Height 800 /ColorSpace /DeviceRGB \
+             /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+            jpeg.len()
+        )
+        .into_bytes();
+        image.extend_from_slice(jpeg);
+        image.extend_from_slice(b"\nendstream");
+        objects.push(image);
+        objects.push(
+            b"<< /Subtype /Image /Width 4000 /Height 4000 /ColorSpace /DeviceGray \
+              /BitsPerComponent 8 /Length 1 >>\nstream\n\x00\nendstream"
+                .to_vec(),
+        );
+        objects.push(b"<< /Type /Page /Parent 12 0 R >>".to_vec());
+        let mut raw = format!(
+            "<< /Subtype /Image /Width 2 /Height 1 /ColorSpace [/ICCBased 8 0 R] \
+             /BitsPerComponent 8 /Filter /FlateDecode /Length {} >>\nstream\n",
+            rgb.len()
+        )
+        .into_bytes();
+        raw.extend_from_slice(&rgb);
+        raw.extend_from_slice(b"\nendstream");
+        objects.push(raw);
+        objects.push(b"<< /N 3 /Length 0 >>\nstream\n\nendstream".to_vec());
+        objects.push(b"<< /First 10 0 R /Last 10 0 R >>".to_vec());
+        objects.push(
+            b"<< /Title <FEFF30DC30EA30E530FC30E0> /Dest [3 0 R /Fit] /First 11 0 R /Last 11 0 R >>"
+                .to_vec(),
+        );
+        objects.push(b"<< /Title (Chapter \\(2\\)) /A << /S /GoTo /D (ch2) >> >>".to_vec());
+        objects.push(b"<< /Type /Pages /Parent 2 0 R /Kids [6 0 R 13 0 R] /Count 2 >>".to_vec());
+        objects.push(
+            b"<< /Type /Page /Parent 12 0 R /Contents 18 0 R
+                /Resources << /XObject << /Fm 14 0 R >> >> >>"
+                .to_vec(),
+        );
+        objects.push(
+            b"<< /Type /XObject /Subtype /Form /Resources << /XObject << /Im 7 0 R >> >> \
+              /Length 6 >>\nstream\n/Im Do\nendstream"
+                .to_vec(),
+        );
+        objects.push(b"<< /Names [(ch2) [13 0 R /Fit]] >>".to_vec());
+        objects.push(b"<< /Title (Sample Volume) /Author (Jane Doe) >>".to_vec());
+        objects
+            .push(b"<< /Length 30 >>\nstream\nq 600 0 0 800 0 0 cm /Im1 Do Q\nendstream".to_vec());
+        objects.push(b"<< /Length 10 >>\nstream\nq /Fm Do Q\nendstream".to_vec());
+        objects[0] = b"<< /Type /Catalog /Pages 2 0 R /Outlines 9 0 R /Lang (ja)
+            /Names << /Dests 15 0 R >> /ViewerPreferences << /Direction /R2L >> >>"
+            .to_vec();
+
+        write_objects(path, &objects, "<< /Root 1 0 R /Info 16 0 R /Size 19 >>");
+    }
+
+    /// writes numbered objects, starting at 1, and a trailer as a PDF
+    fn write_objects(path: &std::path::Path, objects: &[Vec<u8>], trailer: &str) {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        for (number, object) in (1..).zip(objects) {
+            pdf.extend_from_slice(format!("{} 0 obj\n", number).as_bytes());
+            pdf.extend_from_slice(object);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+        pdf.extend_from_slice(format!("trailer\n{}\n%%EOF\n", trailer).as_bytes());
+        fs::write(path, pdf).unwrap();
+    }
+
+    #[test]
+    fn test_page_images_and_outline_are_imported() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf = dir.path().join("volume.pdf");
+        write_pdf(&pdf);
+        let output = dir.path().join("volume.bbf");
+
+        execute(FromPdfArgs {
+            input: pdf,
+            output: output.clone(),
+            metadata: vec!["Author:Someone Else".into()],
+            force: false,
+        })
+        .unwrap();
+
+        let reader = BbfReader::open(output).unwrap();
+        let pages = reader.pages().unwrap();
+        let assets = reader.assets().unwrap();
+        assert!(pages.len() == 2);
+
+        let first = &assets[pages[0].asset_index as usize];
+        assert!(MediaType::from(first.media_type) == MediaType::Jpg);
+        assert!(
+            reader
+                .get_asset_data(first)
+                .unwrap()
+                .starts_with(b"\xff\xd8\xff")
+        );
+
+        let second = &assets[pages[1].asset_index as usize];
+        assert!(MediaType::from(second.media_type) == MediaType::Png);
+        let decoded = image::load_from_memory(&reader.get_asset_data(second).unwrap())
+            .unwrap()
+            .to_rgb8();
+        assert!(decoded.dimensions() == (2, 1));
+        assert!(decoded.as_raw() == &[255, 0, 0, 0, 0, 255]);
+
+        assert!(reader.get_metadata("Title").unwrap() == Some("Sample Volume"));
+        assert!(reader.get_metadata("Author").unwrap() == Some("Someone Else"));
+        assert!(reader.get_metadata("Language").unwrap() == Some("ja"));
+        assert!(reader.get_metadata("ReadingDirection").unwrap() == Some("rtl"));
+
+        let tree = reader.section_tree().unwrap();
+        let sections: Vec<_> = tree
+            .iter()
+            .map(|s| (s.title(), s.start(), s.parent().map(|p| p.title())))
+            .collect();
+        assert!(
+            sections
+                == [
+                    ("ボリューム", 0, None),
+                    ("Chapter (2)", 1, Some("ボリューム")),
+                ]
+        );
+    }
+
+    #[test]
+    fn test_pages_with_unsupported_filters_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let pdf = dir.path().join("mixed.pdf");
+        let image = |filter: &str, data: &[u8]| {
+            let mut object = format!(
+                "<< /Subtype /Image /Width 1 /Height 1 /ColorSpace /DeviceGray \
+                 /BitsPerComponent 8 {} /Length {} >>\nstream\n",
+                filter,
+                data.len()
+            )
+            .into_bytes();
+            object.extend_from_slice(data);
+            object.extend_from_slice(b"\nendstream");
+            object
+        };
+        let page = |image: u32| {
+            format!(
+                "<< /Type /Page /Parent 2 0 R /Contents 9 0 R \
+                 /Resources << /XObject << /Im {} 0 R >> >> >>",
+                image
+            )
+            .into_bytes()
+        };
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[0x80]).unwrap();
+        let gray = encoder.finish().unwrap();
+        let objects = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            b"<< /Type /Pages /Kids [3 0 R 5 0 R 7 0 R] /Count 3 >>".to_vec(),
+            page(4),
+            image("/Filter /LZWDecode", b"\x80\x0b\x60\x50"),
+            page(6),
+            image(
+                "/Filter /FlateDecode /DecodeParms << /Predictor 2 >>",
+                &gray,
+            ),
+            page(8),
+            image("/Filter /FlateDecode", &gray),
+            b"<< /Length 6 >>\nstream\n/Im Do\nendstream".to_vec(),
+        ];
+        write_objects(&pdf, &objects, "<< /Root 1 0 R /Size 10 >>");
+        let output = dir.path().join("mixed.bbf");
+
+        execute(FromPdfArgs {
+            input: pdf,
+            output: output.clone(),
+            metadata: Vec::new(),
+            force: false,
+        })
+        .unwrap();
+
+        let reader = BbfReader::open(output).unwrap();
+        assert!(reader.page_count() == 1);
+    }
+
+    #[test]
+    fn test_unpack_samples_expands_low_bit_depths() {
+        assert!(unpack_samples(&[0b1011_0000], 4, 1, 1).unwrap() == [1, 0, 1, 1]);
+        assert!(unpack_samples(&[0b1110_0100], 4, 1, 2).unwrap() == [3, 2, 1, 0]);
+        assert!(unpack_samples(&[1, 2], 4, 1, 8).is_err());
+    }
+}
@@ -0,0 +1,993 @@
+use {
+    boundbook::Result,
+    flate2::read::ZlibDecoder,
+    miette::{Context, IntoDiagnostic, miette},
+    std::{collections::HashMap, io::Read, ops::Range},
+};
+
+/// how deeply arrays and dictionaries may nest before an object is rejected
+const MAX_NESTING: u8 = 64;
+
+/// how many references are followed before giving up on a chain
+const MAX_REFERENCE_CHAIN: u8 = 16;
+
+/// how large a stream may grow when inflated, so a small compressed stream can't exhaust memory
+const MAX_INFLATED_LEN: u64 = 512 << 20;
+
+/// a PDF dictionary, keyed by name without the leading `/`
+pub type Dictionary = HashMap<String, Object>;
+
+/// a PDF object
+#[derive(Debug, Clone, PartialEq)]
+pub enum Object {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Real(f64),
+    /// a string, as raw bytes (see [`text_string`])
+    String(Vec<u8>),
+    /// a name, without the leading `/`
+    Name(String),
+    Array(Vec<Object>),
+    Dictionary(Dictionary),
+    Stream(Stream),
+    /// a reference to an indirect object, by object number
+    ///
+    /// generation numbers are ignored: the last definition of an object in the file wins
+    Reference(u32),
+}
+
+/// a stream object
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stream {
+    pub dict: Dictionary,
+    /// where the still-encoded data lies in the file
+    data: Range<usize>,
+}
+
+static NULL: Object = Object::Null;
+
+impl Object {
+    pub const fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_name(&self) -> Option<&str> {
+        match self {
+            Self::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&[u8]> {
+        match self {
+            Self::String(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Object]> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// the dictionary of a dictionary or stream object
+    pub const fn as_dict(&self) -> Option<&Dictionary> {
+        match self {
+            Self::Dictionary(dict) | Self::Stream(Stream { dict, .. }) => Some(dict),
+            _ => None,
+        }
+    }
+
+    pub const fn as_stream(&self) -> Option<&Stream> {
+        match self {
+            Self::Stream(stream) => Some(stream),
+            _ => None,
+        }
+    }
+}
+
+/// a parsed PDF file
+///
+/// objects are found by scanning the file for `obj` definitions rather than trusting the
+/// cross-reference table, which is frequently broken in files produced by scanning software.
+/// objects packed into object streams are unpacked up front.
+pub struct Document {
+    data: Vec<u8>,
+    objects: HashMap<u32, Object>,
+    /// the trailer dictionaries (and cross-reference stream dictionaries) merged in file order
+    trailer: Dictionary,
+}
+
+impl Document {
+    /// parses a PDF file
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the file isn't a PDF or is encrypted
+    pub fn parse(data: Vec<u8>) -> Result<Self> {
+        if find(&data, b"%PDF-", 0).is_none_or(|start| start > 1024) {
+            return Err(miette!("Not a PDF file").into());
+        }
+
+        let mut objects = HashMap::new();
+        let mut trailers: Vec<(usize, Dictionary)> = Vec::new();
+
+        let mut pos = 0;
+        while let Some(found) = find(&data, b"obj", pos) {
+            pos = found.saturating_add(3);
+            let Some(number) = object_number(&data, found) else {
+                continue;
+            };
+
+            let mut parser = Parser::new(&data, pos);
+            if let Some(object) = parser.parse_indirect() {
+                if let Some(dict) = object.as_dict()
+                    && dict.get("Type").and_then(Object::as_name) == Some("XRef")
+                {
+                    trailers.push((found, dict.clone()));
+                }
+                objects.insert(number, object);
+                pos = parser.pos;
+            }
+        }
+
+        pos = 0;
+        while let Some(found) = find(&data, b"trailer", pos) {
+            pos = found.saturating_add(7);
+            if let Some(Object::Dictionary(dict)) = Parser::new(&data, pos).parse_object(0) {
+                trailers.push((found, dict));
+            }
+        }
+
+        trailers.sort_by_key(|(position, _)| *position);
+        let trailer = trailers.into_iter().flat_map(|(_, dict)| dict).collect();
+
+        let mut document = Self {
+            data,
+            objects,
+            trailer,
+        };
+
+        if document.trailer.contains_key("Encrypt") {
+            return Err(miette!("Encrypted PDFs aren't supported").into());
+        }
+
+        document.unpack_object_streams()?;
+        Ok(document)
+    }
+
+    /// adds the objects stored in object streams, without replacing directly defined ones
+    fn unpack_object_streams(&mut self) -> Result<()> {
+        let mut unpacked = Vec::new();
+
+        for object in self.objects.values() {
+            let Some(stream) = object.as_stream() else {
+                continue;
+            };
+            if stream.dict.get("Type").and_then(Object::as_name) != Some("ObjStm") {
+                continue;
+            }
+
+            let (data, _) = self.decode_stream(stream)?;
+            let count = self.integer(&stream.dict, "N").unwrap_or(0);
+            let first = usize::try_from(self.integer(&stream.dict, "First").unwrap_or(0))
+                .into_diagnostic()?;
+
+            let mut header = Parser::new(&data, 0);
+            for _ in 0..count {
+                let (Some(Object::Integer(number)), Some(Object::Integer(offset))) =
+                    (header.parse_object(0), header.parse_object(0))
+                else {
+                    break;
+                };
+                let (Ok(number), Ok(offset)) = (u32::try_from(number), usize::try_from(offset))
+                else {
+                    continue;
+                };
+                if let Some(object) =
+                    Parser::new(&data, first.saturating_add(offset)).parse_object(0)
+                {
+                    unpacked.push((number, object));
+                }
+            }
+        }
+
+        for (number, object) in unpacked {
+            self.objects.entry(number).or_insert(object);
+        }
+
+        Ok(())
+    }
+
+    /// the merged trailer dictionary
+    pub const fn trailer(&self) -> &Dictionary {
+        &self.trailer
+    }
+
+    /// the document catalog
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the trailer has no usable `Root`
+    pub fn catalog(&self) -> Result<&Dictionary> {
+        self.get(&self.trailer, "Root")
+            .as_dict()
+            .or_else(|| {
+                self.objects
+                    .values()
+                    .filter_map(Object::as_dict)
+                    .find(|dict| dict.get("Type").and_then(Object::as_name) == Some("Catalog"))
+            })
+            .ok_or_else(|| miette!("The PDF has no document catalog").into())
+    }
+
+    /// follows references until reaching a direct object
+    ///
+    /// # Returns
+    ///
+    /// the object, or [`Object::Null`] for references to missing objects
+    pub fn resolve<'a>(&'a self, mut object: &'a Object) -> &'a Object {
+        for _ in 0..MAX_REFERENCE_CHAIN {
+            match object {
+                Object::Reference(number) => {
+                    object = self.objects.get(number).unwrap_or(&NULL);
+                }
+                _ => return object,
+            }
+        }
+        &NULL
+    }
+
+    /// looks up a dictionary entry, following references
+    pub fn get<'a>(&'a self, dict: &'a Dictionary, key: &str) -> &'a Object {
+        dict.get(key).map_or(&NULL, |object| self.resolve(object))
+    }
+
+    /// looks up an integer dictionary entry, following references
+    pub fn integer(&self, dict: &Dictionary, key: &str) -> Option<i64> {
+        self.get(dict, key).as_integer()
+    }
+
+    /// applies a stream's general-purpose filters, stopping at the first image codec
+    ///
+    /// # Returns
+    ///
+    /// the data, and the name of the filter it's still encoded with (such as `DCTDecode`) if
+    /// it's an image codec
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the data lies outside the file, a filter isn't supported or the
+    /// data is corrupt
+    pub fn decode_stream(&self, stream: &Stream) -> Result<(Vec<u8>, Option<String>)> {
+        let mut data = self
+            .data
+            .get(stream.data.clone())
+            .ok_or_else(|| miette!("Stream data lies outside the file"))?
+            .to_vec();
+
+        let filters: Vec<&Object> = match self.get(&stream.dict, "Filter") {
+            Object::Array(filters) => filters.iter().map(|f| self.resolve(f)).collect(),
+            Object::Null => Vec::new(),
+            filter => vec![filter],
+        };
+        let params: Vec<&Object> = match self.get(&stream.dict, "DecodeParms") {
+            Object::Array(params) => params.iter().map(|p| self.resolve(p)).collect(),
+            params => vec![params],
+        };
+
+        for (i, filter) in filters.iter().enumerate() {
+            let name = filter
+                .as_name()
+                .ok_or_else(|| miette!("Stream filter isn't a name"))?;
+            let params = params.get(i).and_then(|p| p.as_dict());
+
+            match name {
+                "FlateDecode" | "Fl" => {
+                    let inflated = inflate(&data, MAX_INFLATED_LEN)?;
+                    data = match params {
+                        Some(params) => self.unpredict(inflated, params)?,
+                        None => inflated,
+                    };
+                }
+                "DCTDecode" | "DCT" | "JPXDecode" | "CCITTFaxDecode" | "CCF" | "JBIG2Decode" => {
+                    return Ok((data, Some(name.to_string())));
+                }
+                other => return Err(miette!("Unsupported stream filter {}", other).into()),
+            }
+        }
+
+        Ok((data, None))
+    }
+
+    /// reverses the predictor applied before flate compression
+    #[macroni_n_cheese::mathinator2000]
+    fn unpredict(&self, data: Vec<u8>, params: &Dictionary) -> Result<Vec<u8>> {
+        let predictor = self.integer(params, "Predictor").unwrap_or(1);
+        if predictor < 10 {
+            return match predictor {
+                1 => Ok(data),
+                _ => Err(miette!("Unsupported TIFF predictor {}", predictor).into()),
+            };
+        }
+
+        let dimension = |key: &str, default: i64| {
+            usize::try_from(self.integer(params, key).unwrap_or(default))
+                .ok()
+                .filter(|&value| value > 0 && value <= 1 << 16)
+                .ok_or_else(|| miette!("Invalid predictor {} in stream", key))
+        };
+        let colors = dimension("Colors", 1)?;
+        let bits = dimension("BitsPerComponent", 8)?;
+        let columns = dimension("Columns", 1)?;
+
+        let pixel = (colors * bits).div_ceil(8);
+        let row = (colors * bits * columns).div_ceil(8);
+        if row > data.len() {
+            return Err(miette!("Predictor rows are longer than the stream").into());
+        }
+        Ok(unfilter_png(&data, row, pixel))
+    }
+}
+
+/// inflates zlib data, refusing to produce more than `limit` bytes
+///
+/// # Errors
+///
+/// returns an error if the data is corrupt or inflates to more than `limit` bytes
+fn inflate(data: &[u8], limit: u64) -> Result<Vec<u8>> {
+    let mut inflated = Vec::new();
+    ZlibDecoder::new(data)
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut inflated)
+        .into_diagnostic()
+        .context("Failed to inflate stream")?;
+
+    if inflated.len() as u64 > limit {
+        return Err(miette!("Stream inflates to more than {} bytes", limit).into());
+    }
+    Ok(inflated)
+}
+
+/// reverses PNG row filters, where every row is prefixed with its filter type
+///
+/// a truncated final row is dropped
+#[macroni_n_cheese::mathinator2000]
+pub fn unfilter_png(data: &[u8], row: usize, pixel: usize) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::with_capacity(data.len());
+    let mut previous = vec![0u8; row];
+
+    for chunk in data.chunks_exact(row + 1) {
+        let filter = chunk[0];
+        let mut current = chunk[1..].to_vec();
+
+        for i in 0..row {
+            let left = if i >= pixel { current[i - pixel] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= pixel { previous[i - pixel] } else { 0 };
+
+            let predicted = match filter {
+                1 => left,
+                2 => up,
+                3 => left.midpoint(up),
+                4 => paeth(left, up, up_left),
+                _ => 0,
+            };
+            current[i] = current[i].wrapping_add(predicted);
+        }
+
+        out.extend_from_slice(&current);
+        previous = current;
+    }
+
+    out
+}
+
+#[macroni_n_cheese::mathinator2000]
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let to_left = (estimate - i16::from(left)).abs();
+    let to_up = (estimate - i16::from(up)).abs();
+    let to_up_left = (estimate - i16::from(up_left)).abs();
+
+    if to_left <= to_up && to_left <= to_up_left {
+        left
+    } else if to_up <= to_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// decodes a PDF text string: UTF-16BE or UTF-8 with a byte order mark, otherwise
+/// PDFDocEncoding (read as Latin-1, which it matches for printable characters)
+pub fn text_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xfe, 0xff]) {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if let Some(utf8) = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]) {
+        String::from_utf8_lossy(utf8).into_owned()
+    } else {
+        bytes.iter().map(|&b| char::from(b)).collect()
+    }
+}
+
+/// lists the XObjects a content stream paints with `Do`, in drawing order
+///
+/// operands are read with the object parser and every other operator is ignored; the data of
+/// inline images (between `ID` and `EI`) is skipped, since it's binary. an operand that fails to
+/// parse is skipped along with everything the parser read, so broken content can't make it
+/// reparse the same bytes over and over
+pub fn drawn_xobjects(content: &[u8]) -> Vec<String> {
+    let mut parser = Parser::new(content, 0);
+    let mut names = Vec::new();
+    // the last operand, if it was a name
+    let mut operand = None;
+
+    loop {
+        parser.skip_whitespace();
+        let Some(byte) = parser.peek() else {
+            break;
+        };
+
+        if is_regular(byte) && !matches!(byte, b'+' | b'-' | b'.' | b'0'..=b'9') {
+            match parser.token() {
+                b"Do" => names.extend(operand.take()),
+                b"ID" => parser.pos = inline_image_end(content, parser.pos),
+                _ => {}
+            }
+            operand = None;
+            continue;
+        }
+
+        let start = parser.pos;
+        operand = match parser.parse_object(0) {
+            Some(Object::Name(name)) => Some(name),
+            Some(_) => None,
+            None => {
+                // a stray delimiter or an unterminated operand; step over it
+                parser.pos = parser.pos.max(start.saturating_add(1));
+                None
+            }
+        };
+    }
+
+    names
+}
+
+/// finds the end of an inline image's data, just past its `EI` operator
+fn inline_image_end(content: &[u8], from: usize) -> usize {
+    let mut at = from;
+    while let Some(found) = find(content, b"EI", at) {
+        let end = found.saturating_add(2);
+        let before = found.checked_sub(1).and_then(|i| content.get(i));
+        let after = content.get(end);
+        if before.is_some_and(|&b| is_whitespace(b)) && after.is_none_or(|&b| !is_regular(b)) {
+            return end;
+        }
+        at = end;
+    }
+    content.len()
+}
+
+/// finds the first occurrence of `needle` at or after `from`
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .and_then(|i| i.checked_add(from))
+}
+
+const fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, 0 | b'\t' | b'\n' | 0x0c | b'\r' | b' ')
+}
+
+const fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+const fn is_regular(byte: u8) -> bool {
+    !is_whitespace(byte) && !is_delimiter(byte)
+}
+
+/// reads the object number of an `N G obj` header whose `obj` keyword is at `at`
+fn object_number(data: &[u8], at: usize) -> Option<u32> {
+    if data
+        .get(at.saturating_add(3))
+        .is_some_and(|&b| is_regular(b))
+    {
+        return None;
+    }
+
+    let before = data.get(..at)?;
+    let rest = before.trim_ascii_end();
+    if rest.len() == before.len() {
+        return None;
+    }
+    let generation_start = rest
+        .iter()
+        .rposition(|b| !b.is_ascii_digit())?
+        .checked_add(1)?;
+    if generation_start == rest.len() {
+        return None;
+    }
+
+    let before = &rest[..generation_start];
+    let rest = before.trim_ascii_end();
+    if rest.len() == before.len() {
+        return None;
+    }
+    let number_start = rest
+        .iter()
+        .rposition(|b| !b.is_ascii_digit())
+        .map_or(Some(0), |i| i.checked_add(1))?;
+    let number = &rest[number_start..];
+    if number.is_empty() || (number_start > 0 && is_regular(rest[number_start.checked_sub(1)?])) {
+        return None;
+    }
+
+    std::str::from_utf8(number).ok()?.parse().ok()
+}
+
+/// reads objects from PDF syntax
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    const fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    const fn bump(&mut self, by: usize) {
+        self.pos = self.pos.saturating_add(by);
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.bump(1);
+        Some(byte)
+    }
+
+    fn starts_with(&self, prefix: &[u8]) -> bool {
+        self.data
+            .get(self.pos..)
+            .is_some_and(|rest| rest.starts_with(prefix))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if is_whitespace(byte) {
+                self.bump(1);
+            } else if byte == b'%' {
+                while self.peek().is_some_and(|b| b != b'\n' && b != b'\r') {
+                    self.bump(1);
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// reads a run of regular characters
+    fn token(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(is_regular) {
+            self.bump(1);
+        }
+        &self.data[start..self.pos]
+    }
+
+    /// parses the body of an indirect object, including its stream data
+    fn parse_indirect(&mut self) -> Option<Object> {
+        let object = self.parse_object(0)?;
+        self.skip_whitespace();
+
+        let object = match object {
+            Object::Dictionary(dict) if self.starts_with(b"stream") => {
+                self.bump(6);
+                let data = self.stream_data(&dict)?;
+                Object::Stream(Stream { dict, data })
+            }
+            object => object,
+        };
+
+        self.skip_whitespace();
+        if self.starts_with(b"endobj") {
+            self.bump(6);
+        }
+        Some(object)
+    }
+
+    /// finds the extent of stream data, right after the `stream` keyword
+    ///
+    /// a direct `Length` is trusted if `endstream` follows it; otherwise the data runs to the
+    /// next `endstream`
+    fn stream_data(&mut self, dict: &Dictionary) -> Option<Range<usize>> {
+        if self.peek() == Some(b'\r') {
+            self.bump(1);
+        }
+        if self.peek() == Some(b'\n') {
+            self.bump(1);
+        }
+        let start = self.pos;
+
+        let by_length = dict
+            .get("Length")
+            .and_then(Object::as_integer)
+            .and_then(|length| usize::try_from(length).ok())
+            .and_then(|length| start.checked_add(length))
+            .filter(|&end| {
+                self.data
+                    .get(end..)
+                    .is_some_and(|rest| rest.trim_ascii_start().starts_with(b"endstream"))
+            });
+
+        let end = match by_length {
+            Some(end) => end,
+            None => {
+                let found = find(self.data, b"endstream", start)?;
+                let data = &self.data[start..found];
+                let trimmed = data
+                    .strip_suffix(b"\r\n")
+                    .or_else(|| data.strip_suffix(b"\n"))
+                    .or_else(|| data.strip_suffix(b"\r"))
+                    .unwrap_or(data);
+                start.checked_add(trimmed.len())?
+            }
+        };
+
+        self.pos = end;
+        self.skip_whitespace();
+        if self.starts_with(b"endstream") {
+            self.bump(9);
+        }
+        Some(start..end)
+    }
+
+    /// parses a direct object, or a reference
+    fn parse_object(&mut self, depth: u8) -> Option<Object> {
+        if depth > MAX_NESTING {
+            return None;
+        }
+        let depth = depth.saturating_add(1);
+        self.skip_whitespace();
+
+        match self.peek()? {
+            b'/' => {
+                self.bump(1);
+                Some(Object::Name(self.name()))
+            }
+            b'(' => {
+                self.bump(1);
+                self.literal_string().map(Object::String)
+            }
+            b'<' if self.starts_with(b"<<") => {
+                self.bump(2);
+                let mut dict = Dictionary::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.starts_with(b">>") {
+                        self.bump(2);
+                        return Some(Object::Dictionary(dict));
+                    }
+                    let Object::Name(key) = self.parse_object(depth)? else {
+                        return None;
+                    };
+                    let value = self.parse_object(depth)?;
+                    dict.insert(key, value);
+                }
+            }
+            b'<' => {
+                self.bump(1);
+                self.hex_string().map(Object::String)
+            }
+            b'[' => {
+                self.bump(1);
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek()? == b']' {
+                        self.bump(1);
+                        return Some(Object::Array(items));
+                    }
+                    items.push(self.parse_object(depth)?);
+                }
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => self.number(),
+            _ => match self.token() {
+                b"true" => Some(Object::Bool(true)),
+                b"false" => Some(Object::Bool(false)),
+                b"null" => Some(Object::Null),
+                _ => None,
+            },
+        }
+    }
+
+    /// parses a number, or a reference if the number is followed by a generation and `R`
+    fn number(&mut self) -> Option<Object> {
+        let token = std::str::from_utf8(self.token()).ok()?;
+        if token.contains('.') {
+            return token.parse().ok().map(Object::Real);
+        }
+        let value: i64 = token.parse().ok()?;
+
+        if let Ok(number) = u32::try_from(value) {
+            let after = self.pos;
+            self.skip_whitespace();
+            let generation = self.token();
+            self.skip_whitespace();
+            if !generation.is_empty()
+                && generation.iter().all(u8::is_ascii_digit)
+                && self.token() == b"R"
+            {
+                return Some(Object::Reference(number));
+            }
+            self.pos = after;
+        }
+
+        Some(Object::Integer(value))
+    }
+
+    /// reads a name after its `/`, decoding `#xx` escapes
+    fn name(&mut self) -> String {
+        let token = self.token();
+        let mut bytes = Vec::with_capacity(token.len());
+        let mut i = 0;
+        while let Some(&byte) = token.get(i) {
+            let escaped = (byte == b'#')
+                .then(|| token.get(i.saturating_add(1)..i.saturating_add(3)))
+                .flatten()
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match escaped {
+                Some(decoded) => {
+                    bytes.push(decoded);
+                    i = i.saturating_add(3);
+                }
+                None => {
+                    bytes.push(byte);
+                    i = i.saturating_add(1);
+                }
+            }
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// reads a literal string after its opening parenthesis
+    fn literal_string(&mut self) -> Option<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut open = 1usize;
+
+        loop {
+            match self.next()? {
+                b'\\' => match self.next()? {
+                    b'n' => bytes.push(b'\n'),
+                    b'r' => bytes.push(b'\r'),
+                    b't' => bytes.push(b'\t'),
+                    b'b' => bytes.push(0x08),
+                    b'f' => bytes.push(0x0c),
+                    b'\r' => {
+                        if self.peek() == Some(b'\n') {
+                            self.bump(1);
+                        }
+                    }
+                    b'\n' => {}
+                    digit @ b'0'..=b'7' => {
+                        let mut value = u32::from(digit.saturating_sub(b'0'));
+                        for _ in 0..2 {
+                            match self.peek() {
+                                Some(next @ b'0'..=b'7') => {
+                                    value = value
+                                        .saturating_mul(8)
+                                        .saturating_add(u32::from(next.saturating_sub(b'0')));
+                                    self.bump(1);
+                                }
+                                _ => break,
+                            }
+                        }
+                        bytes.push(u8::try_from(value & 0xff).unwrap_or_default());
+                    }
+                    other => bytes.push(other),
+                },
+                b'(' => {
+                    open = open.saturating_add(1);
+                    bytes.push(b'(');
+                }
+                b')' => {
+                    open = open.saturating_sub(1);
+                    if open == 0 {
+                        return Some(bytes);
+                    }
+                    bytes.push(b')');
+                }
+                byte => bytes.push(byte),
+            }
+        }
+    }
+
+    /// reads a hex string after its opening angle bracket
+    fn hex_string(&mut self) -> Option<Vec<u8>> {
+        let mut digits = Vec::new();
+        loop {
+            match self.next()? {
+                b'>' => break,
+                byte if byte.is_ascii_hexdigit() => digits.push(byte),
+                byte if is_whitespace(byte) => {}
+                _ => return None,
+            }
+        }
+        if digits.len() % 2 == 1 {
+            digits.push(b'0');
+        }
+
+        digits
+            .chunks_exact(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {
+        super::*,
+        assert2::check as assert,
+        flate2::{Compression, write::ZlibEncoder},
+        std::io::Write,
+    };
+
+    fn parse(source: &str) -> Option<Object> {
+        Parser::new(source.as_bytes(), 0).parse_object(0)
+    }
+
+    #[test]
+    fn test_parses_objects_and_references() {
+        let object = parse(
+            r"<< /Type /Page /Kids [1 0 R 2 0 R] /Count 2 /Scale -1.5
+               /Title (Chapter \(1\)\n\101) /Hex <48 69 7> /Odd#20Name true /None null >>",
+        )
+        .unwrap();
+        let dict = object.as_dict().unwrap();
+
+        assert!(dict["Type"] == Object::Name("Page".into()));
+        assert!(dict["Kids"] == Object::Array(vec![Object::Reference(1), Object::Reference(2)]));
+        assert!(dict["Count"] == Object::Integer(2));
+        assert!(dict["Scale"] == Object::Real(-1.5));
+        assert!(dict["Title"] == Object::String(b"Chapter (1)\nA".to_vec()));
+        assert!(dict["Hex"] == Object::String(b"Hip".to_vec()));
+        assert!(dict["Odd Name"] == Object::Bool(true));
+        assert!(dict["None"] == Object::Null);
+
+        assert!(
+            parse("[1 2 3]")
+                == Some(Object::Array(vec![
+                    Object::Integer(1),
+                    Object::Integer(2),
+                    Object::Integer(3),
+                ]))
+        );
+    }
+
+    #[test]
+    fn test_finds_objects_in_object_streams_and_follows_references() {
+        let packed = b"7 0 8 17 << /Answer 42 >> (packed)";
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(packed).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut pdf = b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog /Answer 7 0 R >>\nendobj\n".to_vec();
+        pdf.extend_from_slice(
+            format!(
+                "2 0 obj\n<< /Type /ObjStm /N 2 /First 9 /Filter /FlateDecode /Length {} >>\nstream\n",
+                compressed.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&compressed);
+        pdf.extend_from_slice(b"\nendstream\nendobj\ntrailer\n<< /Root 1 0 R >>\n%%EOF\n");
+
+        let document = Document::parse(pdf).unwrap();
+        let catalog = document.catalog().unwrap();
+        let answer = document.get(catalog, "Answer").as_dict().unwrap();
+        assert!(answer["Answer"] == Object::Integer(42));
+        assert!(document.resolve(&Object::Reference(8)).as_string() == Some(&b"packed"[..]));
+        assert!(*document.resolve(&Object::Reference(99)) == Object::Null);
+    }
+
+    #[test]
+    fn test_unfilter_png_reverses_row_filters() {
+        // two rows of three one-byte pixels: "sub" then "up"
+        let filtered = [1, 10, 5, 5, 2, 1, 1, 1];
+        assert!(unfilter_png(&filtered, 3, 1) == [10, 15, 20, 11, 16, 21]);
+    }
+
+    #[test]
+    fn test_predictor_rows_longer_than_the_stream_are_rejected() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[0; 16]).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut pdf = b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog >>\nendobj\n".to_vec();
+        pdf.extend_from_slice(
+            format!(
+                "2 0 obj\n<< /Filter /FlateDecode /DecodeParms << /Predictor 12 /Colors 65536 \
+                 /BitsPerComponent 65536 /Columns 65536 >> /Length {} >>\nstream\n",
+                compressed.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&compressed);
+        pdf.extend_from_slice(b"\nendstream\nendobj\ntrailer\n<< /Root 1 0 R >>\n%%EOF\n");
+
+        let document = Document::parse(pdf).unwrap();
+        let Object::Stream(stream) = document.resolve(&Object::Reference(2)) else {
+            panic!("not a stream");
+        };
+        assert!(document.decode_stream(stream).is_err());
+    }
+
+    #[test]
+    fn test_finds_xobjects_drawn_by_content_streams() {
+        let content = b"q 600 0 0 800 0 0 cm /Im1 Do Q
+            BT /F1 12 Tf (not /Fake Do) Tj [(a) -3 (b)] TJ ET
+            % /Comment Do
+            BI /W 2 /H 1 /BPC 8 /CS /G ID \xffEI /Hidden Do EI
+            /Fm#20A Do /Tiny /Other Do";
+
+        assert!(drawn_xobjects(content) == ["Im1", "Fm A", "Other"]);
+    }
+
+    #[test]
+    fn test_broken_operands_are_read_once() {
+        // an unterminated string or hex string swallows the rest of the stream
+        let mut content = b"/Im1 Do ".to_vec();
+        content.extend(std::iter::repeat_n(b'(', 1 << 20));
+        assert!(drawn_xobjects(&content) == ["Im1"]);
+
+        let mut content = b"/Im1 Do ".to_vec();
+        content.extend(std::iter::repeat_n(b'<', 1 << 20));
+        assert!(drawn_xobjects(&content) == ["Im1"]);
+
+        assert!(drawn_xobjects(b") ] } /Im2 Do") == ["Im2"]);
+    }
+
+    #[test]
+    fn test_oversized_streams_are_rejected() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&[0u8; 4096]).unwrap();
+        let deflated = encoder.finish().unwrap();
+
+        assert!(inflate(&deflated, 4096).unwrap().len() == 4096);
+        assert!(inflate(&deflated, 4095).is_err());
+    }
+
+    #[test]
+    fn test_text_strings_honor_byte_order_marks() {
+        assert!(text_string(&[0xfe, 0xff, 0x00, 0x41, 0x30, 0x42]) == "Aあ");
+        assert!(text_string(b"Caf\xe9") == "Café");
+    }
+}
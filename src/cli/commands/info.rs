@@ -1,5 +1,5 @@
 use {
-    boundbook::{BbfReader, Result},
+    boundbook::{Book, Result, SectionView},
     clap::Args,
     miette::{Context, IntoDiagnostic},
     std::path::PathBuf,
@@ -11,15 +11,46 @@ pub struct InfoArgs {
     input: PathBuf,
 }
 
+#[macroni_n_cheese::mathinator2000]
+fn print_section(section: SectionView, indent: &str, last: bool) {
+    let prefix = if last { "└" } else { "├" };
+    let ri = section.index() + 1;
+    let first_page = section.start() + 1;
+    let pages = match section.end() - section.start() {
+        0 => "(no pages)".to_string(),
+        1 => format!("(page {})", first_page),
+        _ => format!("(pages {}-{})", first_page, section.end()),
+    };
+
+    println!(
+        "  {}{} {:>3}. {:<30} {}",
+        indent,
+        prefix,
+        ri,
+        section.title(),
+        pages
+    );
+
+    let child_indent = format!("{}{}", indent, if last { "  " } else { "│ " });
+    let children: Vec<_> = section.children().collect();
+    for (i, child) in children.iter().enumerate() {
+        print_section(*child, &child_indent, i == children.len() - 1);
+    }
+}
+
 #[macroni_n_cheese::mathinator2000]
 pub fn execute(args: InfoArgs) -> Result<()> {
-    let reader = BbfReader::open(&args.input)
+    let book = Book::open(&args.input)
         .into_diagnostic()
         .with_context(|| format!("Failed to open BBF file: {}", args.input.display()))?;
+    let reader = book.reader();
 
     println!("--- Bound Book Format (.bbf) Info");
     println!("--- File: {}", args.input.display());
     println!("--- BBF Version: {}", reader.version());
+    if reader.is_petrified() {
+        println!("--- Petrified: yes (read-only)");
+    }
     println!("--- Pages: {}", reader.page_count());
 
     let asset_count = reader.asset_count();
@@ -32,40 +63,44 @@ pub fn execute(args: InfoArgs) -> Result<()> {
         println!("--- Assets: {} (deduplicated)", asset_count);
     }
 
-    let sections = reader.sections()?;
-    println!("--- Sections: {}", sections.len());
+    let expansion_count = reader.expansions()?.len();
+    if expansion_count > 0 {
+        println!("--- Expansion Records: {}", expansion_count);
+    }
+
+    if let Some(thumbnail) = reader.thumbnail_record()? {
+        println!("--- Thumbnail: {}x{}", thumbnail.width, thumbnail.height);
+    }
+
+    let tree = book.section_tree();
+    println!("--- Sections: {}", tree.len());
 
-    if !sections.is_empty() {
+    if !tree.is_empty() {
         println!();
-        for (i, section) in sections.iter().enumerate() {
-            let title = reader.get_string(section.section_title_offset)?;
-            let prefix = if i == sections.len() - 1 {
-                "└"
-            } else {
-                "├"
-            };
-
-            let ri = i + 1;
-            let start_page = section.section_start_index + 1;
-            println!(
-                "  {} {:>3}. {:<30} (starts at page {})",
-                prefix, ri, title, start_page
-            );
+        let roots: Vec<_> = tree.roots().collect();
+        for (i, section) in roots.iter().enumerate() {
+            print_section(*section, "", i == roots.len() - 1);
+        }
+
+        for issue in tree.issues() {
+            println!("  ⚠ {}", issue);
         }
     }
 
-    let metadata = reader.metadata()?;
+    let metadata = book.metadata_map();
     if !metadata.is_empty() {
+        let entries: Vec<(&str, String)> = metadata
+            .book_display_order()
+            .map(|(key, value)| (key, value.to_string()))
+            .chain(metadata.iter().filter_map(|(key, value, section)| {
+                section.map(|section| (key, format!("{} ({})", value, section)))
+            }))
+            .collect();
+
         println!();
         println!("--- Metadata:");
-        for (i, meta) in metadata.iter().enumerate() {
-            let key = reader.get_string(meta.key_offset)?;
-            let value = reader.get_string(meta.value_offset)?;
-            let prefix = if i == metadata.len() - 1 {
-                "└"
-            } else {
-                "├"
-            };
+        for (i, (key, value)) in entries.iter().enumerate() {
+            let prefix = if i == entries.len() - 1 { "└" } else { "├" };
             println!("  {} {:<15} {}", prefix, format!("{}:", key), value);
         }
     }
@@ -0,0 +1,53 @@
+use {
+    boundbook::{BbfBuilder, BbfError, BbfReader, Result},
+    clap::Args,
+    miette::{Context, IntoDiagnostic, miette},
+    std::path::{Path, PathBuf},
+};
+
+#[derive(Args)]
+#[command(author = "The Motherfucking Bearodactyl")]
+pub struct PetrifyArgs {
+    /// BBF file to mark as permanently read-only
+    input: PathBuf,
+}
+
+/// Refuse to overwrite an existing petrified BBF file unless `force` is set
+pub fn ensure_not_petrified(path: &Path, force: bool) -> Result<()> {
+    let petrified = BbfReader::open(path)
+        .map(|reader| reader.is_petrified())
+        .unwrap_or(false);
+
+    if petrified && !force {
+        return Err(BbfError::from(miette!(
+            help = "Pass --force to overwrite it anyway",
+            "Refusing to overwrite petrified file: {}",
+            path.display()
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn execute(args: PetrifyArgs) -> Result<()> {
+    let reader = BbfReader::open(&args.input)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to open BBF file: {}", args.input.display()))?;
+
+    if reader.is_petrified() {
+        println!("{} is already petrified", args.input.display());
+        return Ok(());
+    }
+
+    drop(reader);
+
+    let mut builder = BbfBuilder::open_existing(&args.input)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to open {} for editing", args.input.display()))?;
+    builder.petrify();
+    builder.finalize()?;
+
+    println!("✓ Petrified {}", args.input.display());
+
+    Ok(())
+}
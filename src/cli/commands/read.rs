@@ -1,5 +1,5 @@
 use {
-    boundbook::{BbfReader, Result},
+    boundbook::{BbfReader, Result, SectionTree},
     clap::Args,
     interpolate::InterpolationMethod,
     miette::IntoDiagnostic,
@@ -101,6 +101,7 @@ pub fn execute(args: ReadArgs) -> Result<()> {
 
 pub struct BookReader {
     pub reader: BbfReader,
+    pub sections: SectionTree,
     pub current_page: usize,
     pub current_section: Option<usize>,
 }
@@ -131,49 +132,29 @@ impl BookReader {
 
     #[macroni_n_cheese::mathinator2000]
     pub fn next_section(&mut self) {
-        let sections_res = self.reader.sections();
-        if let Ok(sections) = sections_res {
-            if sections.is_empty() {
-                return;
-            }
-            let current_idx = self.current_section.unwrap_or(0);
-            if current_idx + 1 < sections.len() {
-                self.current_page = sections[current_idx + 1].section_start_index as usize;
-                self.current_section = Some(current_idx + 1);
-            }
+        let current_idx = self.current_section.unwrap_or(0);
+        if let Some(section) = self.sections.get(current_idx + 1) {
+            self.current_page = section.start() as usize;
+            self.current_section = Some(section.index());
         }
     }
 
     #[macroni_n_cheese::mathinator2000]
     pub fn prev_section(&mut self) {
-        let sections_res = self.reader.sections();
-        if let Ok(sections) = sections_res {
-            if sections.is_empty() {
-                return;
-            }
-            let current_idx = self.current_section.unwrap_or(0);
-            if current_idx > 0 {
-                self.current_page = sections[current_idx - 1].section_start_index as usize;
-                self.current_section = Some(current_idx - 1);
-            }
+        let current_idx = self.current_section.unwrap_or(0);
+        if current_idx > 0
+            && let Some(section) = self.sections.get(current_idx - 1)
+        {
+            self.current_page = section.start() as usize;
+            self.current_section = Some(section.index());
         }
     }
 
     fn update_current_section(&mut self) {
-        let sections_res = self.reader.sections();
-        if let Ok(sections) = sections_res {
-            if sections.is_empty() {
-                self.current_section = None;
-                return;
-            }
-            for (i, section) in sections.iter().enumerate().rev() {
-                if section.section_start_index as usize <= self.current_page {
-                    self.current_section = Some(i);
-                    return;
-                }
-            }
-            self.current_section = None;
-        }
+        self.current_section = self
+            .sections
+            .section_at(self.current_page as u64)
+            .map(|section| section.index());
     }
 
     pub const fn page_count(&self) -> usize {
@@ -181,12 +162,8 @@ impl BookReader {
     }
 
     pub fn get_section_info(&self) -> Option<String> {
-        if let Some(idx) = self.current_section
-            && let Ok(sections) = self.reader.sections()
-            && let Ok(title) = self.reader.get_string(sections[idx].section_title_offset)
-        {
-            return Some(title.to_string());
-        }
-        None
+        self.current_section
+            .and_then(|idx| self.sections.get(idx))
+            .map(|section| section.title().to_string())
     }
 }
@@ -1,11 +1,11 @@
-#![allow(clippy::arithmetic_side_effects)]
+#![allow(clippy::arithmetic_side_effects, clippy::collapsible_match)]
 use {
     super::{
         BookReader,
         render::{ImageRenderer, RenderConfig},
         state::{self, BookState},
     },
-    boundbook::{BbfReader, Result, types::MediaType},
+    boundbook::{BbfReader, Result, SectionView, types::MediaType},
     crossterm::{
         cursor,
         event::{
@@ -101,11 +101,14 @@ impl TuiApp {
         let max_page = (reader.page_count() as usize).saturating_sub(1);
         let restored_page = persisted.current_page.min(max_page);
 
-        let book_reader = BookReader {
+        let sections = reader.section_tree()?;
+        let mut book_reader = BookReader {
             reader,
+            sections,
             current_page: restored_page,
             current_section: None,
         };
+        book_reader.jump_to_page(restored_page);
 
         let renderer = ImageRenderer::new(config);
 
@@ -151,11 +154,11 @@ impl TuiApp {
         let asset = assets.get(page.asset_index as usize)?;
         let data = self.book_reader.reader.get_asset_data(asset).ok()?;
 
-        let is_gif = ImageRenderer::is_gif(data);
+        let is_gif = ImageRenderer::is_gif(&data);
         if is_gif && self.renderer.config.enable_gif_animation {
-            ImageRenderer::decode_gif_first_frame(data).ok()
+            ImageRenderer::decode_gif_first_frame(&data).ok()
         } else {
-            ImageRenderer::decode_image(data).ok()
+            ImageRenderer::decode_image(&data).ok()
         }
     }
 
@@ -334,13 +337,13 @@ impl TuiApp {
             .get_asset_data(asset)
             .into_diagnostic()?;
 
-        if !ImageRenderer::is_gif(data) {
+        if !ImageRenderer::is_gif(&data) {
             self.notification = Some("Current page is not a GIF".to_string());
             self.notification_time = Some(Instant::now());
             return Ok(());
         }
 
-        let frames = self.renderer.decode_gif_frames(data)?;
+        let frames = self.renderer.decode_gif_frames(&data)?;
         if let Some((first_img, _)) = frames.first() {
             self.current_image = Some(self.picker.new_resize_protocol(first_img.clone()));
         }
@@ -533,81 +536,66 @@ impl TuiApp {
         }
     }
 
-    /// # Panics
-    ///
-    /// panics if it fails to initialize the tree widget
-    fn build_tree_items(&self) -> Vec<TreeItem<'static, usize>> {
-        // (unchanged from original -- omitted for brevity, keep as-is)
-        if let Ok(sections) = self.book_reader.reader.sections()
-            && !sections.is_empty()
-        {
-            let mut section_items = Vec::new();
-
-            for (idx, section) in sections.iter().enumerate() {
-                if let Ok(title) = self
-                    .book_reader
-                    .reader
-                    .get_string(section.section_title_offset)
-                {
-                    let start_page = section.section_start_index as usize;
-                    let end_page = sections
-                        .get(idx + 1)
-                        .map(|s| s.section_start_index as usize)
-                        .unwrap_or(self.book_reader.page_count());
-
-                    let mut page_items = Vec::new();
-                    for page in start_page..end_page {
-                        let bookmark_marker = if self.bookmarks.contains(&page) {
-                            " *"
-                        } else {
-                            ""
-                        };
-                        let current_marker = if page == self.book_reader.current_page {
-                            " <"
-                        } else {
-                            ""
-                        };
-                        page_items.push(TreeItem::new_leaf(
-                            page,
-                            format!("  Page {}{}{}", page + 1, bookmark_marker, current_marker),
-                        ));
-                    }
-
-                    if let Ok(tree_item) =
-                        TreeItem::new(start_page, format!("\u{f024b} {}", title), page_items)
-                    {
-                        section_items.push(tree_item);
-                    }
-                }
-            }
+    fn page_label(&self, page: usize) -> String {
+        let bookmark_marker = if self.bookmarks.contains(&page) {
+            " *"
+        } else {
+            ""
+        };
+        let current_marker = if page == self.book_reader.current_page {
+            " <"
+        } else {
+            ""
+        };
+        format!("Page {}{}{}", page + 1, bookmark_marker, current_marker)
+    }
 
-            if !section_items.is_empty() {
-                return section_items;
-            }
-        }
+    /// builds the sidebar item for a section: its child sections interleaved with the pages no
+    /// child covers
+    fn section_tree_item(&self, section: SectionView) -> Option<TreeItem<'static, usize>> {
+        let mut children: Vec<_> = section.children().collect();
+        children.sort_by_key(|child| child.start());
 
-        (0..self.book_reader.page_count())
-            .map(|page| {
-                let bookmark_marker = if self.bookmarks.contains(&page) {
-                    " *"
-                } else {
-                    ""
-                };
-                let current_marker = if page == self.book_reader.current_page {
-                    " <"
-                } else {
-                    ""
-                };
+        let leaves = |from: u64, to: u64| {
+            (from..to).map(|page| {
                 TreeItem::new_leaf(
-                    page,
-                    format!(
-                        "\u{f0309} Page {}{}{}",
-                        page + 1,
-                        bookmark_marker,
-                        current_marker
-                    ),
+                    page as usize,
+                    format!("  {}", self.page_label(page as usize)),
                 )
             })
+        };
+
+        let mut items = Vec::new();
+        let mut cursor = section.start();
+        for child in children {
+            items.extend(leaves(cursor, child.start()));
+            items.extend(self.section_tree_item(child));
+            cursor = cursor.max(child.end());
+        }
+        items.extend(leaves(cursor, section.end()));
+
+        TreeItem::new(
+            section.start() as usize,
+            format!("\u{f024b} {}", section.title()),
+            items,
+        )
+        .ok()
+    }
+
+    fn build_tree_items(&self) -> Vec<TreeItem<'static, usize>> {
+        let section_items: Vec<_> = self
+            .book_reader
+            .sections
+            .roots()
+            .filter_map(|section| self.section_tree_item(section))
+            .collect();
+
+        if !section_items.is_empty() {
+            return section_items;
+        }
+
+        (0..self.book_reader.page_count())
+            .map(|page| TreeItem::new_leaf(page, format!("\u{f0309} {}", self.page_label(page))))
             .collect()
     }
 
@@ -791,21 +779,19 @@ impl TuiApp {
 
         let mut lines: Vec<Line<'static>> = Vec::new();
 
-        if let Ok(metadata) = self.book_reader.reader.metadata() {
+        if let Ok(metadata) = self.book_reader.reader.metadata_map() {
             if metadata.is_empty() {
                 lines.push(Line::from("  (no metadata in this file)"));
             } else {
-                for entry in metadata {
-                    let key = self
-                        .book_reader
-                        .reader
-                        .get_string(entry.key_offset)
-                        .unwrap_or("???");
-                    let val = self
-                        .book_reader
-                        .reader
-                        .get_string(entry.value_offset)
-                        .unwrap_or("???");
+                let section_entries = metadata.iter().filter_map(|(key, value, section)| {
+                    section.map(|section| (key, format!("{} ({})", value, section)))
+                });
+                let entries = metadata
+                    .book_display_order()
+                    .map(|(key, value)| (key, value.to_string()))
+                    .chain(section_entries);
+
+                for (key, val) in entries {
                     lines.push(Line::from(vec![
                         Span::styled(
                             format!("  {}: ", key),
@@ -813,7 +799,7 @@ impl TuiApp {
                                 .fg(Color::Cyan)
                                 .add_modifier(Modifier::BOLD),
                         ),
-                        Span::raw(val.to_string()),
+                        Span::raw(val),
                     ]));
                 }
             }
@@ -835,9 +821,10 @@ impl TuiApp {
             self.book_reader.reader.version()
         )));
 
-        if let Ok(sections) = self.book_reader.reader.sections() {
-            lines.push(Line::from(format!("  Sections: {}", sections.len())));
-        }
+        lines.push(Line::from(format!(
+            "  Sections: {}",
+            self.book_reader.sections.len()
+        )));
 
         lines.push(Line::from(""));
         lines.push(Line::from("  Press [i] or [Esc] to close"));
@@ -922,19 +909,10 @@ impl TuiApp {
     }
 
     fn find_section_for_page(&self, page: usize) -> Option<String> {
-        if let Ok(sections) = self.book_reader.reader.sections() {
-            for section in sections.iter().rev() {
-                if section.section_start_index as usize <= page
-                    && let Ok(title) = self
-                        .book_reader
-                        .reader
-                        .get_string(section.section_title_offset)
-                {
-                    return Some(title.to_string());
-                }
-            }
-        }
-        None
+        self.book_reader
+            .sections
+            .section_at(page as u64)
+            .map(|section| section.title().to_string())
     }
 
     fn render_slideshow_indicator(&self, frame: &mut Frame) {
@@ -0,0 +1,79 @@
+use {
+    boundbook::{RecoveryMethod, Result},
+    clap::Args,
+    miette::{Context, IntoDiagnostic},
+    std::path::PathBuf,
+};
+
+#[derive(Args)]
+#[command(author = "The Motherfucking Bearodactyl")]
+pub struct RepairArgs {
+    /// Damaged BBF file to salvage
+    input: PathBuf,
+
+    /// Output path for the repaired BBF file
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Overwrite the output file even if it is petrified
+    #[arg(long)]
+    force: bool,
+}
+
+pub fn execute(args: RepairArgs) -> Result<()> {
+    super::petrify::ensure_not_petrified(&args.output, args.force)?;
+
+    let report = boundbook::repair(&args.input, &args.output)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to repair {}", args.input.display()))?;
+
+    let method = match report.method {
+        RecoveryMethod::Index => "re-hashed against the surviving index",
+        RecoveryMethod::MagicScan => "found by scanning for image signatures",
+    };
+
+    println!("--- Repair Report");
+    println!(
+        "--- Assets: {} recovered ({})",
+        report.assets_recovered, method
+    );
+
+    if !report.damaged_assets.is_empty() {
+        let damaged: Vec<String> = report
+            .damaged_assets
+            .iter()
+            .map(|index| index.to_string())
+            .collect();
+        println!("--- Damaged assets dropped: {}", damaged.join(", "));
+    }
+
+    println!(
+        "--- Pages: {} recovered, {} lost",
+        report.pages_recovered, report.pages_lost
+    );
+    println!(
+        "--- Sections: {} recovered, {} lost",
+        report.sections_recovered, report.sections_lost
+    );
+    println!(
+        "--- Metadata: {} recovered, {} lost",
+        report.metadata_recovered, report.metadata_lost
+    );
+
+    for note in &report.notes {
+        println!("--- Note: {}", note);
+    }
+
+    println!("---");
+
+    if report.is_lossless() {
+        println!("✓ Fully repaired into {}", args.output.display());
+    } else {
+        println!(
+            "✓ Salvaged what could be recovered into {}",
+            args.output.display()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use {
+    boundbook::{Book, Result},
+    clap::Args,
+    miette::{Context, IntoDiagnostic, miette},
+    std::path::PathBuf,
+};
+
+#[derive(Args)]
+#[command(author = "The Motherfucking Bearodactyl")]
+pub struct ToCbzArgs {
+    /// Input BBF file
+    input: PathBuf,
+
+    /// Output CBZ file, or output directory with --split
+    #[arg(short = 'o', long)]
+    output: PathBuf,
+
+    /// Write each top-level section to its own CBZ file
+    #[arg(short = 's', long)]
+    split: bool,
+}
+
+pub fn execute(args: ToCbzArgs) -> Result<()> {
+    let book = Book::open(&args.input)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to open BBF file: {}", args.input.display()))?;
+
+    if !args.split {
+        println!("Converting BBF to CBZ: {}", args.input.display());
+        boundbook::to_cbz(&book, &args.output)
+            .into_diagnostic()
+            .with_context(|| format!("Failed to write {}", args.output.display()))?;
+
+        println!(
+            "Successfully converted to {} ({} pages)",
+            args.output.display(),
+            book.page_count()
+        );
+        return Ok(());
+    }
+
+    let Some(first) = book.root_sections().next() else {
+        return Err(miette!("{} has no sections to split on", args.input.display()).into());
+    };
+
+    println!(
+        "Splitting BBF into one CBZ per section: {}",
+        args.input.display()
+    );
+
+    let written = boundbook::to_cbz_split(&book, &args.output)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to write archives to {}", args.output.display()))?;
+
+    for path in &written {
+        println!("  Wrote {}", path.display());
+    }
+
+    if first.start() > 0 {
+        println!(
+            "Note: pages 1-{} come before the first section and were not exported",
+            first.start()
+        );
+    }
+
+    println!(
+        "Successfully converted to {} CBZ files in {}",
+        written.len(),
+        args.output.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use {
+    boundbook::{Book, Result},
+    clap::Args,
+    miette::{Context, IntoDiagnostic},
+    std::path::PathBuf,
+};
+
+#[derive(Args)]
+#[command(author = "The Motherfucking Bearodactyl")]
+pub struct ToEpubArgs {
+    /// Input BBF file
+    input: PathBuf,
+
+    /// Output EPUB file
+    #[arg(short = 'o', long)]
+    output: PathBuf,
+}
+
+pub fn execute(args: ToEpubArgs) -> Result<()> {
+    let book = Book::open(&args.input)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to open BBF file: {}", args.input.display()))?;
+
+    println!("Converting BBF to EPUB: {}", args.input.display());
+    boundbook::to_epub(&book, &args.output)
+        .into_diagnostic()
+        .with_context(|| format!("Failed to write {}", args.output.display()))?;
+
+    println!(
+        "Successfully converted to {} ({} pages)",
+        args.output.display(),
+        book.page_count()
+    );
+
+    Ok(())
+}
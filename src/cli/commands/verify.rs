@@ -1,7 +1,10 @@
 use {
-    boundbook::{BbfError, BbfReader, Result},
+    boundbook::{
+        AssetFault, BbfError, BbfReader, IntegrityReport, Result, Severity, ValidationReport,
+    },
     clap::Args,
     miette::{Context, IntoDiagnostic},
+    serde::Serialize,
     std::path::PathBuf,
 };
 
@@ -18,6 +21,115 @@ pub struct VerifyArgs {
     /// Verify a specific asset by index
     #[arg(long, conflicts_with = "index_only")]
     asset: Option<usize>,
+
+    /// Also check all cross-references and layout invariants, reporting every problem found
+    #[arg(long, conflicts_with_all = ["index_only", "asset"])]
+    deep: bool,
+
+    /// Print the full report as JSON instead of text
+    #[arg(long, conflicts_with_all = ["index_only", "asset"])]
+    json: bool,
+}
+
+#[derive(Serialize)]
+struct JsonIssue {
+    severity: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct JsonCorruptedAsset {
+    asset_index: u64,
+    fault: &'static str,
+    page_indices: Vec<u64>,
+}
+
+#[derive(Serialize)]
+struct JsonReport {
+    file: String,
+    ok: bool,
+    structure_ok: bool,
+    index_hash_ok: bool,
+    assets_checked: u64,
+    corrupted_assets: Vec<JsonCorruptedAsset>,
+    issues: Vec<JsonIssue>,
+}
+
+impl JsonReport {
+    fn new(args: &VerifyArgs, validation: &ValidationReport, integrity: &IntegrityReport) -> Self {
+        Self {
+            file: args.input.display().to_string(),
+            ok: !validation.has_errors() && integrity.is_ok(),
+            structure_ok: !validation.has_errors(),
+            index_hash_ok: integrity.index_hash_ok,
+            assets_checked: integrity.assets_checked,
+            corrupted_assets: integrity
+                .corrupted_assets
+                .iter()
+                .map(|asset| JsonCorruptedAsset {
+                    asset_index: asset.asset_index,
+                    fault: fault_name(asset.fault),
+                    page_indices: asset.pages.clone(),
+                })
+                .collect(),
+            issues: validation
+                .issues()
+                .iter()
+                .map(|issue| JsonIssue {
+                    severity: issue.severity().to_string(),
+                    message: issue.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+const fn fault_name(fault: AssetFault) -> &'static str {
+    match fault {
+        AssetFault::HashMismatch => "hash_mismatch",
+        AssetFault::OutOfBounds => "out_of_bounds",
+    }
+}
+
+/// Format zero-based page indices as a list of one-based page numbers
+fn page_list(pages: &[u64]) -> String {
+    pages
+        .iter()
+        .map(|page| page.saturating_add(1).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_report(validation: &ValidationReport, integrity: &IntegrityReport) {
+    for issue in validation.issues() {
+        let marker = match issue.severity() {
+            Severity::Warning => "⚠",
+            Severity::Error => "✗",
+        };
+        println!("{} {}: {}", marker, issue.severity(), issue);
+    }
+
+    if !integrity.index_hash_ok {
+        println!("✗ error: index hash mismatch");
+    }
+
+    for asset in &integrity.corrupted_assets {
+        let fault = match asset.fault {
+            AssetFault::HashMismatch => "hash mismatch",
+            AssetFault::OutOfBounds => "data out of bounds",
+        };
+
+        if asset.pages.is_empty() {
+            println!("✗ error: asset {} {} (unused)", asset.asset_index, fault);
+        } else {
+            println!(
+                "✗ error: asset {} {} (pages {})",
+                asset.asset_index,
+                fault,
+                page_list(&asset.pages)
+            );
+        }
+    }
 }
 
 pub fn execute(args: VerifyArgs) -> Result<()> {
@@ -36,36 +148,80 @@ pub fn execute(args: VerifyArgs) -> Result<()> {
             println!("✓ Asset {} integrity OK", asset_index);
             Ok(())
         } else {
-            Err(BbfError::from(miette::miette!(
-                "✗ Asset {} is corrupted",
-                asset_index
-            )))
+            println!("✗ Asset {} is corrupted", asset_index);
+            Err(BbfError::HashMismatch)
         }
     } else if args.index_only {
         println!("Verifying index hash...");
-        let valid = reader.verify_integrity().into_diagnostic()?;
+        let valid = reader.verify_index_hash().into_diagnostic()?;
 
         if valid {
             println!("✓ Index integrity OK");
             Ok(())
         } else {
-            Err(BbfError::from(miette::miette!(
-                "✗ Index hash mismatch - file may be corrupted"
-            )))
+            println!("✗ Index hash mismatch - file may be corrupted");
+            Err(BbfError::HashMismatch)
         }
     } else {
-        println!("Verifying complete file integrity (parallel)...");
-        let valid = reader.verify_integrity().into_diagnostic()?;
+        if !args.json {
+            if args.deep {
+                println!("Verifying complete file integrity and structure (parallel)...");
+            } else {
+                println!("Verifying complete file integrity (parallel)...");
+            }
+        }
 
-        if valid {
-            println!("✓ All integrity checks passed");
-            println!("  • Index hash: OK");
-            println!("  • {} assets verified: OK", reader.asset_count());
-            Ok(())
+        let validation = if args.deep {
+            reader.validate_deep()
         } else {
+            reader.validate()
+        };
+        let integrity = reader.integrity_report().into_diagnostic()?;
+
+        if args.json {
+            let json =
+                serde_json::to_string_pretty(&JsonReport::new(&args, &validation, &integrity))
+                    .into_diagnostic()?;
+            println!("{}", json);
+        } else {
+            print_report(&validation, &integrity);
+        }
+
+        if !integrity.is_ok() {
+            Err(BbfError::HashMismatch)
+        } else if validation.has_errors() {
             Err(BbfError::from(miette::miette!(
-                "✗ Integrity check failed - file is corrupted"
+                "✗ Structural validation failed - file is inconsistent"
             )))
+        } else {
+            if !args.json {
+                println!("✓ All integrity checks passed");
+                println!(
+                    "  • Structure: OK ({} warnings)",
+                    validation.warnings().count()
+                );
+                println!("  • Index hash: OK");
+                println!("  • {} assets verified: OK", integrity.assets_checked);
+            }
+            Ok(())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, assert2::check as assert};
+
+    #[test]
+    fn test_page_list_is_one_based() {
+        assert!(page_list(&[0, 4, 9]) == "1, 5, 10");
+        assert!(page_list(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_corruption_has_distinct_exit_code() {
+        assert!(crate::cli::exit_code(&BbfError::HashMismatch) == 2);
+        assert!(crate::cli::exit_code(&BbfError::InvalidMagic) == 1);
+    }
+}
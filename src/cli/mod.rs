@@ -1,4 +1,5 @@
 mod commands;
+mod sort;
 
 #[derive(clap::Parser)]
 #[command(name = "boundbook", author = "EF1500", version = "1.0", about = "BBF CLI", long_about = None)]
@@ -18,22 +19,51 @@ pub enum Commands {
     /// Display BBF file information
     Info(commands::info::InfoArgs),
 
-    /// Verify BBF file integrity
+    /// Verify BBF file integrity (exits with status 2 if corruption is found)
     Verify(commands::verify::VerifyArgs),
 
     /// Extract pages from a BBF file
     Extract(commands::extract::ExtractArgs),
 
-    /// Convert CBZ archive to BBF format
-    FromCbz(commands::from_cbz::FromCbzArgs),
+    /// Convert a comic archive (CBZ, CBT, CB7 or CBR) to BBF format
+    #[command(alias = "from-cbz")]
+    FromArchive(commands::from_archive::FromArchiveArgs),
+
+    /// Convert a fixed-layout EPUB to BBF format
+    FromEpub(commands::from_epub::FromEpubArgs),
+
+    /// Convert an image-only PDF to BBF format
+    FromPdf(commands::from_pdf::FromPdfArgs),
+
+    /// Convert BBF file to CBZ archive format
+    ToCbz(commands::to_cbz::ToCbzArgs),
+
+    /// Convert BBF file to fixed-layout EPUB
+    ToEpub(commands::to_epub::ToEpubArgs),
 
     /// Read a BBF file in the terminal
     Read(commands::read::ReadArgs),
 
+    /// Mark a BBF file as permanently read-only
+    Petrify(commands::petrify::PetrifyArgs),
+
+    /// Salvage a damaged BBF file into a new one
+    Repair(commands::repair::RepairArgs),
+
     /// Generate CLI completions
     Complete(commands::complete::CompleteArgs),
 }
 
+/// Process exit status for a failed command
+///
+/// Corruption found by `verify` exits with 2 so scripts can tell it apart from other failures (1)
+pub const fn exit_code(err: &boundbook::BbfError) -> i32 {
+    match err {
+        boundbook::BbfError::HashMismatch => 2,
+        _ => 1,
+    }
+}
+
 pub fn app() -> boundbook::Result<()> {
     let argv = <Cli as clap::Parser>::parse();
 
@@ -47,8 +77,14 @@ pub fn app() -> boundbook::Result<()> {
         Commands::Info(args) => commands::info::execute(args),
         Commands::Verify(args) => commands::verify::execute(args),
         Commands::Extract(args) => commands::extract::execute(args),
-        Commands::FromCbz(args) => commands::from_cbz::execute(args),
+        Commands::FromArchive(args) => commands::from_archive::execute(args),
+        Commands::FromEpub(args) => commands::from_epub::execute(args),
+        Commands::FromPdf(args) => commands::from_pdf::execute(args),
+        Commands::ToCbz(args) => commands::to_cbz::execute(args),
+        Commands::ToEpub(args) => commands::to_epub::execute(args),
         Commands::Read(args) => commands::read::execute(args),
+        Commands::Petrify(args) => commands::petrify::execute(args),
+        Commands::Repair(args) => commands::repair::execute(args),
         Commands::Complete(args) => commands::complete::execute(args),
     }
 }
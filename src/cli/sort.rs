@@ -0,0 +1,124 @@
+use {clap::ValueEnum, std::cmp::Ordering};
+
+/// how pages are ordered when a book is built from loose files or an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum PageSort {
+    /// Compare names with embedded numbers by value, so page2 comes before page10
+    #[default]
+    Natural,
+    /// Compare names character by character, so page10 comes before page2
+    Lexical,
+    /// Order by modification time, oldest first, falling back to natural order
+    Mtime,
+    /// Keep the order the files were found in
+    None,
+}
+
+impl PageSort {
+    /// compares two names (or folder paths) the way this mode orders them
+    ///
+    /// modification-time ordering compares names naturally, since names are its tie-breaker
+    pub fn compare_names(self, a: &str, b: &str) -> Ordering {
+        match self {
+            Self::Natural | Self::Mtime => alphanumeric_sort::compare_str(a, b),
+            Self::Lexical => a.cmp(b),
+            Self::None => Ordering::Equal,
+        }
+    }
+
+    /// compares two pages by name and modification time
+    pub fn compare<M: Ord>(self, a: (&str, Option<M>), b: (&str, Option<M>)) -> Ordering {
+        match self {
+            Self::Mtime => a.1.cmp(&b.1).then_with(|| self.compare_names(a.0, b.0)),
+            _ => self.compare_names(a.0, b.0),
+        }
+    }
+
+    /// compares two archive entry paths, keeping the pages of each folder together
+    ///
+    /// folders are ordered by name (natural order for everything but `lexical`), and the pages
+    /// within a folder by [`PageSort::compare`]
+    pub fn compare_paths<M: Ord>(self, a: (&str, Option<M>), b: (&str, Option<M>)) -> Ordering {
+        let (a_folder, a_file) = split_folder(a.0);
+        let (b_folder, b_file) = split_folder(b.0);
+
+        let folders = match self {
+            Self::None => Ordering::Equal,
+            _ => self.compare_names(a_folder, b_folder),
+        };
+        folders.then_with(|| self.compare((a_file, a.1), (b_file, b.1)))
+    }
+}
+
+/// splits an archive entry path into its folder (empty at the root) and file name
+pub fn split_folder(path: &str) -> (&str, &str) {
+    path.rsplit_once('/').unwrap_or(("", path))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, assert2::check as assert};
+
+    fn sorted(sort: PageSort, names: &[(&str, u32)]) -> Vec<String> {
+        let mut names = names.to_vec();
+        names.sort_by(|a, b| sort.compare_paths((a.0, Some(a.1)), (b.0, Some(b.1))));
+        names
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    const PAGES: &[(&str, u32)] = &[
+        ("page10.jpg", 1),
+        ("page2.jpg", 3),
+        ("Chapter 10/01.jpg", 0),
+        ("page1.jpg", 2),
+        ("Chapter 2/01.jpg", 4),
+    ];
+
+    #[test]
+    fn test_natural_sort_orders_numbers_by_value() {
+        assert!(
+            sorted(PageSort::Natural, PAGES)
+                == [
+                    "page1.jpg",
+                    "page2.jpg",
+                    "page10.jpg",
+                    "Chapter 2/01.jpg",
+                    "Chapter 10/01.jpg",
+                ]
+        );
+    }
+
+    #[test]
+    fn test_other_sorts() {
+        assert!(
+            sorted(PageSort::Lexical, PAGES)
+                == [
+                    "page1.jpg",
+                    "page10.jpg",
+                    "page2.jpg",
+                    "Chapter 10/01.jpg",
+                    "Chapter 2/01.jpg",
+                ]
+        );
+        assert!(
+            sorted(PageSort::Mtime, PAGES)
+                == [
+                    "page10.jpg",
+                    "page1.jpg",
+                    "page2.jpg",
+                    "Chapter 2/01.jpg",
+                    "Chapter 10/01.jpg",
+                ]
+        );
+        assert!(
+            sorted(PageSort::None, PAGES)
+                == PAGES
+                    .iter()
+                    .map(|(name, _)| name.to_string())
+                    .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,561 @@
+use crate::types::*;
+
+/// decodes an on-disk struct from its little-endian byte representation
+///
+/// unlike reinterpreting raw bytes as a `#[repr(C, packed)]` struct, decoding reads each field
+/// explicitly, so it's correct on any target endianness and never depends on the layout of the
+/// in-memory struct.
+pub trait FromLeBytes: Sized {
+    /// size of the encoded struct in bytes
+    const SIZE: usize;
+
+    /// decodes the struct from the start of `bytes`
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - the encoded struct, at least [`FromLeBytes::SIZE`] bytes long
+    ///
+    /// # Returns
+    ///
+    /// the decoded struct, or `None` if `bytes` is too short
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+/// encodes an on-disk struct as its little-endian byte representation
+///
+/// the counterpart to [`FromLeBytes`]; every field is written explicitly in little-endian order,
+/// so files built on big-endian targets match the spec.
+pub trait ToLeBytes {
+    /// appends the encoded struct to `out`
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - the buffer to append to
+    fn write_le_bytes(&self, out: &mut Vec<u8>);
+
+    /// encodes the struct into a new buffer
+    ///
+    /// # Returns
+    ///
+    /// the encoded bytes
+    fn to_le_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_le_bytes(&mut out);
+        out
+    }
+}
+
+/// encodes a table of consecutive entries
+///
+/// # Arguments
+///
+/// * `entries` - the entries to encode, in table order
+///
+/// # Returns
+///
+/// the encoded table
+pub(crate) fn encode_table<T: ToLeBytes>(entries: &[T]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for entry in entries {
+        entry.write_le_bytes(&mut out);
+    }
+    out
+}
+
+/// decodes a table of consecutive entries
+///
+/// # Arguments
+///
+/// * `bytes` - the encoded table; trailing bytes short of a whole entry are ignored
+///
+/// # Returns
+///
+/// every decoded entry, in table order
+pub(crate) fn decode_table<T: FromLeBytes>(bytes: &[u8]) -> Vec<T> {
+    bytes
+        .chunks_exact(T::SIZE)
+        .filter_map(T::from_le_bytes)
+        .collect()
+}
+
+/// sequential field reader over an encoded struct
+struct Fields<'a>(&'a [u8]);
+
+impl Fields<'_> {
+    /// takes the next `N` bytes
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (head, rest) = self.0.split_first_chunk::<N>()?;
+        self.0 = rest;
+        Some(*head)
+    }
+
+    /// takes the next byte
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes::<1>().map(|[b]| b)
+    }
+
+    /// takes the next little-endian u16
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes().map(u16::from_le_bytes)
+    }
+
+    /// takes the next little-endian u32
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes().map(u32::from_le_bytes)
+    }
+
+    /// takes the next little-endian u64
+    fn u64(&mut self) -> Option<u64> {
+        self.bytes().map(u64::from_le_bytes)
+    }
+}
+
+impl ToLeBytes for BbfHeader {
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&{ self.magic });
+        out.extend_from_slice(&{ self.version }.to_le_bytes());
+        out.extend_from_slice(&{ self.header_len }.to_le_bytes());
+        out.extend_from_slice(&{ self.flags }.to_le_bytes());
+        out.push(self.alignment);
+        out.push(self.ream_size);
+        out.extend_from_slice(&{ self.reserved_extra }.to_le_bytes());
+        out.extend_from_slice(&{ self.footer_offset }.to_le_bytes());
+        out.extend_from_slice(&{ self.reserved });
+    }
+}
+
+impl FromLeBytes for BbfHeader {
+    const SIZE: usize = std::mem::size_of::<Self>();
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut f = Fields(bytes);
+        Some(Self {
+            magic: f.bytes()?,
+            version: f.u16()?,
+            header_len: f.u16()?,
+            flags: f.u32()?,
+            alignment: f.u8()?,
+            ream_size: f.u8()?,
+            reserved_extra: f.u16()?,
+            footer_offset: f.u64()?,
+            reserved: f.bytes()?,
+        })
+    }
+}
+
+impl ToLeBytes for BbfFooter {
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        for word in [
+            self.asset_offset,
+            self.page_offset,
+            self.section_offset,
+            self.meta_offset,
+            self.expansion_offset,
+            self.string_pool_offset,
+            self.string_pool_size,
+            self.asset_count,
+            self.page_count,
+            self.section_count,
+            self.meta_count,
+            self.expansion_count,
+        ] {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.extend_from_slice(&{ self.flags }.to_le_bytes());
+        out.push(self.footer_len);
+        out.extend_from_slice(&{ self.padding });
+        out.extend_from_slice(&{ self.footer_hash }.to_le_bytes());
+        out.extend_from_slice(&{ self.reserved });
+    }
+}
+
+impl FromLeBytes for BbfFooter {
+    const SIZE: usize = std::mem::size_of::<Self>();
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut f = Fields(bytes);
+        Some(Self {
+            asset_offset: f.u64()?,
+            page_offset: f.u64()?,
+            section_offset: f.u64()?,
+            meta_offset: f.u64()?,
+            expansion_offset: f.u64()?,
+            string_pool_offset: f.u64()?,
+            string_pool_size: f.u64()?,
+            asset_count: f.u64()?,
+            page_count: f.u64()?,
+            section_count: f.u64()?,
+            meta_count: f.u64()?,
+            expansion_count: f.u64()?,
+            flags: f.u32()?,
+            footer_len: f.u8()?,
+            padding: f.bytes()?,
+            footer_hash: f.u64()?,
+            reserved: f.bytes()?,
+        })
+    }
+}
+
+impl ToLeBytes for AssetEntry {
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        let [hash_low, hash_high] = self.asset_hash;
+        for word in [self.file_offset, hash_low, hash_high, self.file_size] {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.extend_from_slice(&{ self.flags }.to_le_bytes());
+        out.extend_from_slice(&{ self.reserved_value }.to_le_bytes());
+        out.push(self.media_type);
+        out.extend_from_slice(&{ self.reserved });
+    }
+}
+
+impl FromLeBytes for AssetEntry {
+    const SIZE: usize = std::mem::size_of::<Self>();
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut f = Fields(bytes);
+        Some(Self {
+            file_offset: f.u64()?,
+            asset_hash: [f.u64()?, f.u64()?],
+            file_size: f.u64()?,
+            flags: f.u32()?,
+            reserved_value: f.u16()?,
+            media_type: f.u8()?,
+            reserved: f.bytes()?,
+        })
+    }
+}
+
+impl ToLeBytes for PageEntry {
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&{ self.asset_index }.to_le_bytes());
+        out.extend_from_slice(&{ self.flags }.to_le_bytes());
+        out.extend_from_slice(&{ self.reserved });
+    }
+}
+
+impl FromLeBytes for PageEntry {
+    const SIZE: usize = std::mem::size_of::<Self>();
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut f = Fields(bytes);
+        Some(Self {
+            asset_index: f.u64()?,
+            flags: f.u32()?,
+            reserved: f.bytes()?,
+        })
+    }
+}
+
+impl ToLeBytes for Section {
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        for word in [
+            self.section_title_offset,
+            self.section_start_index,
+            self.section_parent_offset,
+        ] {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.extend_from_slice(&{ self.reserved });
+    }
+}
+
+impl FromLeBytes for Section {
+    const SIZE: usize = std::mem::size_of::<Self>();
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut f = Fields(bytes);
+        Some(Self {
+            section_title_offset: f.u64()?,
+            section_start_index: f.u64()?,
+            section_parent_offset: f.u64()?,
+            reserved: f.bytes()?,
+        })
+    }
+}
+
+impl ToLeBytes for Metadata {
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        for word in [self.key_offset, self.value_offset, self.parent_offset] {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.extend_from_slice(&{ self.reserved });
+    }
+}
+
+impl FromLeBytes for Metadata {
+    const SIZE: usize = std::mem::size_of::<Self>();
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut f = Fields(bytes);
+        Some(Self {
+            key_offset: f.u64()?,
+            value_offset: f.u64()?,
+            parent_offset: f.u64()?,
+            reserved: f.bytes()?,
+        })
+    }
+}
+
+impl ToLeBytes for Expansion {
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        for word in self.exp_reserved {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.extend_from_slice(&{ self.flags }.to_le_bytes());
+        out.extend_from_slice(&{ self.reserved });
+    }
+}
+
+impl FromLeBytes for Expansion {
+    const SIZE: usize = std::mem::size_of::<Self>();
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut f = Fields(bytes);
+        let mut exp_reserved = [0u64; 10];
+        for word in &mut exp_reserved {
+            *word = f.u64()?;
+        }
+
+        Some(Self {
+            exp_reserved,
+            flags: f.u32()?,
+            reserved: f.bytes()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, assert2::check as assert};
+
+    /// a golden fixture: the given leading bytes followed by zeros up to `len`
+    fn golden(head: &[u8], len: usize) -> Vec<u8> {
+        let mut bytes = head.to_vec();
+        bytes.resize(len, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_header_golden_bytes() {
+        let fixture = golden(
+            &[
+                b'B', b'B', b'F', b'3', // magic
+                0x03, 0x00, // version
+                0x40, 0x00, // header_len
+                0x04, 0x03, 0x02, 0x01, // flags
+                0x0C, // alignment
+                0x10, // ream_size
+                0x00, 0x00, // reserved_extra
+                0x45, 0x23, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, // footer_offset
+            ],
+            64,
+        );
+
+        let header = BbfHeader::from_le_bytes(&fixture).unwrap();
+        assert!(header.magic == *b"BBF3");
+        assert!({ header.version } == 3);
+        assert!({ header.header_len } == 64);
+        assert!({ header.flags } == 0x0102_0304);
+        assert!(header.alignment == 12);
+        assert!(header.ream_size == 16);
+        assert!({ header.footer_offset } == 0x12345);
+        assert!(header.to_le_bytes() == fixture);
+    }
+
+    #[test]
+    fn test_footer_golden_bytes() {
+        let mut head = Vec::new();
+        for word in 1u8..=12 {
+            head.extend_from_slice(&[word, 0, 0, 0, 0, 0, 0, 0]);
+        }
+        head.extend_from_slice(&[
+            0x10, 0x00, 0x00, 0x00, // flags
+            0x00, // footer_len
+            0x00, 0x00, 0x00, // padding
+            0x0D, 0xF0, 0xFE, 0xCA, 0xEF, 0xBE, 0xAD, 0xDE, // footer_hash
+        ]);
+        let fixture = golden(&head, 256);
+
+        let footer = BbfFooter::from_le_bytes(&fixture).unwrap();
+        assert!({ footer.asset_offset } == 1);
+        assert!({ footer.string_pool_size } == 7);
+        assert!({ footer.expansion_count } == 12);
+        assert!({ footer.flags } == 0x10);
+        assert!({ footer.footer_hash } == 0xDEAD_BEEF_CAFE_F00D);
+        assert!(footer.to_le_bytes() == fixture);
+    }
+
+    #[test]
+    fn test_asset_entry_golden_bytes() {
+        let fixture = golden(
+            &[
+                0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // file_offset
+                0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, // asset_hash[0]
+                0x00, 0xFF, 0xEE, 0xDD, 0xCC, 0xBB, 0xAA, 0x99, // asset_hash[1]
+                0x2A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // file_size
+                0x01, 0x00, 0x00, 0x00, // flags
+                0x00, 0x00, // reserved_value
+                0x09, // media_type
+            ],
+            48,
+        );
+
+        let asset = AssetEntry::from_le_bytes(&fixture).unwrap();
+        assert!({ asset.file_offset } == 0x1000);
+        assert!({ asset.asset_hash } == [0x1122_3344_5566_7788, 0x99AA_BBCC_DDEE_FF00]);
+        assert!({ asset.file_size } == 42);
+        assert!({ asset.flags } == 1);
+        assert!(asset.media_type == 9);
+        assert!(asset.to_le_bytes() == fixture);
+    }
+
+    #[test]
+    fn test_page_entry_golden_bytes() {
+        let fixture = golden(
+            &[
+                0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, // asset_index
+                0xDD, 0xCC, 0xBB, 0xAA, // flags
+            ],
+            16,
+        );
+
+        let page = PageEntry::from_le_bytes(&fixture).unwrap();
+        assert!({ page.asset_index } == 0x0102_0304_0506_0708);
+        assert!({ page.flags } == 0xAABB_CCDD);
+        assert!(page.to_le_bytes() == fixture);
+        assert!(PageEntry::from_le_bytes(&fixture[..15]).is_none());
+    }
+
+    #[test]
+    fn test_section_golden_bytes() {
+        let fixture = golden(
+            &[
+                0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // section_title_offset
+                0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // section_start_index
+                0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // section_parent_offset
+            ],
+            32,
+        );
+
+        let section = Section::from_le_bytes(&fixture).unwrap();
+        assert!({ section.section_title_offset } == 5);
+        assert!({ section.section_start_index } == 16);
+        assert!({ section.section_parent_offset } == u64::MAX);
+        assert!(section.to_le_bytes() == fixture);
+    }
+
+    #[test]
+    fn test_metadata_golden_bytes() {
+        let fixture = golden(
+            &[
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // key_offset
+                0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // value_offset
+                0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // parent_offset
+            ],
+            32,
+        );
+
+        let meta = Metadata::from_le_bytes(&fixture).unwrap();
+        assert!({ meta.key_offset } == 0);
+        assert!({ meta.value_offset } == 7);
+        assert!({ meta.parent_offset } == 10);
+        assert!(meta.to_le_bytes() == fixture);
+    }
+
+    #[test]
+    fn test_expansion_golden_bytes() {
+        let mut head = Vec::new();
+        for word in 1u8..=10 {
+            head.extend_from_slice(&[word, 0, 0, 0, 0, 0, 0, 0]);
+        }
+        head.extend_from_slice(&[0x01, 0x00, 0x00, 0x00]); // flags
+        let fixture = golden(&head, 128);
+
+        let exp = Expansion::from_le_bytes(&fixture).unwrap();
+        assert!(exp.kind() == 1);
+        assert!(exp.version() == 2);
+        assert!(exp.payload() == [3, 4, 5, 6, 7, 8, 9, 10]);
+        assert!(exp.is_required());
+        assert!(exp.to_le_bytes() == fixture);
+    }
+
+    #[test]
+    fn test_encoded_sizes_match_structs() {
+        let fixture = [0u8; 256];
+        assert!(
+            BbfHeader::from_le_bytes(&fixture)
+                .unwrap()
+                .to_le_bytes()
+                .len()
+                == BbfHeader::SIZE
+        );
+        assert!(
+            BbfFooter::from_le_bytes(&fixture)
+                .unwrap()
+                .to_le_bytes()
+                .len()
+                == BbfFooter::SIZE
+        );
+        assert!(
+            AssetEntry::from_le_bytes(&fixture)
+                .unwrap()
+                .to_le_bytes()
+                .len()
+                == AssetEntry::SIZE
+        );
+        assert!(
+            PageEntry::from_le_bytes(&fixture)
+                .unwrap()
+                .to_le_bytes()
+                .len()
+                == PageEntry::SIZE
+        );
+        assert!(
+            Section::from_le_bytes(&fixture)
+                .unwrap()
+                .to_le_bytes()
+                .len()
+                == Section::SIZE
+        );
+        assert!(
+            Metadata::from_le_bytes(&fixture)
+                .unwrap()
+                .to_le_bytes()
+                .len()
+                == Metadata::SIZE
+        );
+        assert!(
+            Expansion::from_le_bytes(&fixture)
+                .unwrap()
+                .to_le_bytes()
+                .len()
+                == Expansion::SIZE
+        );
+    }
+
+    #[test]
+    fn test_table_roundtrip() {
+        let pages = [
+            PageEntry {
+                asset_index: 3,
+                flags: 1,
+                reserved: [0; 4],
+            },
+            PageEntry {
+                asset_index: 9,
+                flags: 0,
+                reserved: [0; 4],
+            },
+        ];
+
+        let bytes = encode_table(&pages);
+        assert!(bytes.len() == 32);
+
+        let decoded: Vec<PageEntry> = decode_table(&bytes);
+        assert!(decoded.len() == 2);
+        assert!({ decoded[1].asset_index } == 9);
+        assert!(decode_table::<PageEntry>(&bytes[..31]).len() == 1);
+    }
+}
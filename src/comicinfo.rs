@@ -0,0 +1,521 @@
+use crate::{
+    BbfError, Book, Result, SectionView, Storage,
+    format::{BBF_PAGE_BACK_COVER_FLAG, BBF_PAGE_DOUBLE_SPREAD_FLAG, BBF_PAGE_FRONT_COVER_FLAG},
+    keys,
+    xml::escape,
+};
+
+/// ComicInfo elements whose meaning matches a well-known BBF key
+const FIELD_KEYS: &[(&str, &str)] = &[
+    ("Title", keys::TITLE),
+    ("Series", keys::SERIES),
+    ("Volume", keys::VOLUME),
+    ("Writer", keys::AUTHOR),
+    ("Publisher", keys::PUBLISHER),
+    ("LanguageISO", keys::LANGUAGE),
+    ("GTIN", keys::ISBN),
+];
+
+/// ComicInfo elements that aren't carried over as metadata
+///
+/// the page list is mapped onto page flags and sections instead, the page count is implied by
+/// the book, and the date parts are combined into a single [`keys::DATE`] entry
+const SKIPPED_FIELDS: &[&str] = &["Pages", "PageCount", "Year", "Month", "Day"];
+
+/// the schema's placeholders for "not set"
+const UNSET_VALUES: &[&str] = &["-1", "Unknown"];
+
+/// the elements of the ComicInfo 2.0 schema, in the order the schema requires
+const SCHEMA_FIELDS: &[&str] = &[
+    "Title",
+    "Series",
+    "Number",
+    "Count",
+    "Volume",
+    "AlternateSeries",
+    "AlternateNumber",
+    "AlternateCount",
+    "Summary",
+    "Notes",
+    "Year",
+    "Month",
+    "Day",
+    "Writer",
+    "Penciller",
+    "Inker",
+    "Colorist",
+    "Letterer",
+    "CoverArtist",
+    "Editor",
+    "Translator",
+    "Publisher",
+    "Imprint",
+    "Genre",
+    "Tags",
+    "Web",
+    "PageCount",
+    "LanguageISO",
+    "Format",
+    "BlackAndWhite",
+    "Manga",
+    "Characters",
+    "Teams",
+    "Locations",
+    "ScanInformation",
+    "StoryArc",
+    "StoryArcNumber",
+    "SeriesGroup",
+    "AgeRating",
+    "Pages",
+    "CommunityRating",
+    "MainCharacterOrTeam",
+    "Review",
+    "GTIN",
+];
+
+/// the contents of a `ComicInfo.xml` file
+///
+/// ComicInfo is the de facto metadata format of CBZ archives. [`ComicInfo::parse`] maps its
+/// fields onto BBF metadata: elements with a well-known counterpart (`Writer`, `LanguageISO`, ...)
+/// use the [`crate::keys`] spelling, `Year`/`Month`/`Day` become one ISO 8601 [`keys::DATE`],
+/// `Manga="YesAndRightToLeft"` becomes an `rtl` [`keys::READING_DIRECTION`], and every other
+/// element is kept under its own name. [`ComicInfo::to_xml`] maps them back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComicInfo {
+    /// `(key, value)` metadata in document order
+    pub metadata: Vec<(String, String)>,
+    /// the `<Pages>` list in document order
+    pub pages: Vec<ComicPage>,
+}
+
+/// a `<Page>` entry of a [`ComicInfo`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComicPage {
+    /// zero-based index of the image within the archive
+    pub image: u64,
+    /// the page types, such as `FrontCover` or `Story`
+    pub kinds: Vec<String>,
+    /// whether the image is a double-page spread
+    pub double_page: bool,
+    /// the bookmark set on the page, if any
+    pub bookmark: Option<String>,
+}
+
+impl ComicPage {
+    /// describes a BBF page by its flags
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - zero-based index of the image within the archive
+    /// * `flags` - the BBF page flags
+    /// * `bookmark` - the bookmark to set on the page, if any
+    ///
+    /// # Returns
+    ///
+    /// the page entry, typed `FrontCover` or `BackCover` when the flags say so
+    pub fn from_flags(image: u64, flags: u32, bookmark: Option<String>) -> Self {
+        let mut kinds = Vec::new();
+        if flags & BBF_PAGE_FRONT_COVER_FLAG != 0 {
+            kinds.push("FrontCover".to_string());
+        }
+        if flags & BBF_PAGE_BACK_COVER_FLAG != 0 {
+            kinds.push("BackCover".to_string());
+        }
+
+        Self {
+            image,
+            kinds,
+            double_page: flags & BBF_PAGE_DOUBLE_SPREAD_FLAG != 0,
+            bookmark,
+        }
+    }
+
+    /// returns the BBF page flags matching this page's types
+    pub fn flags(&self) -> u32 {
+        let mut flags = 0;
+        for kind in &self.kinds {
+            match kind.as_str() {
+                "FrontCover" => flags |= BBF_PAGE_FRONT_COVER_FLAG,
+                "BackCover" => flags |= BBF_PAGE_BACK_COVER_FLAG,
+                _ => {}
+            }
+        }
+        if self.double_page {
+            flags |= BBF_PAGE_DOUBLE_SPREAD_FLAG;
+        }
+        flags
+    }
+}
+
+impl ComicInfo {
+    /// the file name ComicInfo is stored under at the root of an archive
+    pub const FILE_NAME: &str = "ComicInfo.xml";
+
+    /// parses a `ComicInfo.xml` document
+    ///
+    /// # Arguments
+    ///
+    /// * `xml` - the document text
+    ///
+    /// # Returns
+    ///
+    /// the metadata and page list of the document
+    ///
+    /// # Errors
+    ///
+    /// returns [`BbfError::ComicInfo`] if the document isn't well-formed XML or its root element
+    /// isn't `<ComicInfo>`
+    pub fn parse(xml: &str) -> Result<Self> {
+        let document = roxmltree::Document::parse(xml).map_err(|e| BbfError::ComicInfo {
+            reason: e.to_string(),
+        })?;
+        let root = document.root_element();
+
+        if root.tag_name().name() != "ComicInfo" {
+            return Err(BbfError::ComicInfo {
+                reason: format!(
+                    "root element is <{}>, not <ComicInfo>",
+                    root.tag_name().name()
+                ),
+            });
+        }
+
+        let mut info = Self::default();
+        let mut date = [None; 3];
+
+        for element in root.children().filter(roxmltree::Node::is_element) {
+            let name = element.tag_name().name();
+            let value = element.text().map(str::trim).unwrap_or_default();
+
+            if name == "Pages" {
+                info.pages = element
+                    .children()
+                    .filter(|node| node.has_tag_name("Page"))
+                    .filter_map(parse_page)
+                    .collect();
+            }
+
+            if let Some(part) = ["Year", "Month", "Day"].iter().position(|&p| p == name) {
+                date[part] = value.parse::<u16>().ok().filter(|&n| n > 0);
+            }
+
+            if value.is_empty() || UNSET_VALUES.contains(&value) || SKIPPED_FIELDS.contains(&name) {
+                continue;
+            }
+
+            if name == "Manga" && value == "YesAndRightToLeft" {
+                info.metadata
+                    .push((keys::READING_DIRECTION.to_string(), "rtl".to_string()));
+                continue;
+            }
+
+            let key = FIELD_KEYS
+                .iter()
+                .find(|(field, _)| *field == name)
+                .map_or(name, |(_, key)| key);
+            info.metadata.push((key.to_string(), value.to_string()));
+        }
+
+        let date = match date {
+            [Some(year), Some(month), Some(day)] => Some(format!("{year:04}-{month:02}-{day:02}")),
+            [Some(year), Some(month), None] => Some(format!("{year:04}-{month:02}")),
+            [Some(year), ..] => Some(format!("{year:04}")),
+            _ => None,
+        };
+        if let Some(date) = date {
+            info.metadata.push((keys::DATE.to_string(), date));
+        }
+
+        Ok(info)
+    }
+
+    /// describes a book, or one section of it, as ComicInfo
+    ///
+    /// the page list covers every page in range, typed by its flags and bookmarked with the
+    /// first section (in table order) that starts on it. when describing a section, the
+    /// section's own metadata wins over the book's, the section title stands in for a missing
+    /// [`keys::TITLE`], and the book's title becomes the [`keys::SERIES`] if it has none.
+    ///
+    /// # Arguments
+    ///
+    /// * `book` - the book to describe
+    /// * `section` - the section to describe, or `None` for the whole book
+    ///
+    /// # Returns
+    ///
+    /// the ComicInfo for the pages in range, with images numbered from 0
+    pub fn from_book<S: Storage>(book: &Book<S>, section: Option<SectionView<'_>>) -> Self {
+        let map = book.metadata_map();
+        let owned = |(key, value): (&str, &str)| (key.to_string(), value.to_string());
+
+        let mut metadata: Vec<(String, String)> = Vec::new();
+        if let Some(section) = section {
+            metadata.extend(map.section(section.title()).map(owned));
+            if !metadata.iter().any(|(key, _)| key == keys::TITLE) {
+                metadata.insert(0, owned((keys::TITLE, section.title())));
+            }
+            if !metadata.iter().any(|(key, _)| key == keys::SERIES)
+                && map.get(keys::SERIES).is_none()
+                && let Some(title) = map.get(keys::TITLE)
+            {
+                metadata.push(owned((keys::SERIES, title)));
+            }
+        }
+
+        let overridden: Vec<String> = metadata.iter().map(|(key, _)| key.clone()).collect();
+        metadata.extend(
+            map.book()
+                .filter(|(key, _)| !overridden.iter().any(|k| k == key))
+                .map(owned),
+        );
+
+        let range = section.map_or(0..book.page_count(), |section| section.pages());
+        let pages = book
+            .pages()
+            .skip(usize::try_from(range.start).unwrap_or(usize::MAX))
+            .take_while(|page| range.contains(&page.index))
+            .enumerate()
+            .map(|(image, page)| {
+                let bookmark = book
+                    .sections()
+                    .find(|s| {
+                        s.start() == page.index
+                            && section.is_none_or(|section| section.index() != s.index())
+                    })
+                    .map(|s| s.title().to_string());
+                ComicPage::from_flags(image as u64, page.flags, bookmark)
+            })
+            .collect();
+
+        Self { metadata, pages }
+    }
+
+    /// renders the document as `ComicInfo.xml`
+    ///
+    /// metadata keys are mapped back onto ComicInfo elements and written in schema order. several
+    /// entries with the same key (one `Author` per author, say) are joined with `, `, a
+    /// [`keys::DATE`] is split into `Year`/`Month`/`Day`, an `rtl` [`keys::READING_DIRECTION`]
+    /// becomes `Manga="YesAndRightToLeft"`, and keys the schema has no element for are left out
+    /// so that strict readers accept the file.
+    ///
+    /// # Returns
+    ///
+    /// the XML document
+    pub fn to_xml(&self) -> String {
+        let mut fields: Vec<(&str, Vec<String>)> = Vec::new();
+        let mut add =
+            |field: &'static str, value: String| match fields.iter_mut().find(|(f, _)| *f == field)
+            {
+                Some((_, values)) => values.push(value),
+                None => fields.push((field, vec![value])),
+            };
+
+        for (key, value) in &self.metadata {
+            let key = keys::canonical_key(key);
+            if key == keys::DATE {
+                let parts = value.split('-').map(|part| part.trim().parse::<u16>());
+                for (field, part) in ["Year", "Month", "Day"].into_iter().zip(parts) {
+                    let Ok(number) = part else { break };
+                    add(field, number.to_string());
+                }
+            } else if key == keys::READING_DIRECTION {
+                if value.eq_ignore_ascii_case("rtl") {
+                    add("Manga", "YesAndRightToLeft".to_string());
+                }
+            } else if let Some((field, _)) = FIELD_KEYS.iter().find(|(_, k)| *k == key) {
+                add(field, value.clone());
+            } else if let Some(field) = SCHEMA_FIELDS
+                .iter()
+                .find(|f| **f == key && !SKIPPED_FIELDS.contains(f))
+            {
+                add(field, value.clone());
+            }
+        }
+
+        if !self.pages.is_empty() {
+            fields.push(("PageCount", vec![self.pages.len().to_string()]));
+        }
+
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<ComicInfo \
+             xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\" \
+             xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\n",
+        );
+
+        for &field in SCHEMA_FIELDS {
+            if field == "Pages" && !self.pages.is_empty() {
+                xml.push_str("  <Pages>\n");
+                for page in &self.pages {
+                    xml.push_str(&page_xml(page));
+                }
+                xml.push_str("  </Pages>\n");
+            }
+
+            // the schema allows each element once, so repeated keys become a list
+            if let Some((_, values)) = fields.iter().find(|(f, _)| *f == field) {
+                let value = escape(&values.join(", "));
+                xml.push_str(&format!("  <{field}>{value}</{field}>\n"));
+            }
+        }
+
+        xml.push_str("</ComicInfo>\n");
+        xml
+    }
+
+    /// finds the page entry for an image
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - zero-based index of the image within the archive
+    ///
+    /// # Returns
+    ///
+    /// the first `<Page>` entry for that image, or `None` if it has none
+    pub fn page(&self, image: u64) -> Option<&ComicPage> {
+        self.pages.iter().find(|page| page.image == image)
+    }
+
+    /// returns the BBF page flags for an image, or 0 if it has no page entry
+    pub fn page_flags(&self, image: u64) -> u32 {
+        self.page(image).map_or(0, ComicPage::flags)
+    }
+
+    /// iterates over the bookmarked pages as `(image, bookmark)` in document order
+    pub fn bookmarks(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.pages
+            .iter()
+            .filter_map(|page| Some((page.image, page.bookmark.as_deref()?)))
+    }
+}
+
+/// renders a `<Page>` element
+fn page_xml(page: &ComicPage) -> String {
+    let mut xml = format!("    <Page Image=\"{}\"", page.image);
+    if !page.kinds.is_empty() {
+        xml.push_str(&format!(" Type=\"{}\"", escape(&page.kinds.join(" "))));
+    }
+    if page.double_page {
+        xml.push_str(" DoublePage=\"true\"");
+    }
+    if let Some(bookmark) = &page.bookmark {
+        xml.push_str(&format!(" Bookmark=\"{}\"", escape(bookmark)));
+    }
+    xml.push_str(" />\n");
+    xml
+}
+
+/// reads a `<Page>` element, skipping it if it has no valid `Image` attribute
+fn parse_page(node: roxmltree::Node<'_, '_>) -> Option<ComicPage> {
+    Some(ComicPage {
+        image: node.attribute("Image")?.trim().parse().ok()?,
+        kinds: node
+            .attribute("Type")
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect(),
+        double_page: node
+            .attribute("DoublePage")
+            .is_some_and(|v| v.eq_ignore_ascii_case("true")),
+        bookmark: node
+            .attribute("Bookmark")
+            .map(str::trim)
+            .filter(|b| !b.is_empty())
+            .map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, assert2::check as assert};
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<ComicInfo xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <Title>The Beginning</Title>
+  <Series>Example Saga</Series>
+  <Number>1</Number>
+  <Volume>2</Volume>
+  <Count>-1</Count>
+  <Summary>Things &amp; stuff happen.</Summary>
+  <Year>2021</Year>
+  <Month>3</Month>
+  <Day>9</Day>
+  <Writer>Jane Doe</Writer>
+  <Penciller>John Roe</Penciller>
+  <LanguageISO>ja</LanguageISO>
+  <BlackAndWhite>Unknown</BlackAndWhite>
+  <Manga>YesAndRightToLeft</Manga>
+  <PageCount>4</PageCount>
+  <Pages>
+    <Page Image="0" Type="FrontCover" ImageSize="1234" />
+    <Page Image="1" Type="Story" Bookmark="Prologue" />
+    <Page Image="2" DoublePage="True" />
+    <Page Image="3" Type="BackCover" Bookmark="  " />
+    <Page Type="Story" />
+  </Pages>
+</ComicInfo>"#;
+
+    #[test]
+    fn test_parse_maps_fields_onto_metadata() {
+        let info = ComicInfo::parse(SAMPLE).unwrap();
+        let metadata: Vec<_> = info
+            .metadata
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+
+        assert!(
+            metadata
+                == [
+                    ("Title", "The Beginning"),
+                    ("Series", "Example Saga"),
+                    ("Number", "1"),
+                    ("Volume", "2"),
+                    ("Summary", "Things & stuff happen."),
+                    ("Author", "Jane Doe"),
+                    ("Penciller", "John Roe"),
+                    ("Language", "ja"),
+                    ("ReadingDirection", "rtl"),
+                    ("Date", "2021-03-09"),
+                ]
+        );
+    }
+
+    #[test]
+    fn test_parse_maps_pages_onto_flags_and_bookmarks() {
+        let info = ComicInfo::parse(SAMPLE).unwrap();
+
+        assert!(info.pages.len() == 4);
+        assert!(info.page_flags(0) == BBF_PAGE_FRONT_COVER_FLAG);
+        assert!(info.page_flags(1) == 0);
+        assert!(info.page_flags(2) == BBF_PAGE_DOUBLE_SPREAD_FLAG);
+        assert!(info.page_flags(3) == BBF_PAGE_BACK_COVER_FLAG);
+        assert!(info.page_flags(9) == 0);
+        assert!(info.bookmarks().collect::<Vec<_>>() == [(1, "Prologue")]);
+    }
+
+    #[test]
+    fn test_to_xml_round_trips_through_parse() {
+        let info = ComicInfo::parse(SAMPLE).unwrap();
+        let xml = info.to_xml();
+
+        assert!(xml.contains("<Summary>Things &amp; stuff happen.</Summary>"));
+        assert!(xml.contains("<Manga>YesAndRightToLeft</Manga>"));
+        assert!(xml.contains("<PageCount>4</PageCount>"));
+        assert!(ComicInfo::parse(&xml).unwrap() == info);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_documents() {
+        assert!(matches!(
+            ComicInfo::parse("<ComicInfo><Title>x</ComicInfo>"),
+            Err(BbfError::ComicInfo { .. })
+        ));
+        assert!(matches!(
+            ComicInfo::parse("<Other />"),
+            Err(BbfError::ComicInfo { .. })
+        ));
+        assert!(ComicInfo::parse("<ComicInfo/>").unwrap() == ComicInfo::default());
+    }
+}
@@ -0,0 +1,273 @@
+//! golden-file conformance corpus
+//!
+//! `fixtures/conformance/` holds hand-crafted BBF files alongside `expected.txt`, which records
+//! the outcome every reader should reach for each one: the result of a hardened open, the worst
+//! severity a deep validation finds, and whether integrity checking passes. the manifest is plain
+//! text so other implementations can be checked against the same corpus.
+//!
+//! the fixtures are checked in and never regenerated by the normal test run. after an intentional
+//! format change, regenerate them with
+//! `cargo test conformance::regenerate_fixtures -- --ignored` and review the diff.
+#![allow(clippy::missing_panics_doc)]
+
+use {
+    crate::{BbfBuilder, BbfError, BbfReader, Storage, format::*, types::*},
+    assert2::check as assert,
+    std::path::{Path, PathBuf},
+};
+
+/// returns the directory holding the conformance corpus
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/conformance")
+}
+
+/// the manifest label for an open failure
+fn error_label(error: &BbfError) -> &'static str {
+    match error {
+        BbfError::FileTooSmall => "file_too_small",
+        BbfError::InvalidMagic => "invalid_magic",
+        BbfError::InvalidOffset { .. } => "invalid_offset",
+        BbfError::UnsupportedExpansion { .. } => "unsupported_expansion",
+        BbfError::Io(_) => "io",
+        _ => "other",
+    }
+}
+
+/// the manifest columns reached by reading a fixture
+fn outcome<S: Storage>(opened: crate::Result<BbfReader<S>>) -> [String; 3] {
+    let reader = match opened {
+        Ok(reader) => reader,
+        Err(e) => return [error_label(&e).into(), "-".into(), "-".into()],
+    };
+
+    let report = reader.validate_deep();
+    let validate = if report.has_errors() {
+        "errors"
+    } else if report.is_clean() {
+        "clean"
+    } else {
+        "warnings"
+    };
+
+    let integrity = match reader.integrity_report() {
+        Ok(report) if report.is_ok() => "ok",
+        Ok(_) => "corrupt",
+        Err(e) => error_label(&e),
+    };
+
+    ["ok".into(), validate.into(), integrity.into()]
+}
+
+/// parses `expected.txt` into (file name, expected columns) pairs
+fn manifest() -> Vec<(String, [String; 3])> {
+    let text = std::fs::read_to_string(fixtures_dir().join("expected.txt")).unwrap();
+
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            assert!(fields.len() == 4, "malformed manifest line: {line}");
+            (
+                fields[0].to_string(),
+                [fields[1].into(), fields[2].into(), fields[3].into()],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_corpus_matches_expected_outcomes() {
+    let entries = manifest();
+    assert!(!entries.is_empty());
+
+    for (name, expected) in entries {
+        let path = fixtures_dir().join(&name);
+        let data = std::fs::read(&path).unwrap();
+
+        let hardened = outcome(BbfReader::open_hardened(&path));
+        assert!(hardened == expected, "{name}: hardened open");
+
+        let in_memory = outcome(BbfReader::from_storage_hardened(data.clone()));
+        assert!(in_memory == expected, "{name}: hardened in-memory open");
+
+        // the zero-copy reader checks tables lazily, so it may open files the hardened reader
+        // rejects, but it must agree on everything the hardened reader accepts
+        let zero_copy = outcome(BbfReader::from_bytes(data));
+        if expected[0] == "ok" {
+            assert!(zero_copy == expected, "{name}: zero-copy open");
+        }
+    }
+}
+
+#[test]
+fn test_corpus_has_no_unlisted_fixtures() {
+    let listed: Vec<String> = manifest().into_iter().map(|(name, _)| name).collect();
+
+    for entry in std::fs::read_dir(fixtures_dir()).unwrap() {
+        let name = entry.unwrap().file_name().to_string_lossy().into_owned();
+        if name.ends_with(".bbf") {
+            assert!(listed.contains(&name), "{name} has no expected outcome");
+        }
+    }
+}
+
+/// overwrites `bytes` at `offset`
+#[macroni_n_cheese::mathinator2000]
+fn patch(data: &mut [u8], offset: usize, bytes: &[u8]) {
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+/// builds a book with the builder and returns its bytes
+fn build(configure: impl FnOnce(&mut BbfBuilder)) -> Vec<u8> {
+    let output = tempfile::NamedTempFile::new().unwrap();
+    let mut builder = BbfBuilder::new(output.path(), 3, 3, 0).unwrap();
+    configure(&mut builder);
+    builder.finalize().unwrap();
+    std::fs::read(output.path()).unwrap()
+}
+
+/// a two-page book with a section and a metadata entry
+fn minimal() -> Vec<u8> {
+    build(|builder| {
+        builder
+            .add_page_bytes(&[0x11; 64], MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&[0x22; 48], MediaType::Jpg, 0, 0)
+            .unwrap();
+        builder.add_section("Chapter 1", 0, None);
+        builder.add_metadata("Title", "Conformance", None);
+    })
+}
+
+/// byte offset of the footer
+fn footer_at(data: &[u8]) -> usize {
+    BbfHeader::from_le_bytes(data).unwrap().footer_offset as usize
+}
+
+/// the decoded footer
+fn footer_of(data: &[u8]) -> BbfFooter {
+    BbfFooter::from_le_bytes(&data[footer_at(data)..]).unwrap()
+}
+
+#[test]
+#[ignore = "rewrites the checked-in conformance corpus"]
+#[macroni_n_cheese::mathinator2000]
+fn regenerate_fixtures() {
+    let mut fixtures: Vec<(&str, Vec<u8>)> = Vec::new();
+
+    fixtures.push(("valid_minimal.bbf", minimal()));
+    fixtures.push(("valid_empty.bbf", build(|_| {})));
+    fixtures.push((
+        "valid_dedup.bbf",
+        build(|builder| {
+            for byte in [0x11, 0x22, 0x11] {
+                builder
+                    .add_page_bytes(&[byte; 32], MediaType::Png, 0, 0)
+                    .unwrap();
+            }
+        }),
+    ));
+    fixtures.push((
+        "valid_thumbnail.bbf",
+        build(|builder| {
+            builder
+                .add_page_bytes(&[0x11; 64], MediaType::Png, 0, 0)
+                .unwrap();
+            builder
+                .set_thumbnail(&[0x33; 16], MediaType::Jpg, 4, 4)
+                .unwrap();
+        }),
+    ));
+
+    let base = minimal();
+    let footer = footer_of(&base);
+    let footer_at = footer_at(&base);
+    let asset_at = footer.asset_offset as usize;
+    let page_at = footer.page_offset as usize;
+    let section_at = footer.section_offset as usize;
+
+    fixtures.push(("truncated_header.bbf", base[..32].to_vec()));
+    fixtures.push(("truncated_footer.bbf", base[..base.len() - 100].to_vec()));
+
+    let mut data = base.clone();
+    patch(&mut data, 0, b"BBF2");
+    fixtures.push(("bad_magic.bbf", data));
+
+    let mut data = base.clone();
+    patch(&mut data, 16, &u64::MAX.to_le_bytes());
+    fixtures.push(("footer_offset_past_end.bbf", data));
+
+    let mut data = base.clone();
+    patch(&mut data, footer_at, &(base.len() as u64 + 1).to_le_bytes());
+    fixtures.push(("asset_offset_past_end.bbf", data));
+
+    let mut data = base.clone();
+    patch(&mut data, footer_at + 64, &u64::MAX.to_le_bytes());
+    fixtures.push(("huge_page_count.bbf", data));
+
+    let mut data = base.clone();
+    patch(&mut data, footer_at + 56, &(1u64 << 40).to_le_bytes());
+    fixtures.push(("huge_asset_count.bbf", data));
+
+    let first_asset = AssetEntry::from_le_bytes(&base[asset_at..]).unwrap();
+
+    let mut data = base.clone();
+    patch(
+        &mut data,
+        asset_at + 48,
+        &{ first_asset.file_offset }.to_le_bytes(),
+    );
+    fixtures.push(("overlapping_assets.bbf", data));
+
+    let mut data = base.clone();
+    patch(&mut data, asset_at + 24, &(1u64 << 40).to_le_bytes());
+    fixtures.push(("asset_out_of_bounds.bbf", data));
+
+    let mut data = base.clone();
+    patch(&mut data, page_at, &99u64.to_le_bytes());
+    fixtures.push(("page_asset_out_of_range.bbf", data));
+
+    let mut data = base.clone();
+    patch(&mut data, section_at, &10_000u64.to_le_bytes());
+    fixtures.push(("dangling_string.bbf", data));
+
+    let mut data = base.clone();
+    let damaged = first_asset.file_offset as usize;
+    data[damaged] ^= 0xFF;
+    fixtures.push(("corrupted_asset.bbf", data));
+
+    let mut data = base.clone();
+    patch(&mut data, footer_at + 104, &0u64.to_le_bytes());
+    fixtures.push(("index_hash_mismatch.bbf", data));
+
+    let mut data = base.clone();
+    data[24] = 1;
+    fixtures.push(("reserved_nonzero.bbf", data));
+
+    let mut data = base.clone();
+    data.extend_from_slice(&[0xAB; 16]);
+    fixtures.push(("trailing_data.bbf", data));
+
+    fixtures.push((
+        "required_unknown_expansion.bbf",
+        build(|builder| {
+            builder
+                .add_page_bytes(&[0x11; 64], MediaType::Png, 0, 0)
+                .unwrap();
+            builder.add_expansion_entry(Expansion::new(
+                0xFFFF,
+                1,
+                [0; 8],
+                BBF_EXPANSION_REQUIRED_FLAG,
+            ));
+        }),
+    ));
+
+    let dir = fixtures_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, data) in fixtures {
+        std::fs::write(dir.join(name), data).unwrap();
+    }
+}
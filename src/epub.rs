@@ -0,0 +1,667 @@
+use {
+    crate::{
+        BbfError, Book, Result, SectionView, Storage,
+        format::{BBF_PAGE_DOUBLE_SPREAD_FLAG, BBF_PAGE_FRONT_COVER_FLAG},
+        keys,
+        types::MediaType,
+        xml::escape,
+    },
+    std::{
+        borrow::Cow,
+        collections::HashMap,
+        fs::File,
+        io::{BufWriter, Cursor, Seek, Write},
+        path::Path,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions},
+};
+
+/// the viewport used for pages whose image size can't be read and that have no page before them
+const DEFAULT_VIEWPORT: (u32, u32) = (1000, 1500);
+
+/// the OCF container document pointing readers at the package document
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+/// an image written to the package, shared by every page that displays its asset
+struct Image {
+    /// manifest id, also used as the file stem
+    id: String,
+    /// path relative to the package document
+    href: String,
+    media_type: &'static str,
+    width: u32,
+    height: u32,
+}
+
+/// writes a book as a fixed-layout EPUB 3 package
+///
+/// every page becomes an XHTML document showing its image at full size, with a viewport that
+/// matches the image dimensions. assets shared by several pages are stored once and listed in
+/// the manifest with the MIME type of their [`MediaType`]; images in a format outside the EPUB
+/// core media types (anything but PNG, JPEG, GIF and WebP) are converted to PNG. the navigation
+/// document mirrors the section tree, the package metadata is mapped from the well-known
+/// [`crate::keys`], and an `rtl`/`ltr` [`keys::READING_DIRECTION`] sets the spine's
+/// `page-progression-direction`. front-cover pages provide the `cover-image` (falling back to
+/// the first page) and double-spread pages are centered across both halves of a spread.
+///
+/// # Arguments
+///
+/// * `book` - the book to export
+/// * `writer` - where to write the package
+///
+/// # Returns
+///
+/// the writer, after the package has been finished
+///
+/// # Errors
+///
+/// returns an error if the book has no pages, a page's data can't be read or converted, or the
+/// package can't be written
+#[macroni_n_cheese::mathinator2000]
+pub fn write_epub<S: Storage, W: Write + Seek>(book: &Book<S>, writer: W) -> Result<W> {
+    if book.page_count() == 0 {
+        return Err(BbfError::Other {
+            message: "Can't export a book with no pages to EPUB".to_string(),
+        });
+    }
+
+    let width = book.page_count().to_string().len().max(4);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default();
+
+    let mut zip = ZipWriter::new(writer);
+
+    // the mimetype has to be the first entry and can't be compressed
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(CONTAINER_XML.as_bytes())?;
+
+    let cover = book
+        .pages()
+        .find(|page| page.flags & BBF_PAGE_FRONT_COVER_FLAG != 0)
+        .map_or(0, |page| page.index);
+
+    let mut images: Vec<Image> = Vec::new();
+    let mut by_asset: HashMap<u64, usize> = HashMap::new();
+    let mut pages = Vec::with_capacity(book.pages().len());
+    let mut cover_image = 0;
+    let mut last_size = DEFAULT_VIEWPORT;
+
+    for page in book.pages() {
+        let offset = page.asset.file_offset;
+        let image = match by_asset.get(&offset) {
+            Some(&image) => image,
+            None => {
+                let (data, media_type) = core_image(page.data()?, page.media_type)?;
+                let number = images.len() + 1;
+                let id = format!("i{number:0width$}");
+                let href = format!("images/{id}{}", media_type.as_extension());
+
+                let (image_width, image_height) = image_size(&data).unwrap_or(last_size);
+                zip.start_file(format!("OEBPS/{href}"), stored)?;
+                zip.write_all(&data)?;
+
+                images.push(Image {
+                    id,
+                    href,
+                    media_type: media_type.as_mime_type(),
+                    width: image_width,
+                    height: image_height,
+                });
+                by_asset.insert(offset, images.len() - 1);
+                images.len() - 1
+            }
+        };
+
+        if page.index == cover {
+            cover_image = image;
+        }
+        last_size = (images[image].width, images[image].height);
+
+        let number = page.index + 1;
+        zip.start_file(format!("OEBPS/pages/p{number:0width$}.xhtml"), deflated)?;
+        zip.write_all(page_xhtml(number, &images[image]).as_bytes())?;
+        pages.push((number, page.flags));
+    }
+
+    zip.start_file("OEBPS/nav.xhtml", deflated)?;
+    zip.write_all(nav_xhtml(book, width).as_bytes())?;
+
+    zip.start_file("OEBPS/content.opf", deflated)?;
+    zip.write_all(package_opf(book, &images, cover_image, &pages, width).as_bytes())?;
+
+    Ok(zip.finish()?)
+}
+
+/// exports a whole book as an EPUB file
+///
+/// # Arguments
+///
+/// * `book` - the book to export
+/// * `output` - path of the EPUB file to create, replacing any existing file
+///
+/// # Errors
+///
+/// returns an error if the file can't be created or [`write_epub`] fails
+pub fn to_epub<S: Storage, P: AsRef<Path>>(book: &Book<S>, output: P) -> Result<()> {
+    let file = BufWriter::new(File::create(output)?);
+    write_epub(book, file)?.into_inner()?;
+    Ok(())
+}
+
+/// reads an image's dimensions from its header without decoding it
+fn image_size(data: &[u8]) -> Option<(u32, u32)> {
+    image::ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+        .filter(|&(width, height)| width > 0 && height > 0)
+}
+
+/// returns an image in a format every EPUB reading system supports
+///
+/// EPUB core media types are kept as they are; anything else is converted to PNG, since readers
+/// aren't required to display it and the package would need a fallback for it
+///
+/// # Errors
+///
+/// returns an error if the image needs converting but can't be decoded
+fn core_image(data: Cow<'_, [u8]>, media_type: MediaType) -> Result<(Cow<'_, [u8]>, MediaType)> {
+    if matches!(
+        media_type,
+        MediaType::Png | MediaType::Jpg | MediaType::Gif | MediaType::Webp
+    ) {
+        return Ok((data, media_type));
+    }
+
+    let convert = || {
+        let mut png = Vec::new();
+        image::load_from_memory(&data)?
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+        Ok::<_, image::ImageError>(png)
+    };
+    let png = convert().map_err(|e| BbfError::Other {
+        message: format!("Can't convert a {media_type:?} page to PNG for EPUB: {e}"),
+    })?;
+    Ok((Cow::Owned(png), MediaType::Png))
+}
+
+/// builds the XHTML document for one page
+fn page_xhtml(number: u64, image: &Image) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head>
+  <title>Page {number}</title>
+  <meta name="viewport" content="width={width}, height={height}"/>
+  <style>html, body {{ margin: 0; padding: 0; }} img {{ display: block; width: {width}px; height: {height}px; }}</style>
+</head>
+<body>
+  <img src="../{href}" alt="Page {number}"/>
+</body>
+</html>
+"#,
+        width = image.width,
+        height = image.height,
+        href = image.href,
+    )
+}
+
+/// builds the navigation document from the section tree
+///
+/// books without sections get a single entry pointing at the first page, since the table of
+/// contents can't be empty
+fn nav_xhtml<S: Storage>(book: &Book<S>, width: usize) -> String {
+    let roots: Vec<_> = book.root_sections().collect();
+    let list = if roots.is_empty() {
+        format!(
+            "<ol>\n<li><a href=\"{}\">{}</a></li>\n</ol>\n",
+            page_href(0, book.page_count(), width),
+            escape(title(book))
+        )
+    } else {
+        nav_list(&roots, book.page_count(), width)
+    };
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head>
+  <title>{title}</title>
+</head>
+<body>
+<nav epub:type="toc" id="toc">
+<h1>{title}</h1>
+{list}</nav>
+</body>
+</html>
+"#,
+        title = escape(title(book)),
+    )
+}
+
+/// renders a list of sections and their children as nested `<ol>` elements
+fn nav_list(sections: &[SectionView<'_>], page_count: u64, width: usize) -> String {
+    let mut xml = String::from("<ol>\n");
+    for section in sections {
+        xml.push_str(&format!(
+            "<li><a href=\"{}\">{}</a>",
+            page_href(section.start(), page_count, width),
+            escape(section.title())
+        ));
+        let children: Vec<_> = section.children().collect();
+        if !children.is_empty() {
+            xml.push('\n');
+            xml.push_str(&nav_list(&children, page_count, width));
+        }
+        xml.push_str("</li>\n");
+    }
+    xml.push_str("</ol>\n");
+    xml
+}
+
+/// the path of a page document relative to the package document, clamped to the last page
+#[macroni_n_cheese::mathinator2000]
+fn page_href(index: u64, page_count: u64, width: usize) -> String {
+    let number = index.min(page_count - 1) + 1;
+    format!("pages/p{number:0width$}.xhtml")
+}
+
+/// the book's title, or a placeholder if it has none
+fn title<S: Storage>(book: &Book<S>) -> &str {
+    book.metadata_map().get(keys::TITLE).unwrap_or("Untitled")
+}
+
+/// removes a leading `urn:isbn:` from an ISBN, ignoring case
+fn strip_urn_prefix(isbn: &str) -> &str {
+    const PREFIX: &str = "urn:isbn:";
+    isbn.get(..PREFIX.len())
+        .filter(|start| start.eq_ignore_ascii_case(PREFIX))
+        .map_or(isbn, |_| &isbn[PREFIX.len()..])
+}
+
+/// builds the package document
+///
+/// `pages` holds the one-based number and flags of every page in reading order
+fn package_opf<S: Storage>(
+    book: &Book<S>,
+    images: &[Image],
+    cover_image: usize,
+    pages: &[(u64, u32)],
+    width: usize,
+) -> String {
+    let map = book.metadata_map();
+    let mut metadata = String::new();
+    let mut push = |element: String| {
+        metadata.push_str("    ");
+        metadata.push_str(&element);
+        metadata.push('\n');
+    };
+
+    let hash = book.reader().footer().footer_hash;
+    let identifier = map.get(keys::ISBN).map_or_else(
+        || format!("urn:bbf:{hash:016x}"),
+        |isbn| format!("urn:isbn:{}", strip_urn_prefix(isbn.trim())),
+    );
+    push(format!(
+        "<dc:identifier id=\"book-id\">{}</dc:identifier>",
+        escape(&identifier)
+    ));
+    push(format!("<dc:title>{}</dc:title>", escape(title(book))));
+    for author in map.get_all(keys::AUTHOR) {
+        push(format!("<dc:creator>{}</dc:creator>", escape(author)));
+    }
+    push(format!(
+        "<dc:language>{}</dc:language>",
+        escape(map.get(keys::LANGUAGE).unwrap_or("und"))
+    ));
+    if let Some(publisher) = map.get(keys::PUBLISHER) {
+        push(format!(
+            "<dc:publisher>{}</dc:publisher>",
+            escape(publisher)
+        ));
+    }
+    if let Some(date) = map.get(keys::DATE) {
+        push(format!("<dc:date>{}</dc:date>", escape(date)));
+    }
+    if let Some(series) = map.get(keys::SERIES) {
+        push(format!(
+            "<meta property=\"belongs-to-collection\" id=\"series\">{}</meta>",
+            escape(series)
+        ));
+        push("<meta refines=\"#series\" property=\"collection-type\">series</meta>".to_string());
+        if let Some(volume) = map.get(keys::VOLUME) {
+            push(format!(
+                "<meta refines=\"#series\" property=\"group-position\">{}</meta>",
+                escape(volume)
+            ));
+        }
+    }
+    push(format!(
+        "<meta property=\"dcterms:modified\">{}</meta>",
+        timestamp(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        )
+    ));
+    push("<meta property=\"rendition:layout\">pre-paginated</meta>".to_string());
+    push("<meta property=\"rendition:spread\">auto</meta>".to_string());
+
+    let mut manifest = String::from(
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n",
+    );
+    for (i, image) in images.iter().enumerate() {
+        let properties = if i == cover_image {
+            " properties=\"cover-image\""
+        } else {
+            ""
+        };
+        manifest.push_str(&format!(
+            "    <item id=\"{}\" href=\"{}\" media-type=\"{}\"{properties}/>\n",
+            image.id, image.href, image.media_type
+        ));
+    }
+
+    let mut spine = String::new();
+    for &(number, flags) in pages {
+        let id = format!("p{number:0width$}");
+        manifest.push_str(&format!(
+            "    <item id=\"{id}\" href=\"pages/{id}.xhtml\" media-type=\"application/xhtml+xml\"/>\n"
+        ));
+        let properties = if flags & BBF_PAGE_DOUBLE_SPREAD_FLAG != 0 {
+            " properties=\"rendition:page-spread-center\""
+        } else {
+            ""
+        };
+        spine.push_str(&format!("    <itemref idref=\"{id}\"{properties}/>\n"));
+    }
+
+    let direction = match map.get(keys::READING_DIRECTION).map(str::trim) {
+        Some(direction) if direction.eq_ignore_ascii_case("rtl") => {
+            " page-progression-direction=\"rtl\""
+        }
+        Some(direction) if direction.eq_ignore_ascii_case("ltr") => {
+            " page-progression-direction=\"ltr\""
+        }
+        _ => "",
+    };
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" prefix="rendition: http://www.idpf.org/vocab/rendition/#">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+{metadata}  </metadata>
+  <manifest>
+{manifest}  </manifest>
+  <spine{direction}>
+{spine}  </spine>
+</package>
+"#
+    )
+}
+
+/// formats seconds since the unix epoch as a UTC `dcterms:modified` timestamp
+/// (`CCYY-MM-DDThh:mm:ssZ`)
+#[macroni_n_cheese::mathinator2000]
+fn timestamp(seconds: u64) -> String {
+    let days = seconds / 86_400;
+    let time = seconds % 86_400;
+
+    // Howard Hinnant's civil_from_days, shifted so the year starts on March 1st
+    let shifted = days + 719_468;
+    let era = shifted / 146_097;
+    let day_of_era = shifted % 146_097;
+    let corrected = day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096;
+    let year_of_era = corrected / 365;
+    let year_start = year_of_era * 365 + year_of_era / 4 - year_of_era / 100;
+    let day_of_year = day_of_era - year_start;
+    let month_offset = day_of_year * 5 + 2;
+    let month_index = month_offset / 153;
+    let month_start = month_index * 153 + 2;
+    let day = day_of_year - month_start / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {
+        super::*,
+        crate::{BbfBuilder, types::MediaType},
+        assert2::check as assert,
+        std::io::Read,
+        tempfile::NamedTempFile,
+        zip::ZipArchive,
+    };
+
+    fn build_book() -> NamedTempFile {
+        let png = |width, height| {
+            let mut data = Vec::new();
+            image::RgbImage::new(width, height)
+                .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+                .unwrap();
+            data
+        };
+
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&png(8, 12), MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&png(6, 9), MediaType::Png, BBF_PAGE_FRONT_COVER_FLAG, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&png(16, 12), MediaType::Png, BBF_PAGE_DOUBLE_SPREAD_FLAG, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&[7u8; 64], MediaType::Jpg, 0, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&png(8, 12), MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_section("Volume 1", 0, None);
+        builder.add_section("Chapter <1>", 1, Some("Volume 1"));
+        builder.add_section("Chapter 2", 3, Some("Volume 1"));
+        builder.add_metadata("Title", "Saga & Co", None);
+        builder.add_metadata("Author", "Jane Doe", None);
+        builder.add_metadata("Author", "John Roe", None);
+        builder.add_metadata("Series", "Saga", None);
+        builder.add_metadata("Volume", "3", None);
+        builder.add_metadata("ISBN", "9781234567897", None);
+        builder.add_metadata("ReadingDirection", "RTL", None);
+        builder.finalize().unwrap();
+        temp_output
+    }
+
+    fn entries(data: Vec<u8>) -> Vec<(String, Vec<u8>)> {
+        let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut file = archive.by_index(i).unwrap();
+                let mut data = Vec::new();
+                file.read_to_end(&mut data).unwrap();
+                (file.name().to_string(), data)
+            })
+            .collect()
+    }
+
+    fn text<'a>(entries: &'a [(String, Vec<u8>)], name: &str) -> &'a str {
+        let data = &entries.iter().find(|(n, _)| n == name).unwrap().1;
+        std::str::from_utf8(data).unwrap()
+    }
+
+    #[test]
+    fn test_write_epub_packages_pages_images_and_metadata() {
+        let file = build_book();
+        let book = Book::open(file.path()).unwrap();
+
+        let data = write_epub(&book, Cursor::new(Vec::new()))
+            .unwrap()
+            .into_inner();
+        let mut archive = ZipArchive::new(Cursor::new(data.clone())).unwrap();
+        assert!(archive.by_index(0).unwrap().compression() == CompressionMethod::Stored);
+
+        let entries = entries(data);
+        assert!(entries[0] == ("mimetype".to_string(), b"application/epub+zip".to_vec()));
+        let images: Vec<_> = entries
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .filter(|name| name.starts_with("OEBPS/images/"))
+            .collect();
+        assert!(
+            images
+                == [
+                    "OEBPS/images/i0001.png",
+                    "OEBPS/images/i0002.png",
+                    "OEBPS/images/i0003.png",
+                    "OEBPS/images/i0004.jpg",
+                ]
+        );
+
+        let opf = text(&entries, "OEBPS/content.opf");
+        assert!(
+            opf.contains("<dc:identifier id=\"book-id\">urn:isbn:9781234567897</dc:identifier>")
+        );
+        assert!(opf.contains("<dc:title>Saga &amp; Co</dc:title>"));
+        assert!(opf.contains("<dc:creator>Jane Doe</dc:creator>"));
+        assert!(opf.contains("<dc:creator>John Roe</dc:creator>"));
+        assert!(opf.contains("<dc:language>und</dc:language>"));
+        assert!(opf.contains("<meta property=\"belongs-to-collection\" id=\"series\">Saga</meta>"));
+        assert!(opf.contains("<meta refines=\"#series\" property=\"group-position\">3</meta>"));
+        assert!(opf.contains("<meta property=\"rendition:layout\">pre-paginated</meta>"));
+        assert!(opf.contains(
+            "<item id=\"i0002\" href=\"images/i0002.png\" media-type=\"image/png\" properties=\"cover-image\"/>"
+        ));
+        assert!(
+            opf.contains(
+                "<item id=\"i0004\" href=\"images/i0004.jpg\" media-type=\"image/jpeg\"/>"
+            )
+        );
+        assert!(opf.contains("<spine page-progression-direction=\"rtl\">"));
+        assert!(
+            opf.contains("<itemref idref=\"p0003\" properties=\"rendition:page-spread-center\"/>")
+        );
+        assert!(opf.matches("<itemref ").count() == 5);
+
+        let page = text(&entries, "OEBPS/pages/p0003.xhtml");
+        assert!(page.contains("content=\"width=16, height=12\""));
+        assert!(page.contains("src=\"../images/i0003.png\""));
+        // the jpeg can't be read, so its page reuses the size of the page before it
+        let page = text(&entries, "OEBPS/pages/p0004.xhtml");
+        assert!(page.contains("content=\"width=16, height=12\""));
+        // identical pages share one image
+        let page = text(&entries, "OEBPS/pages/p0005.xhtml");
+        assert!(page.contains("src=\"../images/i0001.png\""));
+    }
+
+    #[test]
+    fn test_write_epub_builds_nav_from_section_tree() {
+        let file = build_book();
+        let book = Book::open(file.path()).unwrap();
+
+        let entries = entries(
+            write_epub(&book, Cursor::new(Vec::new()))
+                .unwrap()
+                .into_inner(),
+        );
+        let nav = text(&entries, "OEBPS/nav.xhtml");
+        assert!(nav.contains(
+            "<ol>\n<li><a href=\"pages/p0001.xhtml\">Volume 1</a>\n<ol>\n\
+             <li><a href=\"pages/p0002.xhtml\">Chapter &lt;1&gt;</a></li>\n\
+             <li><a href=\"pages/p0004.xhtml\">Chapter 2</a></li>\n</ol>\n</li>\n</ol>\n"
+        ));
+    }
+
+    #[test]
+    fn test_write_epub_rejects_empty_books() {
+        let temp_output = NamedTempFile::new().unwrap();
+        BbfBuilder::with_defaults(temp_output.path())
+            .unwrap()
+            .finalize()
+            .unwrap();
+        let book = Book::open(temp_output.path()).unwrap();
+
+        assert!(write_epub(&book, Cursor::new(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn test_write_epub_converts_non_core_images_to_png() {
+        let mut bmp = Vec::new();
+        image::RgbImage::new(5, 7)
+            .write_to(&mut Cursor::new(&mut bmp), image::ImageFormat::Bmp)
+            .unwrap();
+
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder.add_page_bytes(&bmp, MediaType::Bmp, 0, 0).unwrap();
+        builder.add_metadata("ISBN", "URN:ISBN:9781234567897", None);
+        builder.finalize().unwrap();
+        let book = Book::open(temp_output.path()).unwrap();
+
+        let entries = entries(
+            write_epub(&book, Cursor::new(Vec::new()))
+                .unwrap()
+                .into_inner(),
+        );
+        let image = &entries
+            .iter()
+            .find(|(name, _)| name == "OEBPS/images/i0001.png")
+            .unwrap()
+            .1;
+        assert!(MediaType::from_bytes(image) == MediaType::Png);
+        assert!(image_size(image) == Some((5, 7)));
+
+        let opf = text(&entries, "OEBPS/content.opf");
+        assert!(opf.contains("href=\"images/i0001.png\" media-type=\"image/png\""));
+        assert!(!opf.contains("image/bmp"));
+        assert!(
+            opf.contains("<dc:identifier id=\"book-id\">urn:isbn:9781234567897</dc:identifier>")
+        );
+    }
+
+    #[test]
+    fn test_write_epub_rejects_images_it_cant_convert() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[0xFF, 0x0A, 1, 2, 3], MediaType::Jxl, 0, 0)
+            .unwrap();
+        builder.finalize().unwrap();
+        let book = Book::open(temp_output.path()).unwrap();
+
+        assert!(write_epub(&book, Cursor::new(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_formats_utc_dates() {
+        assert!(timestamp(0) == "1970-01-01T00:00:00Z");
+        assert!(timestamp(951_782_400) == "2000-02-29T00:00:00Z");
+        assert!(timestamp(1_792_152_245) == "2026-10-16T12:04:05Z");
+    }
+}
@@ -131,6 +131,62 @@ pub enum BbfError {
         exponent: u8,
     },
 
+    /// File has the petrification flag set and must not be modified
+    #[error("File is petrified and cannot be modified")]
+    #[diagnostic(
+        code(boundbook::petrified),
+        help(
+            "The file was marked as permanently read-only. Build a new file from its contents instead of editing it in place."
+        )
+    )]
+    Petrified,
+
+    /// File contains a required expansion record of an unknown kind
+    #[error("Unsupported required expansion record (kind {kind:#x})")]
+    #[diagnostic(
+        code(boundbook::unsupported_expansion),
+        help(
+            "The file uses a format extension this version of boundbook doesn't understand. Try a newer version."
+        )
+    )]
+    UnsupportedExpansion {
+        /// the unknown kind id
+        kind: u64,
+    },
+
+    /// Thumbnail couldn't be generated
+    #[error("Thumbnail generation failed: {reason}")]
+    #[diagnostic(
+        code(boundbook::thumbnail),
+        help("The source image couldn't be decoded or re-encoded. Try a different cover image.")
+    )]
+    Thumbnail {
+        /// the underlying image error
+        reason: String,
+    },
+
+    /// ComicInfo.xml couldn't be parsed
+    #[error("Invalid ComicInfo.xml: {reason}")]
+    #[diagnostic(
+        code(boundbook::comic_info),
+        help("The archive's ComicInfo.xml isn't well-formed XML. Fix or remove it and try again.")
+    )]
+    ComicInfo {
+        /// the underlying parse error
+        reason: String,
+    },
+
+    /// An archive couldn't be read or written
+    #[error("Archive error: {reason}")]
+    #[diagnostic(
+        code(boundbook::archive),
+        help("The archive is damaged, uses an unsupported feature, or couldn't be written.")
+    )]
+    Archive {
+        /// the underlying archive error
+        reason: String,
+    },
+
     /// Clipboard operation failed
     ///
     /// Wraps errors from the arboard clipboard library
@@ -173,6 +229,15 @@ impl From<String> for BbfError {
     }
 }
 
+#[cfg(any(feature = "cbz", feature = "epub"))]
+impl From<zip::result::ZipError> for BbfError {
+    fn from(value: zip::result::ZipError) -> Self {
+        Self::Archive {
+            reason: value.to_string(),
+        }
+    }
+}
+
 impl From<miette::Report> for BbfError {
     fn from(value: miette::Report) -> Self {
         Self::Generic(value.into())
@@ -226,6 +291,9 @@ mod tests {
         let err = BbfError::InvalidUtf8;
         assert!(format!("{err}").contains("Invalid UTF-8"));
 
+        let err = BbfError::Petrified;
+        assert!(format!("{err}").contains("petrified"));
+
         let err = BbfError::AlignmentTooLarge { exponent: 20 };
         let msg = format!("{err}");
         assert!(msg.contains("20"));
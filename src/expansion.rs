@@ -0,0 +1,94 @@
+use crate::types::Expansion;
+
+/// a typed expansion record
+///
+/// implement this for each kind of extension data stored in the expansion table. records are
+/// written with [`crate::BbfBuilder::add_expansion`] and read back with
+/// [`crate::BbfReader::expansion_records`]. the kind id must be unique. readers refuse files with
+/// required records of kinds they don't know, so books using a record with
+/// [`ExpansionRecord::REQUIRED`] set have to be opened with
+/// [`crate::BbfReader::open_with_expansions`] (or
+/// [`crate::BbfReader::from_storage_with_expansions`]) listing its [`ExpansionRecord::KIND`].
+pub trait ExpansionRecord: Sized {
+    /// unique kind id stored in `exp_reserved[0]`
+    const KIND: u64;
+
+    /// newest payload layout version this implementation writes
+    const VERSION: u64;
+
+    /// whether readers that don't know this kind must refuse the file
+    const REQUIRED: bool = false;
+
+    /// encodes the record into the 8-word expansion payload
+    ///
+    /// # Returns
+    ///
+    /// the payload words to store in `exp_reserved[2..10]`
+    fn to_payload(&self) -> [u64; 8];
+
+    /// decodes a record from an expansion payload
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - the payload layout version the record was written with
+    /// * `payload` - the payload words from `exp_reserved[2..10]`
+    ///
+    /// # Returns
+    ///
+    /// the decoded record, or `None` if this version can't be decoded (the record is then
+    /// skipped like an unknown kind)
+    fn from_payload(version: u64, payload: [u64; 8]) -> Option<Self>;
+
+    /// encodes the record as a complete expansion table entry
+    ///
+    /// # Returns
+    ///
+    /// the expansion entry, flagged as required if [`ExpansionRecord::REQUIRED`] is set
+    fn to_expansion(&self) -> Expansion {
+        let flags = if Self::REQUIRED {
+            crate::format::BBF_EXPANSION_REQUIRED_FLAG
+        } else {
+            0
+        };
+
+        Expansion::new(Self::KIND, Self::VERSION, self.to_payload(), flags)
+    }
+
+    /// decodes a record from an expansion table entry
+    ///
+    /// # Arguments
+    ///
+    /// * `expansion` - the expansion table entry
+    ///
+    /// # Returns
+    ///
+    /// the decoded record, or `None` if the entry is of a different kind or an unsupported
+    /// version
+    fn from_expansion(expansion: &Expansion) -> Option<Self> {
+        if expansion.kind() != Self::KIND {
+            return None;
+        }
+
+        Self::from_payload(expansion.version(), expansion.payload())
+    }
+}
+
+/// finds the first required expansion whose kind isn't registered or known to the caller
+///
+/// # Arguments
+///
+/// * `expansions` - the expansion table
+/// * `known_kinds` - kinds understood by the caller on top of the registered ones
+///
+/// # Returns
+///
+/// the offending kind id, if any
+pub(crate) fn first_unsupported_required(
+    expansions: &[Expansion],
+    known_kinds: &[u64],
+) -> Option<u64> {
+    expansions
+        .iter()
+        .find(|exp| exp.is_required() && !exp.is_registered() && !known_kinds.contains(&exp.kind()))
+        .map(Expansion::kind)
+}
@@ -0,0 +1,193 @@
+use {
+    crate::{BbfBuilder, BbfReader, Result, Storage},
+    rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator},
+};
+
+/// why an asset failed verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetFault {
+    /// the asset's data doesn't match its stored hash
+    HashMismatch,
+    /// the asset's data range lies outside the file
+    OutOfBounds,
+}
+
+/// an asset that failed verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptedAsset {
+    /// index of the asset in the asset table
+    pub asset_index: u64,
+    /// why the asset failed
+    pub fault: AssetFault,
+    /// indices of every page displaying this asset, in page order
+    pub pages: Vec<u64>,
+}
+
+/// the result of hashing the index and every asset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// whether the footer hash over the index region matches
+    pub index_hash_ok: bool,
+    /// number of assets checked
+    pub assets_checked: u64,
+    /// every asset that failed, ordered by asset index
+    pub corrupted_assets: Vec<CorruptedAsset>,
+}
+
+impl IntegrityReport {
+    /// checks whether the index and every asset passed
+    pub const fn is_ok(&self) -> bool {
+        self.index_hash_ok && self.corrupted_assets.is_empty()
+    }
+
+    /// returns the indices of every page that displays a corrupted asset, sorted and deduped
+    pub fn damaged_pages(&self) -> Vec<u64> {
+        let mut pages: Vec<u64> = self
+            .corrupted_assets
+            .iter()
+            .flat_map(|asset| asset.pages.iter().copied())
+            .collect();
+        pages.sort_unstable();
+        pages.dedup();
+        pages
+    }
+}
+
+/// hashes the index region and every asset
+///
+/// # Arguments
+///
+/// * `reader` - the reader to check
+///
+/// # Returns
+///
+/// the full report; unlike [`BbfReader::verify_integrity`] this doesn't stop at the first failure
+///
+/// # Errors
+///
+/// returns an error if the index region bounds or the asset and page tables are invalid
+pub(crate) fn check<S: Storage>(reader: &BbfReader<S>) -> Result<IntegrityReport> {
+    let index_hash_ok = reader.verify_index_hash()?;
+    let assets = reader.assets()?;
+    let pages = reader.pages()?;
+
+    let mut corrupted_assets: Vec<CorruptedAsset> = assets
+        .par_iter()
+        .enumerate()
+        .filter_map(|(i, asset)| {
+            let fault = match reader.get_asset_data(asset) {
+                Ok(data) => {
+                    let hash_128 = BbfBuilder::calculate_hash_128(&data);
+                    let hash = [hash_128 as u64, (hash_128 >> 64) as u64];
+                    (hash != { asset.asset_hash }).then_some(AssetFault::HashMismatch)?
+                }
+                Err(_) => AssetFault::OutOfBounds,
+            };
+
+            Some(CorruptedAsset {
+                asset_index: i as u64,
+                fault,
+                pages: Vec::new(),
+            })
+        })
+        .collect();
+
+    corrupted_assets.sort_unstable_by_key(|asset| asset.asset_index);
+
+    if !corrupted_assets.is_empty() {
+        for (page_index, page) in pages.iter().enumerate() {
+            let asset_index = page.asset_index;
+            if let Ok(i) =
+                corrupted_assets.binary_search_by_key(&asset_index, |asset| asset.asset_index)
+            {
+                corrupted_assets[i].pages.push(page_index as u64);
+            }
+        }
+    }
+
+    Ok(IntegrityReport {
+        index_hash_ok,
+        assets_checked: assets.len() as u64,
+        corrupted_assets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, crate::types::MediaType, assert2::check as assert, tempfile::NamedTempFile};
+
+    fn build_book() -> NamedTempFile {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&[2u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&[1u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder.finalize().unwrap();
+        temp_output
+    }
+
+    #[test]
+    fn test_clean_book_passes() {
+        let book = build_book();
+        let reader = BbfReader::open(book.path()).unwrap();
+        let report = reader.integrity_report().unwrap();
+
+        assert!(report.is_ok());
+        assert!(report.assets_checked == 2);
+        assert!(report.damaged_pages().is_empty());
+    }
+
+    #[test]
+    fn test_reports_corrupted_asset_and_its_pages() {
+        let book = build_book();
+        let reader = BbfReader::open(book.path()).unwrap();
+        let offset = reader.assets().unwrap()[0].file_offset as usize;
+        drop(reader);
+
+        let mut data = std::fs::read(book.path()).unwrap();
+        data[offset] ^= 0xFF;
+        std::fs::write(book.path(), data).unwrap();
+
+        let reader = BbfReader::open(book.path()).unwrap();
+        let report = reader.integrity_report().unwrap();
+
+        assert!(!report.is_ok());
+        assert!(report.index_hash_ok);
+        assert!(
+            report.corrupted_assets
+                == vec![CorruptedAsset {
+                    asset_index: 0,
+                    fault: AssetFault::HashMismatch,
+                    pages: vec![0, 2],
+                }]
+        );
+        assert!(report.damaged_pages() == vec![0, 2]);
+    }
+
+    #[test]
+    #[macroni_n_cheese::mathinator2000]
+    fn test_reports_index_hash_mismatch() {
+        let book = build_book();
+        let reader = BbfReader::open(book.path()).unwrap();
+        let page_offset = reader.footer().page_offset as usize;
+        drop(reader);
+
+        let mut data = std::fs::read(book.path()).unwrap();
+        data[page_offset + 8] = 1;
+        std::fs::write(book.path(), data).unwrap();
+
+        let reader = BbfReader::open(book.path()).unwrap();
+        let report = reader.integrity_report().unwrap();
+
+        assert!(!report.index_hash_ok);
+        assert!(report.corrupted_assets.is_empty());
+        assert!(!report.is_ok());
+    }
+}
@@ -0,0 +1,341 @@
+//! interop suite against the reference libbbf implementation
+//!
+//! `fixtures/libbbf/cases.txt` describes a set of books by their builder parameters and input
+//! files. for every case, boundbook builds the book itself and checks that it reads back to exactly
+//! those inputs with the expected asset alignment. the reader must parse the matching `<case>.bbf`
+//! produced by the reference C++ implementation the same way, and the builder must reproduce it
+//! byte for byte. a missing reference file, or one whose libbbf commit and command aren't recorded,
+//! fails those tests; they are ignored until the files are checked in, and run with
+//! `cargo test interop -- --include-ignored`.
+//!
+//! the input files are checked in and never regenerated by the normal test run. to rewrite them,
+//! run `cargo test interop::regenerate_inputs -- --ignored`, then rebuild every reference file.
+#![allow(clippy::missing_panics_doc)]
+
+use {
+    crate::{BbfBuilder, BbfReader, Storage, format::*, types::*},
+    assert2::check as assert,
+    std::path::{Path, PathBuf},
+};
+
+/// returns the directory holding the interop cases
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/libbbf")
+}
+
+/// a section as listed in the manifest
+struct CaseSection {
+    title: String,
+    start: u64,
+    parent: Option<String>,
+}
+
+/// a metadata entry as listed in the manifest
+struct CaseMeta {
+    key: String,
+    value: String,
+    parent: Option<String>,
+}
+
+/// one book described by the manifest
+struct Case {
+    name: String,
+    alignment: u8,
+    ream_size: u8,
+    flags: u32,
+    pages: Vec<String>,
+    sections: Vec<CaseSection>,
+    metadata: Vec<CaseMeta>,
+    /// the libbbf command the reference file was produced with
+    command: Option<String>,
+}
+
+impl Case {
+    /// path of the reference file produced by libbbf
+    fn reference_path(&self) -> PathBuf {
+        fixtures_dir().join(format!("{}.bbf", self.name))
+    }
+
+    /// path of a page input
+    fn input_path(name: &str) -> PathBuf {
+        fixtures_dir().join("inputs").join(name)
+    }
+
+    /// the alignment an asset of `size` bytes must start on
+    const fn expected_alignment(&self, size: u64) -> u64 {
+        if self.flags & BBF_VARIABLE_REAM_SIZE_FLAG != 0 && size < 1u64 << self.ream_size {
+            8
+        } else {
+            1u64 << self.alignment
+        }
+    }
+}
+
+/// an empty manifest field means "none"
+fn optional(field: Option<&str>) -> Option<String> {
+    field.filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// parses `cases.txt`
+fn cases() -> Vec<Case> {
+    let text = std::fs::read_to_string(fixtures_dir().join("cases.txt")).unwrap();
+    let mut cases: Vec<Case> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+        if fields[0] == "libbbf" {
+            continue;
+        }
+        if fields[0] == "case" {
+            assert!(fields.len() == 5, "malformed case line: {line}");
+            cases.push(Case {
+                name: fields[1].into(),
+                alignment: fields[2].parse().unwrap(),
+                ream_size: fields[3].parse().unwrap(),
+                flags: fields[4].parse().unwrap(),
+                pages: Vec::new(),
+                sections: Vec::new(),
+                metadata: Vec::new(),
+                command: None,
+            });
+            continue;
+        }
+
+        let case = cases.last_mut().expect("entry before the first case");
+        match fields[0] {
+            "page" => case.pages.push(fields[1].into()),
+            "section" => case.sections.push(CaseSection {
+                title: fields[1].into(),
+                start: fields[2].parse().unwrap(),
+                parent: optional(fields.get(3).copied()),
+            }),
+            "meta" => case.metadata.push(CaseMeta {
+                key: fields[1].into(),
+                value: fields[2].into(),
+                parent: optional(fields.get(3).copied()),
+            }),
+            // the command itself may contain `|`
+            "command" => case.command = optional(line.split_once('|').map(|(_, c)| c.trim())),
+            other => panic!("unknown manifest entry {other:?}"),
+        }
+    }
+
+    cases
+}
+
+/// parses the `libbbf | version | commit` line of `cases.txt`
+fn libbbf_provenance() -> Option<(String, String)> {
+    let text = std::fs::read_to_string(fixtures_dir().join("cases.txt")).unwrap();
+    text.lines()
+        .map(|line| line.split('|').map(str::trim).collect::<Vec<_>>())
+        .find(|fields| fields[0] == "libbbf")
+        .and_then(|fields| optional(fields.get(1).copied()).zip(optional(fields.get(2).copied())))
+}
+
+/// reads the reference file of `case`, failing if it hasn't been produced
+fn reference(case: &Case) -> Vec<u8> {
+    let path = case.reference_path();
+    let data = std::fs::read(&path).unwrap_or_else(|e| {
+        panic!(
+            "{}: missing reference file {} ({e}); produce it with libbbf as described in cases.txt",
+            case.name,
+            path.display()
+        )
+    });
+
+    assert!(
+        libbbf_provenance().is_some(),
+        "cases.txt doesn't record the libbbf version and commit the reference files came from"
+    );
+    assert!(
+        case.command.is_some(),
+        "{}: cases.txt doesn't record the libbbf command the reference file was produced with",
+        case.name
+    );
+    data
+}
+
+/// builds a case with [`BbfBuilder`] and returns its bytes
+fn build(case: &Case) -> Vec<u8> {
+    let output = tempfile::NamedTempFile::new().unwrap();
+    let mut builder =
+        BbfBuilder::new(output.path(), case.alignment, case.ream_size, case.flags).unwrap();
+
+    for page in &case.pages {
+        builder.add_page(Case::input_path(page), 0, 0).unwrap();
+    }
+    for section in &case.sections {
+        builder.add_section(&section.title, section.start, section.parent.as_deref());
+    }
+    for meta in &case.metadata {
+        builder.add_metadata(&meta.key, &meta.value, meta.parent.as_deref());
+    }
+
+    builder.finalize().unwrap();
+    std::fs::read(output.path()).unwrap()
+}
+
+/// resolves an optional string offset
+fn parent_of<S: Storage>(reader: &BbfReader<S>, offset: u64) -> Option<String> {
+    (offset != u64::MAX).then(|| reader.get_string(offset).unwrap().to_string())
+}
+
+/// checks that a book reads back to exactly the inputs of `case`
+fn check_reads_as(case: &Case, data: Vec<u8>) {
+    let name = &case.name;
+    let reader = BbfReader::from_storage_hardened(data).unwrap();
+
+    let header = reader.header();
+    assert!({ header.alignment } == case.alignment, "{name}: alignment");
+    assert!({ header.ream_size } == case.ream_size, "{name}: ream size");
+    assert!({ header.flags } == case.flags, "{name}: header flags");
+
+    let assets = reader.assets().unwrap();
+    let pages = reader.pages().unwrap();
+    assert!(pages.len() == case.pages.len(), "{name}: page count");
+
+    for (i, (page, input)) in pages.iter().zip(&case.pages).enumerate() {
+        let asset = &assets[page.asset_index as usize];
+        let expected = std::fs::read(Case::input_path(input)).unwrap();
+        let extension = Path::new(input).extension().unwrap().to_str().unwrap();
+
+        assert!(
+            *reader.get_asset_data(asset).unwrap() == *expected,
+            "{name}: page {i} data"
+        );
+        assert!(
+            MediaType::from(asset.media_type) == MediaType::from_extension(extension),
+            "{name}: page {i} media type"
+        );
+    }
+
+    for (i, asset) in assets.iter().enumerate() {
+        let alignment = case.expected_alignment(asset.file_size);
+        assert!(
+            { asset.file_offset }.is_multiple_of(alignment),
+            "{name}: asset {i} not aligned to {alignment}"
+        );
+    }
+
+    let sections = reader.sections().unwrap();
+    assert!(
+        sections.len() == case.sections.len(),
+        "{name}: section count"
+    );
+    for (section, expected) in sections.iter().zip(&case.sections) {
+        assert!(reader.get_string(section.section_title_offset).unwrap() == expected.title);
+        assert!({ section.section_start_index } == expected.start);
+        assert!(parent_of(&reader, section.section_parent_offset) == expected.parent);
+    }
+
+    let metadata = reader.metadata().unwrap();
+    assert!(
+        metadata.len() == case.metadata.len(),
+        "{name}: metadata count"
+    );
+    for (meta, expected) in metadata.iter().zip(&case.metadata) {
+        assert!(reader.get_string(meta.key_offset).unwrap() == expected.key);
+        assert!(reader.get_string(meta.value_offset).unwrap() == expected.value);
+        assert!(parent_of(&reader, meta.parent_offset) == expected.parent);
+    }
+
+    assert!(reader.validate_deep().is_clean(), "{name}: validation");
+    assert!(
+        reader.integrity_report().unwrap().is_ok(),
+        "{name}: integrity"
+    );
+}
+
+#[test]
+fn test_builder_output_reads_back_as_inputs() {
+    let cases = cases();
+    assert!(!cases.is_empty());
+
+    for case in &cases {
+        check_reads_as(case, build(case));
+    }
+}
+
+#[test]
+fn test_builder_is_deterministic() {
+    for case in &cases() {
+        assert!(build(case) == build(case), "{}", case.name);
+    }
+}
+
+#[test]
+fn test_cases_cover_variable_ream_size() {
+    assert!(
+        cases()
+            .iter()
+            .any(|case| case.flags & BBF_VARIABLE_REAM_SIZE_FLAG != 0)
+    );
+}
+
+#[test]
+#[ignore = "the libbbf reference files listed in cases.txt aren't checked in yet"]
+fn test_reader_parses_reference_files() {
+    for case in &cases() {
+        check_reads_as(case, reference(case));
+    }
+}
+
+#[test]
+#[ignore = "the libbbf reference files listed in cases.txt aren't checked in yet"]
+fn test_builder_matches_reference_files() {
+    for case in &cases() {
+        let reference = reference(case);
+
+        let built = build(case);
+        let first_difference = built
+            .iter()
+            .zip(&reference)
+            .position(|(a, b)| a != b)
+            .unwrap_or(built.len().min(reference.len()));
+
+        assert!(
+            built == reference,
+            "{}: output differs from libbbf at byte {first_difference}",
+            case.name
+        );
+    }
+}
+
+/// deterministic filler bytes after a format signature
+fn input(signature: &[u8], size: usize, seed: u8) -> Vec<u8> {
+    let mut data = signature.to_vec();
+    data.extend((signature.len()..size).map(|i| (i as u8).wrapping_mul(31) ^ seed));
+    data
+}
+
+#[test]
+#[ignore = "rewrites the checked-in interop inputs"]
+fn regenerate_inputs() {
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+    const JPG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0];
+    const GIF: &[u8] = b"GIF89a";
+    const WEBP: &[u8] = b"RIFF\x10\x00\x00\x00WEBPVP8 ";
+
+    let p01 = input(PNG, 200, 1);
+    let inputs = [
+        ("p01.png", p01.clone()),
+        ("p02.jpg", input(JPG, 300, 2)),
+        ("p01_copy.png", p01),
+        ("small.png", input(PNG, 100, 3)),
+        ("large.jpg", input(JPG, 70_000, 4)),
+        ("medium.webp", input(WEBP, 3_000, 5)),
+        ("a.png", input(PNG, 512, 6)),
+        ("b.gif", input(GIF, 2_048, 7)),
+        ("c.png", input(PNG, 1_000, 8)),
+    ];
+
+    let dir = fixtures_dir().join("inputs");
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, data) in inputs {
+        std::fs::write(dir.join(name), data).unwrap();
+    }
+}
@@ -1,15 +1,53 @@
 //! boundbook - a Rust implementation of the Bound Book specification
 mod _types;
+mod book;
 mod builder;
+#[cfg(feature = "cbz")]
+mod cbz;
+mod codec;
+#[cfg(feature = "comicinfo")]
+mod comicinfo;
+#[cfg(test)]
+mod conformance;
+#[cfg(feature = "epub")]
+mod epub;
 mod error;
+mod expansion;
+mod integrity;
+#[cfg(test)]
+mod interop;
+mod metadata;
 mod reader;
+mod repair;
+mod sections;
+mod storage;
+mod thumbnail;
+mod validation;
+#[cfg(any(feature = "comicinfo", feature = "epub"))]
+mod xml;
 
 pub use {
+    book::{Book, MetadataEntry, MetadataScope, Page},
     builder::BbfBuilder,
     error::{BbfError, Result},
+    expansion::ExpansionRecord,
+    integrity::{AssetFault, CorruptedAsset, IntegrityReport},
+    metadata::MetadataMap,
     reader::BbfReader,
+    repair::{RecoveryMethod, RepairReport, repair},
+    sections::{SectionIssue, SectionTree, SectionView},
+    storage::{SeekStorage, Storage},
+    thumbnail::Thumbnail,
+    validation::{Severity, ValidationIssue, ValidationReport},
 };
 
+#[cfg(feature = "cbz")]
+pub use cbz::{to_cbz, to_cbz_split, write_cbz};
+#[cfg(feature = "comicinfo")]
+pub use comicinfo::{ComicInfo, ComicPage};
+#[cfg(feature = "epub")]
+pub use epub::{to_epub, write_epub};
+
 pub mod types {
     //! binary data structures used in BBF files
     //!
@@ -25,7 +63,12 @@ pub mod types {
     //! - [`Section`] - defines chapters or volumes with hierarchical structure
     //! - [`Metadata`] - key-value pairs for book information
     //! - [`MediaType`] - enum identifying image format (png, avif, webp, etc)
-    //! - [`Expansion`] - reserved for future format extensions
+    //! - [`Expansion`] - typed, versioned extension records (see [`crate::ExpansionRecord`])
+    //!
+    //! # encoding
+    //!
+    //! every structure is stored little-endian on disk regardless of the host. [`ToLeBytes`] and
+    //! [`FromLeBytes`] encode and decode them field by field.
     //!
     //! # usage
     //!
@@ -40,8 +83,11 @@ pub mod types {
     //! # Ok(())
     //! # }
     //! ```
-    pub use crate::_types::{
-        AssetEntry, BbfFooter, BbfHeader, Expansion, MediaType, Metadata, PageEntry, Section,
+    pub use crate::{
+        _types::{
+            AssetEntry, BbfFooter, BbfHeader, Expansion, MediaType, Metadata, PageEntry, Section,
+        },
+        codec::{FromLeBytes, ToLeBytes},
     };
 }
 
@@ -58,11 +104,22 @@ pub mod format {
     //! - [`DEFAULT_SMALL_REAM_THRESHOLD`] - default ream size exponent (16 = 64kb)
     //! - [`MAX_BALE_SIZE`] - maximum index region size (16mb)
     //! - [`MAX_FORME_SIZE`] - maximum string scan length (2kb)
+    //! - [`DEFAULT_THUMBNAIL_SIZE`] - default thumbnail bounding box (256px)
     //!
     //! # flags
     //!
     //! - [`BBF_PETRIFICATION_FLAG`] - marks file as read-only/immutable
     //! - [`BBF_VARIABLE_REAM_SIZE_FLAG`] - enables variable alignment for small assets
+    //! - [`BBF_ASSET_THUMBNAIL_FLAG`] - marks an asset as the cover thumbnail
+    //! - [`BBF_PAGE_FRONT_COVER_FLAG`], [`BBF_PAGE_BACK_COVER_FLAG`] - mark a page as a cover
+    //! - [`BBF_PAGE_DOUBLE_SPREAD_FLAG`] - marks a page as a double-page spread
+    //! - [`BBF_EXPANSION_REQUIRED_FLAG`] - marks an expansion record readers must understand
+    //!
+    //! # registries
+    //!
+    //! - [`REGISTERED_EXPANSION_KINDS`] - expansion record kinds this implementation understands
+    //!   (see [`crate::BbfReader::open_with_expansions`] for kinds defined elsewhere)
+    //! - [`THUMBNAIL_EXPANSION_KIND`] - kind of the [`crate::Thumbnail`] record
     //!
     //! # usage
     //!
@@ -81,8 +138,46 @@ pub mod format {
     //! # }
     //! ```
     pub use crate::_types::{
-        ALIGNMENT, BBF_PETRIFICATION_FLAG, BBF_VARIABLE_REAM_SIZE_FLAG, DEFAULT_GUARD_ALIGNMENT,
-        DEFAULT_SMALL_REAM_THRESHOLD, MAGIC, MAX_BALE_SIZE, MAX_FORME_SIZE, VERSION,
+        ALIGNMENT, BBF_ASSET_THUMBNAIL_FLAG, BBF_EXPANSION_REQUIRED_FLAG, BBF_PAGE_BACK_COVER_FLAG,
+        BBF_PAGE_DOUBLE_SPREAD_FLAG, BBF_PAGE_FRONT_COVER_FLAG, BBF_PETRIFICATION_FLAG,
+        BBF_VARIABLE_REAM_SIZE_FLAG, DEFAULT_GUARD_ALIGNMENT, DEFAULT_SMALL_REAM_THRESHOLD,
+        DEFAULT_THUMBNAIL_SIZE, MAGIC, MAX_BALE_SIZE, MAX_FORME_SIZE, REGISTERED_EXPANSION_KINDS,
+        THUMBNAIL_EXPANSION_KIND, VERSION,
+    };
+}
+
+pub mod keys {
+    //! well-known metadata keys
+    //!
+    //! metadata keys are free-form strings, but these have an agreed meaning that the builder,
+    //! `info`, and the reader understand. they're matched case-insensitively: the builder stores
+    //! keys as given, and lookups through [`crate::MetadataMap`] or
+    //! [`crate::BbfReader::get_metadata`] accept any casing.
+    //!
+    //! # keys
+    //!
+    //! - [`TITLE`], [`AUTHOR`], [`SERIES`], [`VOLUME`], [`LANGUAGE`], [`ISBN`],
+    //!   [`READING_DIRECTION`], [`PUBLISHER`], [`DATE`]
+    //! - [`WELL_KNOWN_KEYS`] - all of the above, in display order
+    //!
+    //! # usage
+    //!
+    //! ```no_run
+    //! use boundbook::{BbfBuilder, BbfReader, keys};
+    //!
+    //! # fn example() -> boundbook::Result<()> {
+    //! let mut builder = BbfBuilder::with_defaults("book.BBF")?;
+    //! builder.add_metadata(keys::TITLE, "Example", None);
+    //! builder.finalize()?;
+    //!
+    //! let reader = BbfReader::open("book.BBF")?;
+    //! assert_eq!(reader.get_metadata("title")?, Some("Example"));
+    //! # Ok(())
+    //! # }
+    //! ```
+    pub use crate::metadata::{
+        AUTHOR, DATE, ISBN, LANGUAGE, PUBLISHER, READING_DIRECTION, SERIES, TITLE, VOLUME,
+        WELL_KNOWN_KEYS, canonical_key, is_well_known,
     };
 }
 
@@ -96,7 +191,17 @@ pub mod prelude {
     //!
     //! - [`BbfBuilder`] - for creating BBF files
     //! - [`BbfReader`] - for reading BBF files
+    //! - [`Book`], [`Page`], [`MetadataEntry`], [`MetadataScope`] - resolved, high-level view of
+    //!   a BBF file
+    //! - [`SectionTree`], [`SectionView`], [`SectionIssue`] - sections linked into a tree with page
+    //!   ranges
+    //! - [`MetadataMap`] - metadata indexed by key
+    //! - [`Storage`], [`SeekStorage`] - byte sources a reader can read from
     //! - [`BbfError`] - error type for BBF operations
+    //! - [`ExpansionRecord`] - trait for typed expansion records
+    //! - [`Thumbnail`] - the cover thumbnail expansion record
+    //! - [`ValidationReport`], [`ValidationIssue`], [`Severity`] - structural validation results
+    //! - [`IntegrityReport`], [`CorruptedAsset`], [`AssetFault`] - per-asset hash check results
     //! - [`crate::types::MediaType`] - image format enum
     //!
     //! # usage
@@ -111,5 +216,10 @@ pub mod prelude {
     //!     Ok(())
     //! }
     //! ```
-    pub use crate::{BbfBuilder, BbfError, BbfReader, Result, format::*, types::*};
+    pub use crate::{
+        AssetFault, BbfBuilder, BbfError, BbfReader, Book, CorruptedAsset, ExpansionRecord,
+        IntegrityReport, MetadataEntry, MetadataMap, MetadataScope, Page, Result, SectionIssue,
+        SectionTree, SectionView, SeekStorage, Severity, Storage, Thumbnail, ValidationIssue,
+        ValidationReport, format::*, types::*,
+    };
 }
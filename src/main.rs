@@ -16,10 +16,12 @@ fn main() {
     .ok();
 
     if let Err(err) = cli::app() {
+        let code = cli::exit_code(&err);
         eprintln!("{:?}", miette::Report::from(err));
-        std::process::exit(1);
+        std::process::exit(code);
     }
 }
 
 #[cfg(not(feature = "cli"))]
+#[allow(clippy::missing_const_for_fn)]
 fn main() {}
@@ -0,0 +1,295 @@
+use {
+    crate::{BbfReader, Result, Storage},
+    hashbrown::HashMap,
+};
+
+/// the title of the book
+pub const TITLE: &str = "Title";
+/// the book's author or writer
+pub const AUTHOR: &str = "Author";
+/// the series the book belongs to
+pub const SERIES: &str = "Series";
+/// the book's volume number within its series
+pub const VOLUME: &str = "Volume";
+/// the language of the book's text, as an IETF language tag
+pub const LANGUAGE: &str = "Language";
+/// the book's ISBN
+pub const ISBN: &str = "ISBN";
+/// the direction pages are read in, `ltr` or `rtl`
+pub const READING_DIRECTION: &str = "ReadingDirection";
+/// the book's publisher
+pub const PUBLISHER: &str = "Publisher";
+/// the publication date, as an ISO 8601 date
+pub const DATE: &str = "Date";
+
+/// every well-known key, in display order
+pub const WELL_KNOWN_KEYS: &[&str] = &[
+    TITLE,
+    AUTHOR,
+    SERIES,
+    VOLUME,
+    LANGUAGE,
+    ISBN,
+    READING_DIRECTION,
+    PUBLISHER,
+    DATE,
+];
+
+/// maps a key to its canonical spelling
+///
+/// well-known keys are matched case-insensitively (so `title` and `TITLE` both become
+/// [`TITLE`]); any other key is returned unchanged.
+///
+/// # Arguments
+///
+/// * `key` - the key as written by a user or found in a file
+///
+/// # Returns
+///
+/// the canonical spelling of a well-known key, or `key` itself
+pub fn canonical_key(key: &str) -> &str {
+    WELL_KNOWN_KEYS
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(key))
+        .copied()
+        .unwrap_or(key)
+}
+
+/// checks whether a key is one of the well-known keys, ignoring case
+pub fn is_well_known(key: &str) -> bool {
+    WELL_KNOWN_KEYS
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(key))
+}
+
+/// checks whether two keys name the same entry once canonicalized
+pub(crate) fn keys_match(a: &str, b: &str) -> bool {
+    canonical_key(a) == canonical_key(b)
+}
+
+/// a borrowed metadata entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry<'a> {
+    /// the key, canonicalized
+    key: &'a str,
+    /// the value
+    value: &'a str,
+    /// the title of the section the entry belongs to, or `None` for the whole book
+    section: Option<&'a str>,
+}
+
+/// the metadata of a book indexed by key
+///
+/// built once with [`BbfReader::metadata_map`] or [`crate::Book::metadata_map`], then answers
+/// lookups without rescanning the table. keys are canonicalized with [`canonical_key`], so
+/// well-known keys match regardless of case. a key may appear several times (for example one
+/// `Author` entry per author); [`MetadataMap::get`] returns the first and
+/// [`MetadataMap::get_all`] every one, in table order.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataMap<'a> {
+    /// every entry in table order
+    entries: Vec<Entry<'a>>,
+    /// positions of the book-wide entries, by canonical key
+    book_index: HashMap<&'a str, Vec<usize>>,
+    /// positions of the section entries, by section title then canonical key
+    section_index: HashMap<&'a str, HashMap<&'a str, Vec<usize>>>,
+}
+
+impl<'a> MetadataMap<'a> {
+    /// indexes metadata given as `(key, value, section title)` in table order
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - the entries to index; `None` as section marks a book-wide entry
+    ///
+    /// # Returns
+    ///
+    /// the indexed map
+    pub fn new(entries: impl IntoIterator<Item = (&'a str, &'a str, Option<&'a str>)>) -> Self {
+        let mut map = Self::default();
+
+        for (key, value, section) in entries {
+            let key = canonical_key(key);
+            let index = match section {
+                Some(title) => map.section_index.entry(title).or_default(),
+                None => &mut map.book_index,
+            };
+            index.entry(key).or_default().push(map.entries.len());
+            map.entries.push(Entry {
+                key,
+                value,
+                section,
+            });
+        }
+
+        map
+    }
+
+    /// reads and indexes every metadata entry of a reader
+    pub(crate) fn from_reader<S: Storage>(reader: &'a BbfReader<S>) -> Result<Self> {
+        let entries = reader
+            .metadata()?
+            .iter()
+            .map(|meta| {
+                let section = match meta.parent_offset {
+                    u64::MAX => None,
+                    offset => Some(reader.get_string(offset)?),
+                };
+                Ok((
+                    reader.get_string(meta.key_offset)?,
+                    reader.get_string(meta.value_offset)?,
+                    section,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::new(entries))
+    }
+
+    /// positions of the entries with a key in a scope
+    fn positions(&self, section: Option<&str>, key: &str) -> &[usize] {
+        let index = match section {
+            Some(title) => self.section_index.get(title),
+            None => Some(&self.book_index),
+        };
+        index
+            .and_then(|index| index.get(canonical_key(key)))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// returns the number of entries
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// checks whether the book has no metadata
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// returns the first book-wide value for a key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key to look up; well-known keys match regardless of case
+    ///
+    /// # Returns
+    ///
+    /// the value, or `None` if the book has no book-wide entry with that key
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.get_all(key).next()
+    }
+
+    /// iterates over every book-wide value for a key in table order
+    pub fn get_all(&self, key: &str) -> impl Iterator<Item = &'a str> + '_ {
+        self.positions(None, key)
+            .iter()
+            .map(|&i| self.entries[i].value)
+    }
+
+    /// returns the first value for a key within a section
+    ///
+    /// # Arguments
+    ///
+    /// * `section` - the section title
+    /// * `key` - the key to look up
+    ///
+    /// # Returns
+    ///
+    /// the value, or `None` if the section has no entry with that key
+    pub fn get_in_section(&self, section: &str, key: &str) -> Option<&'a str> {
+        self.positions(Some(section), key)
+            .first()
+            .map(|&i| self.entries[i].value)
+    }
+
+    /// iterates over the `(key, value)` pairs that apply to the whole book, in table order
+    pub fn book(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.entries
+            .iter()
+            .filter(|entry| entry.section.is_none())
+            .map(|entry| (entry.key, entry.value))
+    }
+
+    /// iterates over the `(key, value)` pairs that belong to a section, in table order
+    pub fn section<'s>(&'s self, title: &'s str) -> impl Iterator<Item = (&'a str, &'a str)> + 's {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.section == Some(title))
+            .map(|entry| (entry.key, entry.value))
+    }
+
+    /// iterates over every entry as `(key, value, section title)` in table order
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str, Option<&'a str>)> + '_ {
+        self.entries
+            .iter()
+            .map(|entry| (entry.key, entry.value, entry.section))
+    }
+
+    /// iterates over the book-wide entries with well-known keys first, in
+    /// [`WELL_KNOWN_KEYS`] order, followed by every other book-wide entry in table order
+    pub fn book_display_order(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        let known = WELL_KNOWN_KEYS
+            .iter()
+            .flat_map(|key| self.get_all(key).map(move |value| (*key, value)));
+        let rest = self.book().filter(|(key, _)| !is_well_known(key));
+        known.chain(rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, assert2::check as assert};
+
+    fn map() -> MetadataMap<'static> {
+        MetadataMap::new([
+            ("Custom", "x", None),
+            ("author", "First", None),
+            ("Title", "Book", None),
+            ("Author", "Second", None),
+            ("Translator", "Someone", Some("Chapter 2")),
+            ("title", "Chapter Two", Some("Chapter 2")),
+        ])
+    }
+
+    #[test]
+    fn test_canonical_key_matches_well_known_keys_ignoring_case() {
+        assert!(canonical_key("readingdirection") == READING_DIRECTION);
+        assert!(canonical_key("ISBN") == ISBN);
+        assert!(canonical_key("isbn") == ISBN);
+        assert!(canonical_key("Colorist") == "Colorist");
+        assert!(is_well_known("DATE"));
+        assert!(!is_well_known("Colorist"));
+    }
+
+    #[test]
+    fn test_lookups_are_scoped() {
+        let map = map();
+
+        assert!(map.len() == 6);
+        assert!(map.get("TITLE") == Some("Book"));
+        assert!(map.get("Translator").is_none());
+        assert!(map.get_in_section("Chapter 2", TITLE) == Some("Chapter Two"));
+        assert!(map.get_in_section("Chapter 3", TITLE).is_none());
+        assert!(map.get_all(AUTHOR).collect::<Vec<_>>() == ["First", "Second"]);
+        assert!(
+            map.section("Chapter 2").collect::<Vec<_>>()
+                == [("Translator", "Someone"), ("Title", "Chapter Two")]
+        );
+    }
+
+    #[test]
+    fn test_display_order_puts_well_known_keys_first() {
+        let order: Vec<_> = map().book_display_order().collect();
+        assert!(
+            order
+                == [
+                    ("Title", "Book"),
+                    ("Author", "First"),
+                    ("Author", "Second"),
+                    ("Custom", "x"),
+                ]
+        );
+    }
+}
@@ -1,33 +1,74 @@
 use {
-    crate::prelude::*,
+    crate::{
+        prelude::*,
+        storage::{SeekStorage, Storage},
+    },
     miette::IntoDiagnostic,
     rayon::iter::{IntoParallelRefIterator, ParallelIterator},
     std::{
+        borrow::Cow,
         fs::File,
-        io::{self, ErrorKind},
+        io::{self, ErrorKind, Read, Seek},
         path::Path,
     },
 };
 
 /// a BBF file reader
 ///
-/// provides ro access to BBF files via mem-mapping for efficient random access to assets, pages,
-/// sections, and metadata. supports integrity verification via hash checking.
-#[derive(Debug)]
-pub struct BbfReader {
-    /// mem-mapped file contents
-    mmap: memmap2::Mmap,
+/// provides ro access to BBF files for random access to assets, pages, sections, and metadata.
+/// supports integrity verification via hash checking. generic over the [`Storage`] it reads from:
+/// the default mem-maps a file and hands out zero-copy slices, while storages that can't lend
+/// slices (see [`SeekStorage`]) have their index region buffered once at open time and asset
+/// data read into owned buffers on demand.
+pub struct BbfReader<S: Storage = memmap2::Mmap> {
+    /// the underlying byte source
+    storage: S,
+    /// copy of the index region (asset table through end of file), empty when the storage lends
+    /// slices itself
+    index: Vec<u8>,
     /// parsed file header
     header: BbfHeader,
     /// parsed file footer with all index info
     footer: BbfFooter,
+    /// index tables decoded into owned memory, populated by hardened opens and on big-endian
+    /// targets
+    tables: Option<OwnedTables>,
+}
+
+/// index tables decoded field by field from the index region
+#[derive(Debug)]
+struct OwnedTables {
+    /// decoded asset table
+    assets: Vec<AssetEntry>,
+    /// decoded page table
+    pages: Vec<PageEntry>,
+    /// decoded section table
+    sections: Vec<Section>,
+    /// decoded metadata table
+    metadata: Vec<Metadata>,
+    /// decoded expansion table
+    expansions: Vec<Expansion>,
+}
+
+impl<S: Storage> std::fmt::Debug for BbfReader<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BbfReader")
+            .field("len", &self.storage.len())
+            .field("zero_copy", &self.storage.as_slice().is_some())
+            .field("header", &self.header)
+            .field("footer", &self.footer)
+            .field("hardened", &self.tables.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl BbfReader {
     /// opens and validates a BBF file
     ///
-    /// mem-maps the file for efficient access, validates the magic num, checks reserved fields,
-    /// verifies footer offset is within bounds, and validates index region size constraints
+    /// mem-maps the file for efficient access, then parses it with [`BbfReader::from_storage`].
+    /// spec violations that don't prevent reading (like nonzero reserved fields) are tolerated
+    /// silently; use [`BbfReader::validate`] to inspect them or [`BbfReader::open_strict`] to
+    /// reject them.
     ///
     /// # Arguments
     ///
@@ -42,19 +83,41 @@ impl BbfReader {
     /// returns an error if:
     /// - file opening fails
     /// - mem mapping fails
-    /// - file is too small to contain header and footer
-    /// - magic number is not "BBF3"
-    /// - footer offset is out of bounds
-    /// - index region exceeds max_bale_size
-    /// - arithmetic operations overflow (see [`macroni_n_cheese::mathinator2000`])
+    /// - [`BbfReader::from_storage`] rejects the contents
     ///
     /// # Safety
     ///
-    /// uses unsafe for mem-mapping and reading structs from raw ptrs. mem-mapping is safe because
-    /// the file is opened ro and the mmap lifetime is tied to the reader. struct reads are safe
-    /// because bounds are checked and structs are #[repr(c, packed)].
-    #[macroni_n_cheese::mathinator2000]
+    /// uses unsafe for mem-mapping. the mapping is opened ro and its lifetime is tied to the
+    /// reader, but it's only sound while no other process truncates or rewrites the file:
+    /// touching a page past a truncated end raises SIGBUS. use [`BbfReader::open_hardened`] for
+    /// files you don't control.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with_expansions(path, &[])
+    }
+
+    /// opens and validates a BBF file that may use expansion kinds defined outside this crate
+    ///
+    /// behaves like [`BbfReader::open`], but required expansion records of the kinds in
+    /// `known_kinds` are accepted as well as the built-in
+    /// [`crate::format::REGISTERED_EXPANSION_KINDS`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path to the BBF file to open
+    /// * `known_kinds` - the [`crate::ExpansionRecord::KIND`]s the caller understands
+    ///
+    /// # Returns
+    ///
+    /// a `BbfReader` instance ready for querying assets, pages, sections, and metadata
+    ///
+    /// # Errors
+    ///
+    /// see [`BbfReader::open`]
+    ///
+    /// # Safety
+    ///
+    /// see [`BbfReader::open`]
+    pub fn open_with_expansions<P: AsRef<Path>>(path: P, known_kinds: &[u64]) -> Result<Self> {
         let file = File::open(path)
             .into_diagnostic()
             .map_err(|e| io::Error::new(ErrorKind::NotFound, e))?;
@@ -66,102 +129,469 @@ impl BbfReader {
                 })?
         };
 
-        if mmap.len() < std::mem::size_of::<BbfHeader>() + std::mem::size_of::<BbfFooter>() {
-            return Err(BbfError::FileTooSmall);
-        }
+        Self::from_storage_with_expansions(mmap, known_kinds)
+    }
 
-        let header: BbfHeader = unsafe {
-            Self::read_struct(&mmap, 0)
-                .into_diagnostic()
-                .map_err(BbfError::from)?
-        };
+    /// opens a BBF file, rejecting files with nonzero reserved fields
+    ///
+    /// behaves like [`BbfReader::open`], then runs [`BbfReader::validate`] and fails on the first
+    /// reserved-field finding.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path to the BBF file to open
+    ///
+    /// # Returns
+    ///
+    /// a `BbfReader` instance ready for querying assets, pages, sections, and metadata
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - [`BbfReader::open`] fails
+    /// - any reserved field, padding, or footer flag is nonzero
+    pub fn open_strict<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let reader = Self::open(path)?;
+
+        let violation = reader
+            .validate()
+            .issues()
+            .iter()
+            .find_map(|issue| match issue {
+                ValidationIssue::ReservedFieldNonZero { location } => Some(location.clone()),
+                _ => None,
+            });
 
-        if &header.magic != MAGIC {
-            return Err(BbfError::InvalidMagic);
+        match violation {
+            Some(location) => Err(BbfError::ReservedFieldNonZero {
+                description: format!("nonzero {location}"),
+            }),
+            None => Ok(reader),
         }
+    }
+}
 
-        if header.reserved_extra != 0 {
-            eprintln!("Warning: BBF header has nonzero reserved_extra field");
-        }
+impl BbfReader<SeekStorage<File>> {
+    /// opens an untrusted BBF file without mem-mapping it
+    ///
+    /// reads through positioned file reads instead of a mapping, so a file truncated by another
+    /// process surfaces as an i/o error rather than SIGBUS, and decodes every index table
+    /// field by field into owned memory (see [`BbfReader::from_storage_hardened`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path to the BBF file to open
+    ///
+    /// # Returns
+    ///
+    /// a hardened `BbfReader` instance ready for querying assets, pages, sections, and metadata
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - file opening fails
+    /// - [`BbfReader::from_storage_hardened`] rejects the contents
+    pub fn open_hardened<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)
+            .into_diagnostic()
+            .map_err(|e| io::Error::new(ErrorKind::NotFound, e))?;
 
-        if header.reserved != [0; 40] {
-            eprintln!("Warning: BBF header has nonzero reserved fields");
-        }
+        Self::from_storage_hardened(SeekStorage::new(file)?)
+    }
+}
 
-        if header.footer_offset as usize + std::mem::size_of::<BbfFooter>() > mmap.len() {
-            return Err(BbfError::InvalidOffset {
-                description: "Footer offset out of bounds".to_string(),
-            });
-        }
+impl BbfReader<Vec<u8>> {
+    /// parses a BBF file held in memory
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - the complete file contents
+    ///
+    /// # Returns
+    ///
+    /// a `BbfReader` that borrows everything from `data`
+    ///
+    /// # Errors
+    ///
+    /// returns an error if [`BbfReader::from_storage`] rejects the contents
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        Self::from_storage(data)
+    }
+}
+
+impl<R: Read + Seek + Send> BbfReader<SeekStorage<R>> {
+    /// parses a BBF file from a seekable reader
+    ///
+    /// only the header, footer, and index region are read up front; asset data is read on
+    /// demand. useful for files on filesystems that don't support mmap or for network-backed
+    /// readers.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - the reader to parse from
+    ///
+    /// # Returns
+    ///
+    /// a `BbfReader` that reads asset data through `reader`
+    ///
+    /// # Errors
+    ///
+    /// returns an error if seeking fails or [`BbfReader::from_storage`] rejects the contents
+    pub fn from_reader(reader: R) -> Result<Self> {
+        Self::from_storage(SeekStorage::new(reader)?)
+    }
+}
+
+impl<S: Storage> BbfReader<S> {
+    /// parses and validates a BBF file from any storage
+    ///
+    /// validates the magic num, verifies footer offset is within bounds, and validates index
+    /// region size constraints. storages without [`Storage::as_slice`] have their index region
+    /// copied into memory here, so every table and string lookup afterwards is served without
+    /// further reads.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - the byte source holding the file
+    ///
+    /// # Returns
+    ///
+    /// a `BbfReader` instance ready for querying assets, pages, sections, and metadata
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - reading from the storage fails
+    /// - storage is too small to contain header and footer
+    /// - magic number is not "BBF3"
+    /// - footer offset or asset table offset is out of bounds
+    /// - index region exceeds max_bale_size
+    /// - expansion table is out of bounds
+    /// - a required expansion record has an unregistered kind
+    /// - arithmetic operations overflow (see [`macroni_n_cheese::mathinator2000`])
+    pub fn from_storage(storage: S) -> Result<Self> {
+        Self::parse(storage, false, &[])
+    }
+
+    /// parses and validates a BBF file that may use expansion kinds defined outside this crate
+    ///
+    /// behaves like [`BbfReader::from_storage`], but required expansion records of the kinds in
+    /// `known_kinds` are accepted as well as the built-in
+    /// [`crate::format::REGISTERED_EXPANSION_KINDS`].
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - the byte source holding the file
+    /// * `known_kinds` - the [`crate::ExpansionRecord::KIND`]s the caller understands
+    ///
+    /// # Returns
+    ///
+    /// a `BbfReader` instance ready for querying assets, pages, sections, and metadata
+    ///
+    /// # Errors
+    ///
+    /// see [`BbfReader::from_storage`]
+    pub fn from_storage_with_expansions(storage: S, known_kinds: &[u64]) -> Result<Self> {
+        Self::parse(storage, false, known_kinds)
+    }
 
-        let footer: BbfFooter =
-            unsafe { Self::read_struct(&mmap, header.footer_offset as usize).into_diagnostic()? };
+    /// parses and validates a BBF file from any storage, decoding every index table up front
+    ///
+    /// behaves like [`BbfReader::from_storage`], but instead of reinterpreting the index region as
+    /// packed structs, each table is decoded field by field from little-endian bytes into owned
+    /// memory. every table is bounds-checked here, so table accessors can't fail afterwards.
+    /// meant for untrusted input; pair it with a storage that doesn't mem-map the file (like
+    /// [`SeekStorage`]) to also rule out SIGBUS on truncation.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - the byte source holding the file
+    ///
+    /// # Returns
+    ///
+    /// a hardened `BbfReader` instance ready for querying assets, pages, sections, and metadata
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - [`BbfReader::from_storage`] would fail
+    /// - any index table extends beyond the index region
+    pub fn from_storage_hardened(storage: S) -> Result<Self> {
+        Self::parse(storage, true, &[])
+    }
 
-        if footer.flags != 0 {
-            eprintln!("Warning: BBF footer has nonzero flags");
+    /// checks whether the index tables were decoded into owned memory at open time
+    ///
+    /// # Returns
+    ///
+    /// true for readers made by [`BbfReader::open_hardened`] or
+    /// [`BbfReader::from_storage_hardened`], and for every reader on big-endian targets
+    pub const fn is_hardened(&self) -> bool {
+        self.tables.is_some()
+    }
+
+    /// parses the header, footer, and index region shared by every constructor
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - the byte source holding the file
+    /// * `hardened` - whether to decode every index table into owned memory
+    /// * `known_kinds` - expansion kinds accepted on top of the registered ones
+    ///
+    /// # Returns
+    ///
+    /// the parsed reader
+    ///
+    /// # Errors
+    ///
+    /// see [`BbfReader::from_storage`] and [`BbfReader::from_storage_hardened`]
+    #[macroni_n_cheese::mathinator2000]
+    fn parse(storage: S, hardened: bool, known_kinds: &[u64]) -> Result<Self> {
+        let len = storage.len();
+        let footer_size = std::mem::size_of::<BbfFooter>() as u64;
+
+        if len < std::mem::size_of::<BbfHeader>() as u64 + footer_size {
+            return Err(BbfError::FileTooSmall);
         }
 
-        if footer.padding != [0; 3] {
-            eprintln!("Warning: BBF footer has nonzero padding");
+        let header: BbfHeader = Self::read_struct(&storage, 0)?;
+
+        if &header.magic != MAGIC {
+            return Err(BbfError::InvalidMagic);
         }
 
-        if footer.reserved != [0; 144] {
-            eprintln!("Warning: BBF footer has nonzero reserved fields");
+        if header
+            .footer_offset
+            .checked_add(footer_size)
+            .is_none_or(|end| end > len)
+        {
+            return Err(BbfError::InvalidOffset {
+                description: "Footer offset out of bounds".to_string(),
+            });
         }
 
-        let index_size = mmap.len() as u64 - footer.asset_offset;
+        let footer: BbfFooter = Self::read_struct(&storage, header.footer_offset)?;
+
+        let index_size =
+            len.checked_sub(footer.asset_offset)
+                .ok_or_else(|| BbfError::InvalidOffset {
+                    description: "Asset table offset out of bounds".to_string(),
+                })?;
         if index_size > MAX_BALE_SIZE {
             return Err(BbfError::Other {
                 message: format!("Index region too large: {} bytes", index_size),
             });
         }
 
-        Ok(Self {
-            mmap,
+        let index = match storage.as_slice() {
+            Some(_) => Vec::new(),
+            None => storage
+                .read_at(footer.asset_offset, index_size as usize)?
+                .into_owned(),
+        };
+
+        let mut reader = Self {
+            storage,
+            index,
             header,
             footer,
-        })
+            tables: None,
+        };
+
+        // the zero-copy tables reinterpret on-disk (little-endian) bytes in place, which is only
+        // correct on little-endian targets
+        if hardened || cfg!(target_endian = "big") {
+            reader.tables = Some(OwnedTables {
+                assets: reader.decode_table(footer.asset_offset, footer.asset_count, "Asset")?,
+                pages: reader.decode_table(footer.page_offset, footer.page_count, "Page")?,
+                sections: reader.decode_table(
+                    footer.section_offset,
+                    footer.section_count,
+                    "Section",
+                )?,
+                metadata: reader.decode_table(footer.meta_offset, footer.meta_count, "Metadata")?,
+                expansions: reader.decode_table(
+                    footer.expansion_offset,
+                    footer.expansion_count,
+                    "Expansion",
+                )?,
+            });
+        }
+
+        if let Some(kind) =
+            crate::expansion::first_unsupported_required(reader.expansions()?, known_kinds)
+        {
+            return Err(BbfError::UnsupportedExpansion { kind });
+        }
+
+        Ok(reader)
+    }
+
+    /// checks the file's structure for spec violations and inconsistencies
+    ///
+    /// looks for nonzero reserved fields, tables or assets outside the file, overlapping assets,
+    /// pages referencing missing assets, sections starting past the last page, dangling string
+    /// offsets, and unsorted sections. asset data isn't hashed; see
+    /// [`BbfReader::verify_integrity`] for that.
+    ///
+    /// # Returns
+    ///
+    /// a report of every finding with its severity
+    pub fn validate(&self) -> ValidationReport {
+        crate::validation::validate(self)
     }
 
-    /// reads a struct from the mem-mapped file at the given offset
+    /// runs [`BbfReader::validate`] plus cross-reference and layout checks
     ///
-    /// validates that the offset and struct size are within file bounds, then reads the struct
-    /// using unaligned ptr access (required for packed structs).
+    /// additionally looks for parent offsets that don't name a section, assets overlapping the
+    /// header or index region, tables outside the index region or overlapping each other,
+    /// unknown media types, assets no page uses, and data trailing the footer.
+    ///
+    /// # Returns
+    ///
+    /// a report of every finding with its severity
+    pub fn validate_deep(&self) -> ValidationReport {
+        crate::validation::validate_deep(self)
+    }
+
+    /// reads a struct from the storage at the given offset
+    ///
+    /// reads exactly the struct's encoded size, then decodes it field by field from
+    /// little-endian bytes.
     ///
     /// # Arguments
     ///
-    /// * `mmap` - the mem-mapped file
+    /// * `storage` - the byte source
     /// * `offset` - byte offset where the struct begins
     ///
     /// # Returns
     ///
-    /// a copy of the struct read from the file
+    /// the decoded struct
+    ///
+    /// # Errors
+    ///
+    /// returns an error if offset plus struct size exceeds the storage length or the read fails
+    fn read_struct<T: FromLeBytes>(storage: &S, offset: u64) -> Result<T> {
+        let bytes = storage.read_at(offset, T::SIZE)?;
+        T::from_le_bytes(&bytes).ok_or_else(|| BbfError::InvalidOffset {
+            description: format!("Struct read at offset {} exceeds file size", offset),
+        })
+    }
+
+    /// borrows the byte range `[start, end)` of the index region
+    ///
+    /// served straight from the storage when it lends slices, otherwise from the copy of the
+    /// index region taken at open time.
+    ///
+    /// # Returns
+    ///
+    /// the bytes, or `None` if the range is inverted or lies outside the available data
+    fn index_range(&self, start: usize, end: usize) -> Option<&[u8]> {
+        match self.storage.as_slice() {
+            Some(data) => data.get(start..end),
+            None => {
+                let base = self.footer.asset_offset as usize;
+                self.index
+                    .get(start.checked_sub(base)?..end.checked_sub(base)?)
+            }
+        }
+    }
+
+    /// borrows the bytes of a table from the index region
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - byte offset where the table begins
+    /// * `count` - number of entries in the table
+    /// * `name` - the table's name, used in error descriptions
+    ///
+    /// # Returns
+    ///
+    /// exactly `count` entries' worth of bytes
     ///
     /// # Errors
     ///
     /// returns an error if:
-    /// - offset plus struct size exceeds file length
-    /// - arithmetic operations overflow (see [`macroni_n_cheese::mathinator2000`])
+    /// - table size calculation overflows
+    /// - table extends beyond the index region
+    fn table_bytes<T>(&self, offset: u64, count: u64, name: &str) -> Result<&[u8]> {
+        let offset = offset as usize;
+
+        let size = (count as usize)
+            .checked_mul(std::mem::size_of::<T>())
+            .ok_or_else(|| BbfError::InvalidOffset {
+                description: format!("{name} table size calculation overflow"),
+            })?;
+
+        let end = offset
+            .checked_add(size)
+            .ok_or_else(|| BbfError::InvalidOffset {
+                description: format!("{name} table offset + size overflow"),
+            })?;
+
+        if size == 0 {
+            return Ok(&[]);
+        }
+
+        self.index_range(offset, end)
+            .ok_or_else(|| BbfError::InvalidOffset {
+                description: format!("{name} table out of bounds"),
+            })
+    }
+
+    /// reinterprets a range of the index region as a table of packed structs
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - byte offset where the table begins
+    /// * `count` - number of entries in the table
+    /// * `name` - the table's name, used in error descriptions
+    ///
+    /// # Returns
+    ///
+    /// a slice of `count` entries borrowed from the index region
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the table bounds are invalid (see [`BbfReader::table_bytes`])
     ///
     /// # Safety
     ///
-    /// uses unsafe to read from a raw ptr. this is safe because bounds are validated before
-    /// reading and unaligned reads handle packed struct layouts correctly.
-    #[macroni_n_cheese::mathinator2000]
-    unsafe fn read_struct<T: Copy>(mmap: &memmap2::Mmap, offset: usize) -> Result<T> {
-        if offset + std::mem::size_of::<T>() > mmap.len() {
-            return Err(BbfError::InvalidOffset {
-                description: format!("Struct read at offset {} exceeds file size", offset),
-            });
-        }
+    /// uses unsafe to reinterpret bytes as structs. this is sound because every table entry is
+    /// `#[repr(C, packed)]` (alignment 1) and made only of integers, so any byte pattern is a
+    /// valid value, and the byte range was bounds-checked to hold exactly `count` entries.
+    fn table<T: Copy>(&self, offset: u64, count: u64, name: &str) -> Result<&[T]> {
+        let bytes = self.table_bytes::<T>(offset, count, name)?;
 
         unsafe {
-            Ok(std::ptr::read_unaligned(
-                mmap.as_ptr().add(offset) as *const T
+            Ok(std::slice::from_raw_parts(
+                bytes.as_ptr() as *const T,
+                count as usize,
             ))
         }
     }
 
+    /// decodes a table from the index region into owned memory
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - byte offset where the table begins
+    /// * `count` - number of entries in the table
+    /// * `name` - the table's name, used in error descriptions
+    ///
+    /// # Returns
+    ///
+    /// every entry, decoded field by field
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the table bounds are invalid (see [`BbfReader::table_bytes`])
+    fn decode_table<T: FromLeBytes>(&self, offset: u64, count: u64, name: &str) -> Result<Vec<T>> {
+        Ok(crate::codec::decode_table(
+            self.table_bytes::<T>(offset, count, name)?,
+        ))
+    }
+
     /// gets a string from the string pool at the given offset
     ///
     /// reads a null-terminated utf-8 string from the string pool. scans up to max_forme_size bytes
@@ -173,17 +603,16 @@ impl BbfReader {
     ///
     /// # Returns
     ///
-    /// a string slice borrowed from the mem-mapped file, valid for the reader's lifetime
+    /// a string slice borrowed from the index region, valid for the reader's lifetime
     ///
     /// # Errors
     ///
     /// returns an error if:
     /// - offset is beyond the string pool size
     /// - string pool offset calculations overflow
+    /// - string pool extends beyond the index region
     /// - no null terminator found within max_forme_size bytes
     /// - string contains invalid utf-8
-    /// - arithmetic operations overflow (see [`macroni_n_cheese::mathinator2000`])
-    #[macroni_n_cheese::mathinator2000]
     pub fn get_string(&self, offset: u64) -> Result<&str> {
         let pool_start = self.footer.string_pool_offset as usize;
         let pool_size = self.footer.string_pool_size as usize;
@@ -194,35 +623,33 @@ impl BbfReader {
             });
         }
 
-        let start = pool_start + offset as usize;
-        let pool_end = pool_start
-            .checked_add(pool_size)
-            .ok_or_else(|| BbfError::InvalidOffset {
-                description: "String pool offset + size overflow".into(),
-            })
-            .into_diagnostic()?;
+        let pool_end =
+            pool_start
+                .checked_add(pool_size)
+                .ok_or_else(|| BbfError::InvalidOffset {
+                    description: "String pool offset + size overflow".into(),
+                })?;
 
-        if start >= self.mmap.len() || pool_end > self.mmap.len() {
-            return Err(BbfError::InvalidOffset {
-                description: "String pool out of bounds".to_string(),
-            });
-        }
+        let pool =
+            self.index_range(pool_start, pool_end)
+                .ok_or_else(|| BbfError::InvalidOffset {
+                    description: "String pool out of bounds".to_string(),
+                })?;
 
-        let data = &self.mmap[start..pool_end];
+        let data = &pool[offset as usize..];
         let scan_limit = MAX_FORME_SIZE.min(data.len() as u64) as usize;
         let str_end = data[..scan_limit]
             .iter()
             .position(|&b| b == 0)
-            .ok_or_else(|| BbfError::InvalidUtf8)
-            .into_diagnostic()?;
+            .ok_or(BbfError::InvalidUtf8)?;
 
         std::str::from_utf8(&data[..str_end]).map_err(|_| BbfError::InvalidUtf8)
     }
 
     /// returns a slice of all asset entries
     ///
-    /// provides direct access to the asset table via the mem-mapped file. validates that the
-    /// asset table is within file bounds.
+    /// provides direct access to the asset table. validates that the asset table is
+    /// within the index region.
     ///
     /// # Returns
     ///
@@ -232,41 +659,18 @@ impl BbfReader {
     ///
     /// returns an error if:
     /// - asset table size calculation overflows
-    /// - asset table extends beyond file bounds
+    /// - asset table extends beyond the index region
     pub fn assets(&self) -> Result<&[AssetEntry]> {
-        let offset = self.footer.asset_offset as usize;
-        let count = self.footer.asset_count as usize;
-        let size = count
-            .checked_mul(std::mem::size_of::<AssetEntry>())
-            .ok_or_else(|| BbfError::InvalidOffset {
-                description: "Asset table size calculation overflow".into(),
-            })?;
-
-        let end = offset
-            .checked_add(size)
-            .ok_or_else(|| BbfError::InvalidOffset {
-                description: "Asset table offset + size overflow".into(),
-            })
-            .into_diagnostic()?;
-
-        if end > self.mmap.len() {
-            return Err(BbfError::InvalidOffset {
-                description: "Asset table out of bounds".into(),
-            });
-        }
-
-        unsafe {
-            Ok(std::slice::from_raw_parts(
-                self.mmap.as_ptr().add(offset) as *const AssetEntry,
-                count,
-            ))
+        match &self.tables {
+            Some(tables) => Ok(&tables.assets),
+            None => self.table(self.footer.asset_offset, self.footer.asset_count, "Asset"),
         }
     }
 
     /// returns a slice of all page entries
     ///
-    /// provides direct access to the page table via the mem-mapped file. validates that the
-    /// page table is within file bounds.
+    /// provides direct access to the page table. validates that the page table is
+    /// within the index region.
     ///
     /// # Returns
     ///
@@ -276,134 +680,169 @@ impl BbfReader {
     ///
     /// returns an error if:
     /// - page table size calculation overflows
-    /// - page table extends beyond file bounds
+    /// - page table extends beyond the index region
     pub fn pages(&self) -> Result<&[PageEntry]> {
-        let offset = self.footer.page_offset as usize;
-        let count = self.footer.page_count as usize;
-
-        let size = count
-            .checked_mul(std::mem::size_of::<PageEntry>())
-            .ok_or_else(|| BbfError::InvalidOffset {
-                description: "Page table size calculation overflow".into(),
-            })
-            .into_diagnostic()?;
+        match &self.tables {
+            Some(tables) => Ok(&tables.pages),
+            None => self.table(self.footer.page_offset, self.footer.page_count, "Page"),
+        }
+    }
 
-        let end = offset
-            .checked_add(size)
-            .ok_or_else(|| BbfError::InvalidOffset {
-                description: "Page table offset + size overflow".into(),
-            })
-            .into_diagnostic()?;
+    /// returns a slice of all section entries
+    ///
+    /// provides direct access to the section table. validates that the section table is
+    /// within the index region.
+    ///
+    /// # Returns
+    ///
+    /// a slice of all section entries in the file
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - section table size calculation overflows
+    /// - section table extends beyond the index region
+    pub fn sections(&self) -> Result<&[Section]> {
+        match &self.tables {
+            Some(tables) => Ok(&tables.sections),
+            None => self.table(
+                self.footer.section_offset,
+                self.footer.section_count,
+                "Section",
+            ),
+        }
+    }
 
-        if end > self.mmap.len() {
-            return Err(BbfError::InvalidOffset {
-                description: "Page table out of bounds".into(),
-            });
+    /// returns a slice of all metadata entries
+    ///
+    /// provides direct access to the metadata table. validates that the metadata table is
+    /// within the index region.
+    ///
+    /// # Returns
+    ///
+    /// a slice of all metadata entries in the file
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - metadata table size calculation overflows
+    /// - metadata table extends beyond the index region
+    pub fn metadata(&self) -> Result<&[Metadata]> {
+        match &self.tables {
+            Some(tables) => Ok(&tables.metadata),
+            None => self.table(self.footer.meta_offset, self.footer.meta_count, "Metadata"),
         }
+    }
 
-        unsafe {
-            Ok(std::slice::from_raw_parts(
-                self.mmap.as_ptr().add(offset) as *const PageEntry,
-                count,
-            ))
+    /// returns a slice of all expansion entries
+    ///
+    /// provides direct access to the expansion table, including entries
+    /// of kinds this implementation doesn't understand. validates that the expansion table is
+    /// within the index region.
+    ///
+    /// # Returns
+    ///
+    /// a slice of all expansion entries in the file
+    ///
+    /// # Errors
+    ///
+    /// returns an error if:
+    /// - expansion table size calculation overflows
+    /// - expansion table extends beyond the index region
+    pub fn expansions(&self) -> Result<&[Expansion]> {
+        match &self.tables {
+            Some(tables) => Ok(&tables.expansions),
+            None => self.table(
+                self.footer.expansion_offset,
+                self.footer.expansion_count,
+                "Expansion",
+            ),
         }
     }
 
-    /// returns a slice of all section entries
+    /// decodes all expansion records of one kind
+    ///
+    /// entries of other kinds, and entries of this kind whose version the record type can't
+    /// decode, are skipped.
+    ///
+    /// # Returns
+    ///
+    /// the decoded records, in table order
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the expansion table is out of bounds
+    pub fn expansion_records<E: ExpansionRecord>(&self) -> Result<Vec<E>> {
+        Ok(self
+            .expansions()?
+            .iter()
+            .filter_map(E::from_expansion)
+            .collect())
+    }
+
+    /// locates the book's thumbnail asset
     ///
-    /// provides direct access to the section table via the mem-mapped file. validates that the
-    /// section table is within file bounds.
+    /// uses the [`Thumbnail`] expansion record when present, falling back to the first asset
+    /// flagged with [`BBF_ASSET_THUMBNAIL_FLAG`].
     ///
     /// # Returns
     ///
-    /// a slice of all section entries in the file
+    /// the thumbnail record, or `None` if the book has no thumbnail. records recovered from the
+    /// asset flag alone have unknown (zero) dimensions.
     ///
     /// # Errors
     ///
-    /// returns an error if:
-    /// - section table size calculation overflows
-    /// - section table extends beyond file bounds
-    pub fn sections(&self) -> Result<&[Section]> {
-        let offset = self.footer.section_offset as usize;
-        let count = self.footer.section_count as usize;
-
-        let size = count
-            .checked_mul(std::mem::size_of::<Section>())
-            .ok_or_else(|| BbfError::InvalidOffset {
-                description: "Section table size calculation overflow".into(),
-            })
-            .into_diagnostic()?;
-
-        let end = offset
-            .checked_add(size)
-            .ok_or_else(|| BbfError::InvalidOffset {
-                description: "Section table offset + size overflow".into(),
-            })?;
-
-        if end > self.mmap.len() {
-            return Err(BbfError::InvalidOffset {
-                description: "Section table out of bounds".into(),
-            });
+    /// returns an error if the expansion or asset tables are out of bounds
+    pub fn thumbnail_record(&self) -> Result<Option<Thumbnail>> {
+        if let Some(record) = self.expansion_records::<Thumbnail>()?.into_iter().next() {
+            return Ok(Some(record));
         }
 
-        unsafe {
-            Ok(std::slice::from_raw_parts(
-                self.mmap.as_ptr().add(offset) as *const Section,
-                count,
-            ))
-        }
+        Ok(self
+            .assets()?
+            .iter()
+            .position(|asset| asset.flags & BBF_ASSET_THUMBNAIL_FLAG != 0)
+            .map(|index| Thumbnail {
+                asset_index: index as u64,
+                width: 0,
+                height: 0,
+            }))
     }
 
-    /// returns a slice of all metadata entries
-    ///
-    /// provides direct access to the metadata table via the mem-mapped file. validates that the
-    /// metadata table is within file bounds.
+    /// retrieves the book's thumbnail image
     ///
     /// # Returns
     ///
-    /// a slice of all metadata entries in the file
+    /// the thumbnail's media type and raw image data, or `None` if the book has no thumbnail
     ///
     /// # Errors
     ///
-    /// returns an error if:
-    /// - metadata table size calculation overflows
-    /// - metadata table extends beyond file bounds
-    pub fn metadata(&self) -> Result<&[Metadata]> {
-        let offset = self.footer.meta_offset as usize;
-        let count = self.footer.meta_count as usize;
-
-        let size = count
-            .checked_mul(std::mem::size_of::<Metadata>())
-            .ok_or_else(|| BbfError::InvalidOffset {
-                description: "Metadata table size calculation overflow".into(),
-            })
-            .into_diagnostic()?;
+    /// returns an error if the thumbnail record points outside the asset table or the asset
+    /// data is out of bounds
+    pub fn thumbnail(&self) -> Result<Option<(MediaType, Cow<'_, [u8]>)>> {
+        let Some(record) = self.thumbnail_record()? else {
+            return Ok(None);
+        };
 
-        let end = offset
-            .checked_add(size)
+        let assets = self.assets()?;
+        let asset = usize::try_from(record.asset_index)
+            .ok()
+            .and_then(|index| assets.get(index))
             .ok_or_else(|| BbfError::InvalidOffset {
-                description: "Metadata table offset + size overflow".into(),
-            })
-            .into_diagnostic()?;
-
-        if end > self.mmap.len() {
-            return Err(BbfError::InvalidOffset {
-                description: "Metadata table out of bounds".into(),
-            });
-        }
+                description: "Thumbnail asset index out of bounds".into(),
+            })?;
 
-        unsafe {
-            Ok(std::slice::from_raw_parts(
-                self.mmap.as_ptr().add(offset) as *const Metadata,
-                count,
-            ))
-        }
+        Ok(Some((
+            MediaType::from(asset.media_type),
+            self.get_asset_data(asset)?,
+        )))
     }
 
     /// retrieves the raw binary data for an asset
     ///
-    /// returns a slice of the mem-mapped file containing the asset's image data. validates that
-    /// the asset's file offset and size are within bounds.
+    /// borrows the data straight from storages that lend slices (like the default mmap) and reads
+    /// it into an owned buffer otherwise. validates that the asset's file offset and size are
+    /// within bounds.
     ///
     /// # Arguments
     ///
@@ -411,30 +850,45 @@ impl BbfReader {
     ///
     /// # Returns
     ///
-    /// a byte slice containing the asset's raw image data
+    /// the asset's raw image data
     ///
     /// # Errors
     ///
     /// returns an error if:
     /// - asset offset plus size overflows
     /// - asset data extends beyond file bounds
-    pub fn get_asset_data(&self, asset: &AssetEntry) -> Result<&[u8]> {
-        let start = asset.file_offset as usize;
+    /// - reading from the storage fails
+    pub fn get_asset_data(&self, asset: &AssetEntry) -> Result<Cow<'_, [u8]>> {
+        let start = asset.file_offset;
+        let size = asset.file_size;
 
         let end = start
-            .checked_add(asset.file_size as usize)
+            .checked_add(size)
             .ok_or_else(|| BbfError::InvalidOffset {
                 description: "Asset offset + size overflow".into(),
-            })
-            .into_diagnostic()?;
+            })?;
 
-        if end > self.mmap.len() {
+        if end > self.storage.len() {
             return Err(BbfError::InvalidOffset {
                 description: "Asset data out of bounds".into(),
             });
         }
 
-        Ok(&self.mmap[start..end])
+        Ok(self.storage.read_at(start, size as usize)?)
+    }
+
+    /// returns the size of the underlying file in bytes
+    pub(crate) fn file_len(&self) -> u64 {
+        self.storage.len()
+    }
+
+    /// returns the storage the reader reads from
+    ///
+    /// # Returns
+    ///
+    /// a reference to the underlying byte source
+    pub const fn storage(&self) -> &S {
+        &self.storage
     }
 
     /// returns the BBF format version number
@@ -464,6 +918,18 @@ impl BbfReader {
         self.footer.asset_count
     }
 
+    /// checks whether the file is petrified
+    ///
+    /// petrified files have [`BBF_PETRIFICATION_FLAG`] set in their header and must not be
+    /// modified in place.
+    ///
+    /// # Returns
+    ///
+    /// true if the petrification flag is set
+    pub const fn is_petrified(&self) -> bool {
+        self.header.flags & BBF_PETRIFICATION_FLAG != 0
+    }
+
     /// verifies the integrity of the entire file
     ///
     /// validates both the footer hash (covering all index data) and all individual asset hashes in
@@ -479,25 +945,14 @@ impl BbfReader {
     /// - index region bounds are invalid
     /// - retrieving asset data fails
     pub fn verify_integrity(&self) -> Result<bool> {
-        let meta_start = self.footer.asset_offset as usize;
-        let footer_start = self.header.footer_offset as usize;
-
-        if meta_start >= footer_start || footer_start > self.mmap.len() {
-            return Err(BbfError::InvalidOffset {
-                description: "Invalid index region bounds".into(),
-            });
-        }
-
-        let calc_hash = BbfBuilder::calculate_hash_64(&self.mmap[meta_start..footer_start]);
-
-        if calc_hash != self.footer.footer_hash {
+        if !self.verify_index_hash()? {
             return Ok(false);
         }
 
         let assets = self.assets().into_diagnostic()?;
         let all_valid = assets.par_iter().all(|asset| {
             if let Ok(data) = self.get_asset_data(asset) {
-                let hash_128 = BbfBuilder::calculate_hash_128(data);
+                let hash_128 = BbfBuilder::calculate_hash_128(&data);
                 let hash_low = hash_128 as u64;
                 let hash_high = (hash_128 >> 64) as u64;
                 hash_low == asset.asset_hash[0] && hash_high == asset.asset_hash[1]
@@ -509,6 +964,129 @@ impl BbfReader {
         Ok(all_valid)
     }
 
+    /// verifies the footer hash over the index region
+    ///
+    /// # Returns
+    ///
+    /// true if the index region (asset table through string pool) matches the footer hash
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the index region bounds are invalid
+    pub fn verify_index_hash(&self) -> Result<bool> {
+        let meta_start = self.footer.asset_offset as usize;
+        let footer_start = self.header.footer_offset as usize;
+
+        let index =
+            self.index_range(meta_start, footer_start)
+                .ok_or_else(|| BbfError::InvalidOffset {
+                    description: "Invalid index region bounds".into(),
+                })?;
+
+        let calc_hash = BbfBuilder::calculate_hash_64(index);
+
+        Ok(calc_hash == self.footer.footer_hash)
+    }
+
+    /// hashes the index and every asset, collecting every failure
+    ///
+    /// slower to fail than [`BbfReader::verify_integrity`] since it never stops early, but reports
+    /// which assets are damaged and which pages display them.
+    ///
+    /// # Returns
+    ///
+    /// the index hash result and every corrupted asset with the pages referencing it
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the index region bounds or the asset and page tables are invalid
+    pub fn integrity_report(&self) -> Result<IntegrityReport> {
+        crate::integrity::check(self)
+    }
+
+    /// looks up the first book-wide value for a metadata key
+    ///
+    /// scans the metadata table once; build a [`MetadataMap`] with [`BbfReader::metadata_map`]
+    /// for repeated lookups.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - the key to look up; well-known keys (see [`crate::keys`]) match regardless of
+    ///   case
+    ///
+    /// # Returns
+    ///
+    /// the value, or `None` if the book has no book-wide entry with that key
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the metadata table or one of its strings can't be read
+    pub fn get_metadata(&self, key: &str) -> Result<Option<&str>> {
+        for meta in self.metadata()? {
+            if meta.parent_offset == u64::MAX
+                && crate::metadata::keys_match(self.get_string(meta.key_offset)?, key)
+            {
+                return self.get_string(meta.value_offset).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// collects the metadata entries that belong to a section
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - the section title
+    ///
+    /// # Returns
+    ///
+    /// every `(key, value)` pair whose parent is `title`, in table order
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the metadata table or one of its strings can't be read
+    pub fn metadata_for_section(&self, title: &str) -> Result<Vec<(&str, &str)>> {
+        let mut entries = Vec::new();
+        for meta in self.metadata()? {
+            if meta.parent_offset != u64::MAX && self.get_string(meta.parent_offset)? == title {
+                entries.push((
+                    self.get_string(meta.key_offset)?,
+                    self.get_string(meta.value_offset)?,
+                ));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// indexes every metadata entry by key
+    ///
+    /// # Returns
+    ///
+    /// a map answering lookups without rescanning the table
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the metadata table or one of its strings can't be read
+    pub fn metadata_map(&self) -> Result<MetadataMap<'_>> {
+        MetadataMap::from_reader(self)
+    }
+
+    /// links the sections into a tree with a page range per section
+    ///
+    /// # Returns
+    ///
+    /// the tree, along with any missing parents, duplicate titles, or parent cycles found while
+    /// linking it
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the section table or any section title can't be read
+    pub fn section_tree(&self) -> Result<SectionTree> {
+        SectionTree::build(self)
+    }
+
     /// verifies the integrity of a single asset
     ///
     /// calculates the xxh3 128-bit hash of the asset's data and compares it to the stored hash
@@ -537,7 +1115,7 @@ impl BbfReader {
 
         let asset = &assets[index];
         let data = self.get_asset_data(asset).into_diagnostic()?;
-        let hash_128 = BbfBuilder::calculate_hash_128(data);
+        let hash_128 = BbfBuilder::calculate_hash_128(&data);
         let hash_low = hash_128 as u64;
         let hash_high = (hash_128 >> 64) as u64;
 
@@ -793,6 +1371,38 @@ mod tests {
     }
 
     #[test]
+    fn test_metadata_lookups() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[3u8; 256], MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_section("Vol 1", 0, None);
+        builder.add_metadata("TITLE", "Book", None);
+        builder.add_metadata("Author", "Alice", None);
+        builder.add_metadata("Author", "Carol", None);
+        builder.add_metadata("Title", "First Volume", Some("Vol 1"));
+        builder.add_metadata("Translator", "Bob", Some("Vol 1"));
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.get_metadata(crate::keys::TITLE).unwrap() == Some("Book"));
+        assert!(reader.get_metadata("author").unwrap() == Some("Alice"));
+        assert!(reader.get_metadata("Translator").unwrap().is_none());
+        assert!(
+            reader.metadata_for_section("Vol 1").unwrap()
+                == [("Title", "First Volume"), ("Translator", "Bob")]
+        );
+        assert!(reader.metadata_for_section("Vol 2").unwrap().is_empty());
+
+        let map = reader.metadata_map().unwrap();
+        assert!(map.get_all("AUTHOR").collect::<Vec<_>>() == ["Alice", "Carol"]);
+        assert!(map.get_in_section("Vol 1", "translator").is_none());
+        assert!(map.get_in_section("Vol 1", "Translator") == Some("Bob"));
+    }
+
+    #[test]
+    #[macroni_n_cheese::mathinator2000]
     fn test_verify_integrity_detects_corruption() {
         let temp_output = NamedTempFile::new().unwrap();
         let test_image = NamedTempFile::new().unwrap();
@@ -856,4 +1466,207 @@ mod tests {
         assert!(unsafe { read_unaligned(footer.section_count) } == 1);
         assert!(unsafe { read_unaligned(footer.meta_count) } == 1);
     }
+
+    #[test]
+    fn test_is_petrified() {
+        let test_file = create_test_bbf_file();
+        let reader = BbfReader::open(test_file.path()).unwrap();
+        assert!(!reader.is_petrified());
+
+        let temp_output = NamedTempFile::new().unwrap();
+        let builder = BbfBuilder::new(
+            temp_output.path(),
+            12,
+            16,
+            crate::format::BBF_PETRIFICATION_FLAG,
+        )
+        .unwrap();
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.is_petrified());
+    }
+
+    #[test]
+    fn test_thumbnail_absent_by_default() {
+        let test_file = create_test_bbf_file();
+        let reader = BbfReader::open(test_file.path()).unwrap();
+        assert!(reader.thumbnail_record().unwrap().is_none());
+        assert!(reader.thumbnail().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_thumbnail_roundtrip() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 4096], MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .set_thumbnail(&[2u8; 64], MediaType::Jpg, 8, 8)
+            .unwrap();
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+        assert!(reader.page_count() == 1);
+
+        let record = reader.thumbnail_record().unwrap().unwrap();
+        assert!(record.width == 8);
+        assert!(record.height == 8);
+
+        let assets = reader.assets().unwrap();
+        assert!(assets[record.asset_index as usize].flags & BBF_ASSET_THUMBNAIL_FLAG != 0);
+
+        let (media_type, data) = reader.thumbnail().unwrap().unwrap();
+        assert!(media_type == MediaType::Jpg);
+        assert!(*data == [2u8; 64]);
+    }
+
+    #[test]
+    fn test_thumbnail_falls_back_to_asset_flag() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .set_thumbnail(&[3u8; 32], MediaType::Png, 4, 4)
+            .unwrap();
+        builder.remove_expansions(THUMBNAIL_EXPANSION_KIND);
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(temp_output.path()).unwrap();
+        let record = reader.thumbnail_record().unwrap().unwrap();
+        assert!(record.width == 0);
+        assert!(*reader.thumbnail().unwrap().unwrap().1 == [3u8; 32]);
+    }
+
+    #[test]
+    fn test_thumbnail_rejects_dangling_record() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder.add_expansion(&Thumbnail {
+            asset_index: 5,
+            width: 1,
+            height: 1,
+        });
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.thumbnail().is_err());
+    }
+
+    #[test]
+    fn test_from_bytes_matches_mmap() {
+        let test_file = create_test_bbf_file();
+        let mapped = BbfReader::open(test_file.path()).unwrap();
+        let reader = BbfReader::from_bytes(std::fs::read(test_file.path()).unwrap()).unwrap();
+
+        assert!(reader.page_count() == mapped.page_count());
+        assert!(reader.verify_integrity().unwrap());
+
+        let assets = reader.assets().unwrap();
+        let data = reader.get_asset_data(&assets[0]).unwrap();
+        assert!(matches!(data, Cow::Borrowed(_)));
+        assert!(*data == *mapped.get_asset_data(&assets[0]).unwrap());
+    }
+
+    #[test]
+    fn test_from_reader_reads_owned() {
+        let test_file = create_test_bbf_file();
+        let reader = BbfReader::from_reader(File::open(test_file.path()).unwrap()).unwrap();
+
+        assert!(reader.verify_integrity().unwrap());
+        assert!(reader.integrity_report().unwrap().is_ok());
+        assert!(reader.validate_deep().is_clean());
+
+        let sections = reader.sections().unwrap();
+        assert!(reader.get_string(sections[0].section_title_offset).unwrap() == "Chapter 1");
+
+        let assets = reader.assets().unwrap();
+        let data = reader.get_asset_data(&assets[0]).unwrap();
+        assert!(matches!(data, Cow::Owned(_)));
+        assert!(data.len() == 1024);
+        assert!(data.iter().all(|&b| b == 1));
+    }
+
+    #[test]
+    #[macroni_n_cheese::mathinator2000]
+    fn test_from_storage_rejects_asset_offset_past_end() {
+        let test_file = create_test_bbf_file();
+        let reader = BbfReader::open(test_file.path()).unwrap();
+        let footer_offset = reader.header().footer_offset as usize;
+        drop(reader);
+
+        let mut data = std::fs::read(test_file.path()).unwrap();
+        data[footer_offset..footer_offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let result = BbfReader::from_bytes(data);
+        assert!(matches!(result, Err(BbfError::InvalidOffset { .. })));
+    }
+
+    #[test]
+    fn test_open_hardened_matches_mmap() {
+        let test_file = create_test_bbf_file();
+        let mapped = BbfReader::open(test_file.path()).unwrap();
+        let reader = BbfReader::open_hardened(test_file.path()).unwrap();
+
+        assert!(reader.is_hardened());
+        assert!(!mapped.is_hardened());
+        assert!(reader.verify_integrity().unwrap());
+
+        let asset = reader.assets().unwrap()[0];
+        let mapped_asset = mapped.assets().unwrap()[0];
+        assert!({ asset.file_offset } == { mapped_asset.file_offset });
+        assert!({ asset.asset_hash } == { mapped_asset.asset_hash });
+
+        let meta = reader.metadata().unwrap();
+        assert!(reader.get_string(meta[0].value_offset).unwrap() == "Test Book");
+    }
+
+    #[test]
+    #[macroni_n_cheese::mathinator2000]
+    fn test_open_hardened_rejects_table_out_of_bounds() {
+        let test_file = create_test_bbf_file();
+        let reader = BbfReader::open(test_file.path()).unwrap();
+        let footer_offset = reader.header().footer_offset as usize;
+        drop(reader);
+
+        let mut data = std::fs::read(test_file.path()).unwrap();
+        let page_count = footer_offset + 64;
+        data[page_count..page_count + 8].copy_from_slice(&1000u64.to_le_bytes());
+        std::fs::write(test_file.path(), data).unwrap();
+
+        let reader = BbfReader::open(test_file.path()).unwrap();
+        assert!(reader.pages().is_err());
+
+        let result = BbfReader::open_hardened(test_file.path());
+        assert!(matches!(result, Err(BbfError::InvalidOffset { .. })));
+    }
+
+    #[test]
+    fn test_open_hardened_survives_truncation() {
+        let test_file = create_test_bbf_file();
+        let reader = BbfReader::open_hardened(test_file.path()).unwrap();
+        test_file.as_file().set_len(100).unwrap();
+
+        let assets = reader.assets().unwrap();
+        assert!(matches!(
+            reader.get_asset_data(&assets[0]),
+            Err(BbfError::Io(_))
+        ));
+        assert!(reader.sections().unwrap().len() == 1);
+    }
+
+    #[test]
+    fn test_open_strict_rejects_reserved_fields() {
+        let test_file = create_test_bbf_file();
+        assert!(BbfReader::open_strict(test_file.path()).is_ok());
+
+        let mut data = std::fs::read(test_file.path()).unwrap();
+        data[24] = 1;
+        std::fs::write(test_file.path(), data).unwrap();
+
+        assert!(BbfReader::open(test_file.path()).is_ok());
+        let result = BbfReader::open_strict(test_file.path());
+        assert!(matches!(result, Err(BbfError::ReservedFieldNonZero { .. })));
+    }
 }
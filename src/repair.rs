@@ -0,0 +1,923 @@
+use {
+    crate::{BbfBuilder, BbfError, ExpansionRecord, Result, Thumbnail, format::*, types::*},
+    std::path::Path,
+    xxhash_rust::xxh3::xxh3_128,
+};
+
+/// how a repair located the assets it recovered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryMethod {
+    /// the asset table survived and assets were re-hashed against it
+    Index,
+    /// the index was lost and assets were found by scanning for image signatures
+    MagicScan,
+}
+
+/// what a repair managed to recover
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairReport {
+    /// how the assets were located
+    pub method: RecoveryMethod,
+    /// number of assets written to the repaired file
+    pub assets_recovered: u64,
+    /// indices (in the damaged file's asset table) of assets that failed their hash or bounds
+    /// check and were dropped
+    pub damaged_assets: Vec<u64>,
+    /// number of pages written to the repaired file
+    pub pages_recovered: u64,
+    /// number of pages dropped because their asset was damaged or missing
+    pub pages_lost: u64,
+    /// number of sections written to the repaired file
+    pub sections_recovered: u64,
+    /// number of sections dropped because their title or start page was lost
+    pub sections_lost: u64,
+    /// number of metadata entries written to the repaired file
+    pub metadata_recovered: u64,
+    /// number of metadata entries dropped because their strings were lost
+    pub metadata_lost: u64,
+    /// human-readable notes about guesses the repair had to make
+    pub notes: Vec<String>,
+}
+
+impl RepairReport {
+    const fn new(method: RecoveryMethod) -> Self {
+        Self {
+            method,
+            assets_recovered: 0,
+            damaged_assets: Vec::new(),
+            pages_recovered: 0,
+            pages_lost: 0,
+            sections_recovered: 0,
+            sections_lost: 0,
+            metadata_recovered: 0,
+            metadata_lost: 0,
+            notes: Vec::new(),
+        }
+    }
+
+    /// checks whether everything the damaged file described made it into the repaired file
+    pub const fn is_lossless(&self) -> bool {
+        matches!(self.method, RecoveryMethod::Index)
+            && self.damaged_assets.is_empty()
+            && self.pages_lost == 0
+            && self.sections_lost == 0
+            && self.metadata_lost == 0
+    }
+}
+
+/// salvages a damaged BBF file into a new, valid one
+///
+/// if the footer and asset table survived, every asset is re-hashed against its `AssetEntry`;
+/// intact assets are kept along with the pages, sections, and metadata that still resolve.
+/// otherwise the file is scanned for image signatures and every image found becomes a page, in
+/// file order. either way a fresh index and footer are written to `output`.
+///
+/// # Arguments
+///
+/// * `input` - the damaged file
+/// * `output` - where to write the repaired file (must differ from `input`)
+///
+/// # Returns
+///
+/// a report of what was recovered and what was lost
+///
+/// # Errors
+///
+/// returns an error if the input can't be read, the output is the input, or the output can't be
+/// written
+pub fn repair<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<RepairReport> {
+    let input = input.as_ref();
+    let output = output.as_ref();
+
+    if output.exists() && std::fs::canonicalize(input)? == std::fs::canonicalize(output)? {
+        return Err(BbfError::Other {
+            message: "repair output must be a different file than the input".into(),
+        });
+    }
+
+    // read rather than mapped: a damaged file may be truncated or rewritten while it's scanned
+    let data: &[u8] = &std::fs::read(input)?;
+
+    let header = read_at::<BbfHeader>(data, 0).filter(|header| &header.magic == MAGIC);
+
+    let (guard, ream, flags) = match header {
+        Some(h) if h.alignment <= 16 && h.ream_size <= 16 => (h.alignment, h.ream_size, h.flags),
+        _ => (DEFAULT_GUARD_ALIGNMENT, DEFAULT_SMALL_REAM_THRESHOLD, 0),
+    };
+
+    let mut builder = BbfBuilder::new(output, guard, ream, flags)?;
+
+    let report = match SalvagedIndex::find(data, header.as_ref()) {
+        Some(index) => rebuild_from_index(data, &index, &mut builder)?,
+        None => rebuild_from_scan(data, header.as_ref(), &mut builder)?,
+    };
+
+    builder.finalize()?;
+    Ok(report)
+}
+
+/// an index whose footer could be trusted
+struct SalvagedIndex<'a> {
+    /// the asset table
+    assets: Vec<AssetEntry>,
+    /// the page table
+    pages: Vec<PageEntry>,
+    /// the section table
+    sections: Vec<Section>,
+    /// the metadata table
+    metadata: Vec<Metadata>,
+    /// the expansion table
+    expansions: Vec<Expansion>,
+    /// the string pool
+    pool: &'a [u8],
+    /// whether the index region still matches the footer's hash
+    hash_ok: bool,
+}
+
+impl<'a> SalvagedIndex<'a> {
+    /// looks for a trustworthy footer at the header's footer offset, then at the end of the file
+    fn find(data: &'a [u8], header: Option<&BbfHeader>) -> Option<Self> {
+        let footer_size = std::mem::size_of::<BbfFooter>() as u64;
+        let candidates = [
+            header.map(|header| header.footer_offset),
+            (data.len() as u64).checked_sub(footer_size),
+        ];
+
+        candidates
+            .into_iter()
+            .flatten()
+            .filter_map(|offset| read_at::<BbfFooter>(data, offset).map(|f| (offset, f)))
+            .find_map(|(offset, footer)| Self::from_footer(data, offset, &footer))
+    }
+
+    /// reads every table the footer describes, if the footer can be trusted
+    ///
+    /// any 256 bytes after the header decode as some footer (all zeros included), so a candidate
+    /// is only used if the index region still matches its hash, or if it at least looks like a
+    /// real index: it starts after the header, lists assets, and every table lies inside it.
+    /// otherwise the file is scanned instead.
+    fn from_footer(data: &'a [u8], footer_offset: u64, footer: &BbfFooter) -> Option<Self> {
+        let region = footer.asset_offset..footer_offset;
+        if region.start < std::mem::size_of::<BbfHeader>() as u64 {
+            return None;
+        }
+
+        let index =
+            data.get(usize::try_from(region.start).ok()?..usize::try_from(region.end).ok()?)?;
+        let hash_ok = BbfBuilder::calculate_hash_64(index) == { footer.footer_hash };
+        if !hash_ok && footer.asset_count == 0 {
+            return None;
+        }
+
+        let pool_end = footer
+            .string_pool_offset
+            .checked_add(footer.string_pool_size)?;
+        let pool = if footer.string_pool_size == 0 {
+            &[][..]
+        } else if footer.string_pool_offset >= region.start && pool_end <= region.end {
+            data.get(
+                usize::try_from(footer.string_pool_offset).ok()?..usize::try_from(pool_end).ok()?,
+            )?
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            assets: read_index_table(data, &region, footer.asset_offset, footer.asset_count)?,
+            pages: read_index_table(data, &region, footer.page_offset, footer.page_count)?,
+            sections: read_index_table(data, &region, footer.section_offset, footer.section_count)?,
+            metadata: read_index_table(data, &region, footer.meta_offset, footer.meta_count)?,
+            expansions: read_index_table(
+                data,
+                &region,
+                footer.expansion_offset,
+                footer.expansion_count,
+            )?,
+            pool,
+            hash_ok,
+        })
+    }
+
+    /// resolves a string pool offset
+    fn string(&self, offset: u64) -> Option<&'a str> {
+        let rest = self.pool.get(usize::try_from(offset).ok()?..)?;
+        let limit = rest.len().min(MAX_FORME_SIZE as usize);
+        let len = rest[..limit].iter().position(|&b| b == 0)?;
+        std::str::from_utf8(&rest[..len]).ok()
+    }
+
+    /// resolves an optional (`u64::MAX` = none) string pool offset
+    fn optional_string(&self, offset: u64) -> Option<Option<&'a str>> {
+        if offset == u64::MAX {
+            Some(None)
+        } else {
+            self.string(offset).map(Some)
+        }
+    }
+}
+
+/// writes every intact asset, page, section, and metadata entry from a salvaged index
+fn rebuild_from_index(
+    data: &[u8],
+    index: &SalvagedIndex<'_>,
+    builder: &mut BbfBuilder,
+) -> Result<RepairReport> {
+    let mut report = RepairReport::new(RecoveryMethod::Index);
+    if !index.hash_ok {
+        report
+            .notes
+            .push("index doesn't match its hash; entries were checked one by one".into());
+    }
+
+    let intact: Vec<Option<&[u8]>> = index
+        .assets
+        .iter()
+        .enumerate()
+        .map(|(i, asset)| {
+            let bytes = asset_bytes(data, asset).filter(|bytes| {
+                let hash = xxh3_128(bytes);
+                [hash as u64, (hash >> 64) as u64] == { asset.asset_hash }
+            });
+
+            if bytes.is_none() {
+                report.damaged_assets.push(i as u64);
+            }
+            bytes
+        })
+        .collect();
+
+    // new_page_index[old] is the page's index in the repaired file, if it survived
+    let mut new_page_index = Vec::with_capacity(index.pages.len());
+    for page in &index.pages {
+        let Some(&Some(bytes)) = usize::try_from(page.asset_index)
+            .ok()
+            .and_then(|i| intact.get(i))
+        else {
+            new_page_index.push(None);
+            report.pages_lost = report.pages_lost.saturating_add(1);
+            continue;
+        };
+
+        let asset = &index.assets[page.asset_index as usize];
+        builder.add_page_bytes(
+            bytes,
+            MediaType::from(asset.media_type),
+            page.flags,
+            asset.flags,
+        )?;
+        new_page_index.push(Some(report.pages_recovered));
+        report.pages_recovered = report.pages_recovered.saturating_add(1);
+    }
+
+    for section in &index.sections {
+        let start = usize::try_from(section.section_start_index)
+            .ok()
+            .and_then(|start| new_page_index.get(start..))
+            .and_then(|rest| rest.iter().flatten().next().copied());
+        let title = index.string(section.section_title_offset);
+        let parent = index.optional_string(section.section_parent_offset);
+
+        if let (Some(title), Some(start), Some(parent)) = (title, start, parent) {
+            builder.add_section(title, start, parent);
+            report.sections_recovered = report.sections_recovered.saturating_add(1);
+        } else {
+            report.sections_lost = report.sections_lost.saturating_add(1);
+        }
+    }
+
+    for meta in &index.metadata {
+        let key = index.string(meta.key_offset);
+        let value = index.string(meta.value_offset);
+        let parent = index.optional_string(meta.parent_offset);
+
+        if let (Some(key), Some(value), Some(parent)) = (key, value, parent) {
+            builder.add_metadata(key, value, parent);
+            report.metadata_recovered = report.metadata_recovered.saturating_add(1);
+        } else {
+            report.metadata_lost = report.metadata_lost.saturating_add(1);
+        }
+    }
+
+    let expansions = &index.expansions;
+    let thumbnail = expansions
+        .iter()
+        .find_map(Thumbnail::from_expansion)
+        .or_else(|| {
+            index
+                .assets
+                .iter()
+                .position(|asset| asset.flags & BBF_ASSET_THUMBNAIL_FLAG != 0)
+                .map(|i| Thumbnail {
+                    asset_index: i as u64,
+                    width: 0,
+                    height: 0,
+                })
+        });
+
+    for exp in expansions
+        .iter()
+        .filter(|exp| exp.kind() != Thumbnail::KIND)
+    {
+        builder.add_expansion_entry(*exp);
+    }
+
+    if let Some(thumb) = thumbnail {
+        let asset = usize::try_from(thumb.asset_index)
+            .ok()
+            .and_then(|i| Some((index.assets.get(i)?, (*intact.get(i)?)?)));
+
+        match asset {
+            Some((asset, bytes)) => builder.set_thumbnail(
+                bytes,
+                MediaType::from(asset.media_type),
+                thumb.width,
+                thumb.height,
+            )?,
+            None => report
+                .notes
+                .push("thumbnail was damaged and dropped".into()),
+        }
+    }
+
+    report.assets_recovered = builder.asset_count() as u64;
+    Ok(report)
+}
+
+/// writes one page per image found by scanning the file for image signatures
+fn rebuild_from_scan(
+    data: &[u8],
+    header: Option<&BbfHeader>,
+    builder: &mut BbfBuilder,
+) -> Result<RepairReport> {
+    let mut report = RepairReport::new(RecoveryMethod::MagicScan);
+
+    report.notes.push(if header.is_some() {
+        "index unreadable; pages recovered by scanning for image signatures in file order".into()
+    } else {
+        "header and index unreadable; pages recovered by scanning for image signatures in file \
+         order"
+            .into()
+    });
+
+    let found = scan_for_images(data, scan_step(header));
+    let estimated = found.iter().filter(|image| !image.exact).count();
+    if estimated > 0 {
+        report.notes.push(format!(
+            "{estimated} image(s) have no length marker; their sizes were estimated"
+        ));
+    }
+
+    for image in found {
+        builder.add_page_bytes(&data[image.start..image.end], image.media_type, 0, 0)?;
+        report.pages_recovered = report.pages_recovered.saturating_add(1);
+    }
+
+    report.assets_recovered = builder.asset_count() as u64;
+    Ok(report)
+}
+
+/// an image located by [`scan_for_images`]
+struct FoundImage {
+    /// first byte of the image
+    start: usize,
+    /// one past the last byte of the image
+    end: usize,
+    /// the detected format
+    media_type: MediaType,
+    /// whether `end` came from the format's own length information
+    exact: bool,
+}
+
+/// the spacing of the offsets assets can start at
+///
+/// assets are aligned to the header's guard alignment, except that small assets in files with
+/// [`BBF_VARIABLE_REAM_SIZE_FLAG`] set are only 8-byte aligned. without a usable header every
+/// offset has to be tried.
+fn scan_step(header: Option<&BbfHeader>) -> usize {
+    let Some(header) = header.filter(|header| header.alignment <= 16) else {
+        return 1;
+    };
+
+    let guard = 1usize.checked_shl(u32::from(header.alignment)).unwrap_or(1);
+    if header.flags & BBF_VARIABLE_REAM_SIZE_FLAG != 0 {
+        guard.min(8)
+    } else {
+        guard
+    }
+}
+
+/// scans the offsets after the header that are multiples of `step` for image signatures
+///
+/// images whose format records its length are skipped over whole so their contents can't yield
+/// false positives. other images are assumed to run up to the next signature, minus trailing
+/// zero padding.
+fn scan_for_images(data: &[u8], step: usize) -> Vec<FoundImage> {
+    let step = step.max(1);
+    let mut found: Vec<FoundImage> = Vec::new();
+    let mut offset = std::mem::size_of::<BbfHeader>()
+        .checked_next_multiple_of(step)
+        .unwrap_or(usize::MAX);
+
+    while offset < data.len() {
+        let rest = &data[offset..];
+        let media_type = MediaType::from_bytes(rest);
+
+        if media_type == MediaType::Unknown {
+            offset = offset.saturating_add(step);
+            continue;
+        }
+
+        if let Some(last) = found.last_mut().filter(|image| !image.exact) {
+            last.end = trim_padding(data, last.start, offset);
+        }
+
+        match encoded_len(rest, media_type) {
+            Some(len) => {
+                let end = offset.saturating_add(len);
+                found.push(FoundImage {
+                    start: offset,
+                    end,
+                    media_type,
+                    exact: true,
+                });
+                offset = end.checked_next_multiple_of(step).unwrap_or(usize::MAX);
+            }
+            None => {
+                found.push(FoundImage {
+                    start: offset,
+                    end: data.len(),
+                    media_type,
+                    exact: false,
+                });
+                offset = offset.saturating_add(step);
+            }
+        }
+    }
+
+    if let Some(last) = found.last_mut().filter(|image| !image.exact) {
+        last.end = trim_padding(data, last.start, data.len());
+    }
+
+    found
+}
+
+/// moves `end` back over trailing zero bytes, never past `start`
+fn trim_padding(data: &[u8], start: usize, end: usize) -> usize {
+    data[start..end]
+        .iter()
+        .rposition(|&b| b != 0)
+        .map_or(end, |last| start.saturating_add(last).saturating_add(1))
+}
+
+/// determines the length of an encoded image from its own structure
+///
+/// # Returns
+///
+/// the length in bytes, or `None` if the format doesn't record it or the data is truncated
+fn encoded_len(data: &[u8], media_type: MediaType) -> Option<usize> {
+    let len = match media_type {
+        MediaType::Png => png_len(data),
+        MediaType::Jpg => jpeg_len(data),
+        MediaType::Webp => {
+            let size =
+                usize::try_from(u32::from_le_bytes(data.get(4..8)?.try_into().ok()?)).ok()?;
+            size.checked_add(8)?.checked_add(size & 1)
+        }
+        MediaType::Bmp => {
+            usize::try_from(u32::from_le_bytes(data.get(2..6)?.try_into().ok()?)).ok()
+        }
+        MediaType::Gif => gif_len(data),
+        MediaType::Avif => bmff_len(data),
+        _ => None,
+    }?;
+
+    (len > 0 && len <= data.len()).then_some(len)
+}
+
+/// walks png chunks up to and including `IEND`
+fn png_len(data: &[u8]) -> Option<usize> {
+    let mut pos = 8usize;
+
+    loop {
+        let len = u32::from_be_bytes(data.get(pos..pos.checked_add(4)?)?.try_into().ok()?);
+        let kind = data.get(pos.checked_add(4)?..pos.checked_add(8)?)?;
+        pos = pos
+            .checked_add(12)?
+            .checked_add(usize::try_from(len).ok()?)?;
+
+        if kind == b"IEND" {
+            return Some(pos);
+        }
+    }
+}
+
+/// walks jpeg segments, skipping entropy-coded data, up to and including the `EOI` marker
+fn jpeg_len(data: &[u8]) -> Option<usize> {
+    let mut pos = 2usize;
+
+    loop {
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+
+        let marker = *data.get(pos.checked_add(1)?)?;
+        match marker {
+            0xFF => pos = pos.checked_add(1)?,
+            0xD9 => return pos.checked_add(2),
+            0x01 | 0xD0..=0xD8 => pos = pos.checked_add(2)?,
+            _ => {
+                let len = u16::from_be_bytes(
+                    data.get(pos.checked_add(2)?..pos.checked_add(4)?)?
+                        .try_into()
+                        .ok()?,
+                );
+                pos = pos.checked_add(2)?.checked_add(usize::from(len))?;
+
+                if marker == 0xDA {
+                    // entropy-coded data runs until a marker other than a stuffed 0xFF00 or a
+                    // restart marker
+                    while !matches!(
+                        (data.get(pos)?, data.get(pos.checked_add(1)?)?),
+                        (0xFF, next) if *next != 0x00 && !(0xD0..=0xD7).contains(next)
+                    ) {
+                        pos = pos.checked_add(1)?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// walks gif blocks up to and including the trailer
+fn gif_len(data: &[u8]) -> Option<usize> {
+    let color_table_len = |packed: u8| 6usize << (packed & 0x07);
+
+    let screen_flags = *data.get(10)?;
+    let mut pos = 13usize;
+    if screen_flags & 0x80 != 0 {
+        pos = pos.checked_add(color_table_len(screen_flags))?;
+    }
+
+    let skip_sub_blocks = |mut pos: usize| -> Option<usize> {
+        loop {
+            let len = usize::from(*data.get(pos)?);
+            pos = pos.checked_add(1)?.checked_add(len)?;
+            if len == 0 {
+                return Some(pos);
+            }
+        }
+    };
+
+    loop {
+        match *data.get(pos)? {
+            0x3B => return pos.checked_add(1),
+            0x21 => pos = skip_sub_blocks(pos.checked_add(2)?)?,
+            0x2C => {
+                let image_flags = *data.get(pos.checked_add(9)?)?;
+                pos = pos.checked_add(10)?;
+                if image_flags & 0x80 != 0 {
+                    pos = pos.checked_add(color_table_len(image_flags))?;
+                }
+                pos = skip_sub_blocks(pos.checked_add(1)?)?;
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// walks top-level iso-bmff boxes (avif) until the data stops looking like a box
+fn bmff_len(data: &[u8]) -> Option<usize> {
+    let mut pos = 0usize;
+
+    while let (Some(size), Some(kind)) = (
+        data.get(pos..pos.saturating_add(4)),
+        data.get(pos.saturating_add(4)..pos.saturating_add(8)),
+    ) {
+        if !kind.iter().all(u8::is_ascii_alphanumeric) {
+            break;
+        }
+
+        let size = match u32::from_be_bytes(size.try_into().ok()?) {
+            1 => u64::from_be_bytes(
+                data.get(pos.checked_add(8)?..pos.checked_add(16)?)?
+                    .try_into()
+                    .ok()?,
+            ),
+            size => u64::from(size),
+        };
+
+        let Some(end) = usize::try_from(size)
+            .ok()
+            .filter(|&size| size >= 8)
+            .and_then(|size| pos.checked_add(size))
+            .filter(|&end| end <= data.len())
+        else {
+            break;
+        };
+        pos = end;
+    }
+
+    (pos > 0).then_some(pos)
+}
+
+/// returns an asset's data if it lies after the header and within the file
+fn asset_bytes<'a>(data: &'a [u8], asset: &AssetEntry) -> Option<&'a [u8]> {
+    let start = usize::try_from(asset.file_offset).ok()?;
+    let end = start.checked_add(usize::try_from(asset.file_size).ok()?)?;
+
+    if start < std::mem::size_of::<BbfHeader>() {
+        return None;
+    }
+
+    data.get(start..end)
+}
+
+/// decodes a struct at `offset`, if it fits
+fn read_at<T: FromLeBytes>(data: &[u8], offset: u64) -> Option<T> {
+    T::from_le_bytes(data.get(usize::try_from(offset).ok()?..)?)
+}
+
+/// decodes a table that has to lie inside the index region
+fn read_index_table<T: FromLeBytes>(
+    data: &[u8],
+    region: &std::ops::Range<u64>,
+    offset: u64,
+    count: u64,
+) -> Option<Vec<T>> {
+    if count == 0 {
+        return Some(Vec::new());
+    }
+
+    let end = offset.checked_add(count.checked_mul(T::SIZE as u64)?)?;
+    if offset < region.start || end > region.end {
+        return None;
+    }
+
+    read_table(data, offset, count)
+}
+
+/// decodes `count` structs starting at `offset`, if they all fit
+fn read_table<T: FromLeBytes>(data: &[u8], offset: u64, count: u64) -> Option<Vec<T>> {
+    let size = T::SIZE as u64;
+    let len = count.checked_mul(size)?;
+    offset
+        .checked_add(len)
+        .filter(|&end| end <= data.len() as u64)?;
+
+    (0..count)
+        .map(|i| read_at(data, offset.checked_add(i.checked_mul(size)?)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, crate::BbfReader, assert2::check as assert, tempfile::NamedTempFile};
+
+    /// a structurally valid png (unchecked crcs) with a unique payload
+    #[macroni_n_cheese::mathinator2000]
+    fn fake_png(seed: u8, payload_len: usize) -> Vec<u8> {
+        let chunk = |kind: &[u8], body: &[u8]| {
+            let mut out = (body.len() as u32).to_be_bytes().to_vec();
+            out.extend_from_slice(kind);
+            out.extend_from_slice(body);
+            out.extend_from_slice(&[0; 4]);
+            out
+        };
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(chunk(b"IHDR", &[0; 13]));
+        png.extend(chunk(b"IDAT", &vec![seed; payload_len]));
+        png.extend(chunk(b"IEND", &[]));
+        png
+    }
+
+    /// a structurally valid jpeg with a unique entropy-coded payload
+    #[macroni_n_cheese::mathinator2000]
+    fn fake_jpeg(seed: u8) -> Vec<u8> {
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00];
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x03, 0x00]);
+        jpeg.extend_from_slice(&[seed, 0xFF, 0x00, seed, 0xFF, 0xD3, seed]);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        jpeg
+    }
+
+    fn build_book(path: &Path) {
+        let mut builder = BbfBuilder::with_defaults(path).unwrap();
+        builder
+            .add_page_bytes(&fake_png(1, 5000), MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&fake_jpeg(2), MediaType::Jpg, 0, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&fake_png(3, 300), MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_section("One", 0, None);
+        builder.add_section("Two", 1, None);
+        builder.add_metadata("Title", "Salvage", None);
+        builder.finalize().unwrap();
+    }
+
+    #[test]
+    #[macroni_n_cheese::mathinator2000]
+    fn test_encoded_len_of_known_formats() {
+        let png = fake_png(9, 100);
+        let jpeg = fake_jpeg(9);
+
+        let mut padded = png.clone();
+        padded.extend_from_slice(&[0xAB; 50]);
+        assert!(encoded_len(&padded, MediaType::Png) == Some(png.len()));
+
+        let mut padded = jpeg.clone();
+        padded.extend_from_slice(&[0xFF, 0xD8, 0xFF]);
+        assert!(encoded_len(&padded, MediaType::Jpg) == Some(jpeg.len()));
+
+        let truncated = &png[..png.len() - 1];
+        assert!(encoded_len(truncated, MediaType::Png).is_none());
+    }
+
+    #[test]
+    fn test_repair_intact_file_is_lossless() {
+        let input = NamedTempFile::new().unwrap();
+        let output = NamedTempFile::new().unwrap();
+        build_book(input.path());
+
+        let report = repair(input.path(), output.path()).unwrap();
+        assert!(report.is_lossless(), "{report:?}");
+        assert!(report.pages_recovered == 3);
+        assert!(report.sections_recovered == 2);
+        assert!(report.metadata_recovered == 1);
+
+        let reader = BbfReader::open(output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+        assert!(reader.page_count() == 3);
+    }
+
+    #[test]
+    fn test_repair_drops_damaged_asset_and_remaps_sections() {
+        let input = NamedTempFile::new().unwrap();
+        let output = NamedTempFile::new().unwrap();
+        build_book(input.path());
+
+        let reader = BbfReader::open(input.path()).unwrap();
+        let offset = reader.assets().unwrap()[1].file_offset as usize;
+        drop(reader);
+
+        let mut data = std::fs::read(input.path()).unwrap();
+        data[offset + 10] ^= 0xFF;
+        std::fs::write(input.path(), data).unwrap();
+
+        let report = repair(input.path(), output.path()).unwrap();
+        assert!(report.method == RecoveryMethod::Index);
+        assert!(report.damaged_assets == vec![1]);
+        assert!(report.pages_lost == 1);
+        assert!(report.sections_recovered == 2);
+
+        let reader = BbfReader::open(output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+        assert!(reader.page_count() == 2);
+
+        let sections = reader.sections().unwrap();
+        assert!(reader.get_string(sections[1].section_title_offset).unwrap() == "Two");
+        assert!({ sections[1].section_start_index } == 1);
+    }
+
+    #[test]
+    fn test_repair_truncated_file_by_scanning() {
+        let input = NamedTempFile::new().unwrap();
+        let output = NamedTempFile::new().unwrap();
+        build_book(input.path());
+
+        let reader = BbfReader::open(input.path()).unwrap();
+        let index_start = reader.footer().asset_offset as usize;
+        let expected: Vec<Vec<u8>> = reader
+            .assets()
+            .unwrap()
+            .iter()
+            .map(|asset| reader.get_asset_data(asset).unwrap().to_vec())
+            .collect();
+        drop(reader);
+
+        let mut data = std::fs::read(input.path()).unwrap();
+        data.truncate(index_start);
+        std::fs::write(input.path(), data).unwrap();
+
+        let report = repair(input.path(), output.path()).unwrap();
+        assert!(report.method == RecoveryMethod::MagicScan);
+        assert!(report.pages_recovered == 3);
+        assert!(!report.is_lossless());
+
+        let reader = BbfReader::open(output.path()).unwrap();
+        assert!(reader.verify_integrity().unwrap());
+
+        let recovered: Vec<Vec<u8>> = reader
+            .assets()
+            .unwrap()
+            .iter()
+            .map(|asset| reader.get_asset_data(asset).unwrap().to_vec())
+            .collect();
+        assert!(recovered == expected);
+    }
+
+    #[test]
+    fn test_repair_scans_file_with_zero_filled_tail() {
+        let input = NamedTempFile::new().unwrap();
+        let output = NamedTempFile::new().unwrap();
+        build_book(input.path());
+
+        let reader = BbfReader::open(input.path()).unwrap();
+        let second_asset = reader.assets().unwrap()[1].file_offset as usize;
+        drop(reader);
+
+        // like a preallocated download that stopped after the first page
+        let mut data = std::fs::read(input.path()).unwrap();
+        data[second_asset..].fill(0);
+        std::fs::write(input.path(), data).unwrap();
+
+        let report = repair(input.path(), output.path()).unwrap();
+        assert!(report.method == RecoveryMethod::MagicScan);
+        assert!(report.pages_recovered == 1);
+        assert!(!report.is_lossless());
+
+        let reader = BbfReader::open(output.path()).unwrap();
+        assert!(reader.page_count() == 1);
+        let assets = reader.assets().unwrap();
+        assert!(*reader.get_asset_data(&assets[0]).unwrap() == fake_png(1, 5000));
+    }
+
+    #[test]
+    fn test_repair_scans_unaligned_assets() {
+        let input = NamedTempFile::new().unwrap();
+        let output = NamedTempFile::new().unwrap();
+
+        // with an alignment of 0 assets are packed back to back at odd offsets
+        let images = [fake_png(1, 37), fake_jpeg(2), fake_png(3, 101)];
+        let mut builder = BbfBuilder::new(input.path(), 0, 16, 0).unwrap();
+        for image in &images {
+            builder
+                .add_page_bytes(image, MediaType::Unknown, 0, 0)
+                .unwrap();
+        }
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(input.path()).unwrap();
+        let offsets: Vec<u64> = reader
+            .assets()
+            .unwrap()
+            .iter()
+            .map(|asset| asset.file_offset)
+            .collect();
+        let index_start = reader.footer().asset_offset as usize;
+        drop(reader);
+        assert!(offsets.iter().any(|offset| offset % 8 != 0));
+
+        let mut data = std::fs::read(input.path()).unwrap();
+        data.truncate(index_start);
+        std::fs::write(input.path(), data).unwrap();
+
+        let report = repair(input.path(), output.path()).unwrap();
+        assert!(report.method == RecoveryMethod::MagicScan);
+        assert!(report.pages_recovered == 3);
+
+        let reader = BbfReader::open(output.path()).unwrap();
+        let recovered: Vec<Vec<u8>> = reader
+            .assets()
+            .unwrap()
+            .iter()
+            .map(|asset| reader.get_asset_data(asset).unwrap().to_vec())
+            .collect();
+        assert!(recovered == images);
+    }
+
+    #[test]
+    fn test_scan_step_follows_the_header_alignment() {
+        let header = |alignment: u8, flags: u32| BbfHeader {
+            magic: *MAGIC,
+            version: VERSION,
+            header_len: std::mem::size_of::<BbfHeader>() as u16,
+            flags,
+            alignment,
+            ream_size: 16,
+            reserved_extra: 0,
+            footer_offset: 0,
+            reserved: [0; 40],
+        };
+
+        assert!(scan_step(None) == 1);
+        assert!(scan_step(Some(&header(0, 0))) == 1);
+        assert!(scan_step(Some(&header(12, 0))) == 4096);
+        assert!(scan_step(Some(&header(12, BBF_VARIABLE_REAM_SIZE_FLAG))) == 8);
+        assert!(scan_step(Some(&header(2, BBF_VARIABLE_REAM_SIZE_FLAG))) == 4);
+        assert!(scan_step(Some(&header(200, 0))) == 1);
+    }
+
+    #[test]
+    fn test_repair_refuses_in_place() {
+        let input = NamedTempFile::new().unwrap();
+        build_book(input.path());
+
+        assert!(repair(input.path(), input.path()).is_err());
+        assert!(BbfReader::open(input.path()).is_ok());
+    }
+}
@@ -0,0 +1,491 @@
+use {
+    crate::{BbfReader, Result, Storage},
+    std::{fmt, ops::Range},
+};
+
+/// a problem found while linking sections into a tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SectionIssue {
+    /// a section names a parent title no other section has; it's treated as top-level
+    MissingParent {
+        /// the section's position in the section table
+        section: usize,
+        /// the parent title it names
+        parent: String,
+    },
+    /// several sections share a title, so parent links naming it are resolved to the nearest
+    /// preceding section with that title
+    DuplicateTitle {
+        /// the shared title
+        title: String,
+        /// positions of every section with that title
+        sections: Vec<usize>,
+    },
+    /// parent links form a loop; the loop is broken by making its first section top-level
+    Cycle {
+        /// positions of every section in the loop, in table order
+        sections: Vec<usize>,
+    },
+}
+
+impl fmt::Display for SectionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingParent { section, parent } => {
+                write!(f, "section {section} names missing parent {parent:?}")
+            }
+            Self::DuplicateTitle { title, sections } => {
+                write!(f, "sections {sections:?} share the title {title:?}")
+            }
+            Self::Cycle { sections } => {
+                write!(f, "sections {sections:?} are each other's parents")
+            }
+        }
+    }
+}
+
+/// a resolved section, linked to its parent and children by position in the section table
+#[derive(Debug, Clone)]
+struct SectionNode {
+    /// the section title
+    title: String,
+    /// first page of the section
+    start: u64,
+    /// one past the last page of the section
+    end: u64,
+    /// position of the parent section, if any
+    parent: Option<usize>,
+    /// positions of the direct children, in table order
+    children: Vec<usize>,
+}
+
+/// the sections of a book linked into a tree, with a page range per section
+///
+/// the file only stores each section's start page and its parent's *title*, so the tree is
+/// rebuilt here once and every ambiguity is settled by a fixed rule:
+///
+/// - a parent title resolves to the nearest preceding section with that title, or the first
+///   following one if none precede
+/// - a parent title matching no other section makes the section top-level
+/// - a loop of parent links is broken by making its first section (in table order) top-level
+/// - a section covers `[start, end)`, where `end` is the nearest later start of any section
+///   outside its subtree, or the page count
+///
+/// every rule that had to be applied is recorded in [`SectionTree::issues`].
+#[derive(Debug, Clone, Default)]
+pub struct SectionTree {
+    /// every section in table order
+    nodes: Vec<SectionNode>,
+    /// problems found while linking
+    issues: Vec<SectionIssue>,
+}
+
+impl SectionTree {
+    /// reads the section table of a reader and links it into a tree
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - the reader to take sections from
+    ///
+    /// # Returns
+    ///
+    /// the tree along with any issues found while linking it
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the section table lies outside the file or a title or parent title
+    /// can't be read from the string pool
+    pub fn build<S: Storage>(reader: &BbfReader<S>) -> Result<Self> {
+        let table = reader.sections()?;
+
+        let sections = table
+            .iter()
+            .map(|section| {
+                let parent = match section.section_parent_offset {
+                    u64::MAX => None,
+                    offset => Some(reader.get_string(offset)?),
+                };
+                Ok((
+                    reader.get_string(section.section_title_offset)?,
+                    section.section_start_index,
+                    parent,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::from_entries(&sections, reader.page_count()))
+    }
+
+    /// links sections given as `(title, start page, parent title)` in table order
+    #[macroni_n_cheese::mathinator2000]
+    fn from_entries(sections: &[(&str, u64, Option<&str>)], page_count: u64) -> Self {
+        let mut issues = Vec::new();
+
+        let mut by_title: hashbrown::HashMap<&str, Vec<usize>> = hashbrown::HashMap::new();
+        for (i, &(title, ..)) in sections.iter().enumerate() {
+            by_title.entry(title).or_default().push(i);
+        }
+
+        let mut duplicates: Vec<(&str, &Vec<usize>)> = by_title
+            .iter()
+            .filter(|(_, positions)| positions.len() > 1)
+            .map(|(&title, positions)| (title, positions))
+            .collect();
+        duplicates.sort_unstable_by_key(|(_, positions)| positions[0]);
+        issues.extend(duplicates.into_iter().map(|(title, positions)| {
+            SectionIssue::DuplicateTitle {
+                title: title.to_string(),
+                sections: positions.clone(),
+            }
+        }));
+
+        let mut parents: Vec<Option<usize>> = sections
+            .iter()
+            .enumerate()
+            .map(|(i, &(_, _, parent))| {
+                let parent = parent?;
+                let candidates = by_title.get(parent).map_or(&[][..], Vec::as_slice);
+                let resolved = candidates
+                    .iter()
+                    .rev()
+                    .find(|&&j| j < i)
+                    .or_else(|| candidates.iter().find(|&&j| j > i))
+                    .or_else(|| candidates.iter().find(|&&j| j == i))
+                    .copied();
+
+                if resolved.is_none() {
+                    issues.push(SectionIssue::MissingParent {
+                        section: i,
+                        parent: parent.to_string(),
+                    });
+                }
+                resolved
+            })
+            .collect();
+
+        // 0 = unvisited, 1 = on the current walk, 2 = known to reach a root
+        let mut state = vec![0u8; sections.len()];
+        for i in 0..sections.len() {
+            let mut walk = Vec::new();
+            let mut current = Some(i);
+
+            while let Some(node) = current {
+                match state[node] {
+                    2 => break,
+                    1 => {
+                        let looped = walk.iter().position(|&n| n == node).unwrap_or(0);
+                        let mut members = walk[looped..].to_vec();
+                        members.sort_unstable();
+                        parents[members[0]] = None;
+                        issues.push(SectionIssue::Cycle { sections: members });
+                        break;
+                    }
+                    _ => {
+                        state[node] = 1;
+                        walk.push(node);
+                        current = parents[node];
+                    }
+                }
+            }
+
+            for node in walk {
+                state[node] = 2;
+            }
+        }
+
+        let mut nodes: Vec<SectionNode> = sections
+            .iter()
+            .zip(&parents)
+            .map(|(&(title, start, _), &parent)| SectionNode {
+                title: title.to_string(),
+                start: start.min(page_count),
+                end: page_count,
+                parent,
+                children: Vec::new(),
+            })
+            .collect();
+
+        for (i, parent) in parents.iter().enumerate() {
+            if let Some(parent) = *parent {
+                nodes[parent].children.push(i);
+            }
+        }
+
+        let mut in_subtree = vec![false; nodes.len()];
+        for i in 0..nodes.len() {
+            in_subtree.fill(false);
+            let mut stack = vec![i];
+            while let Some(node) = stack.pop() {
+                in_subtree[node] = true;
+                stack.extend(&nodes[node].children);
+            }
+
+            let start = nodes[i].start;
+            nodes[i].end = nodes
+                .iter()
+                .zip(&in_subtree)
+                .filter(|&(other, &inside)| !inside && other.start > start)
+                .map(|(other, _)| other.start)
+                .min()
+                .unwrap_or(page_count);
+        }
+
+        Self { nodes, issues }
+    }
+
+    /// returns the number of sections
+    pub const fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// checks whether the book has no sections
+    pub const fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// returns every problem found while linking the tree
+    pub fn issues(&self) -> &[SectionIssue] {
+        &self.issues
+    }
+
+    /// returns the section at a position in the section table
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the section's position in the section table
+    ///
+    /// # Returns
+    ///
+    /// the section, or `None` if `index` is out of range
+    pub fn get(&self, index: usize) -> Option<SectionView<'_>> {
+        (index < self.nodes.len()).then_some(SectionView {
+            index,
+            nodes: &self.nodes,
+        })
+    }
+
+    /// iterates over every section in table order
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = SectionView<'_>> {
+        let nodes = self.nodes.as_slice();
+        (0..nodes.len()).map(move |index| SectionView { index, nodes })
+    }
+
+    /// iterates over the top-level sections in table order
+    pub fn roots(&self) -> impl Iterator<Item = SectionView<'_>> {
+        self.iter().filter(|section| section.parent().is_none())
+    }
+
+    /// finds the first section with the given title
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - the section title to look for
+    ///
+    /// # Returns
+    ///
+    /// the section, or `None` if no section has that title
+    pub fn find(&self, title: &str) -> Option<SectionView<'_>> {
+        self.iter().find(|section| section.title() == title)
+    }
+
+    /// finds the most deeply nested section containing a page
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - zero-based page number
+    ///
+    /// # Returns
+    ///
+    /// the innermost section whose range contains `page`, or `None` if no section does
+    pub fn section_at(&self, page: u64) -> Option<SectionView<'_>> {
+        let mut found = self.roots().find(|root| root.pages().contains(&page))?;
+        while let Some(child) = found.children().find(|child| child.pages().contains(&page)) {
+            found = child;
+        }
+        Some(found)
+    }
+}
+
+/// a section of a book along with its place in the section hierarchy
+#[derive(Debug, Clone, Copy)]
+pub struct SectionView<'a> {
+    /// position of this section in the section table
+    index: usize,
+    /// every section of the book
+    nodes: &'a [SectionNode],
+}
+
+impl<'a> SectionView<'a> {
+    /// the resolved node backing this view
+    fn node(&self) -> &'a SectionNode {
+        &self.nodes[self.index]
+    }
+
+    /// returns the position of the section in the section table
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// returns the section title
+    pub fn title(&self) -> &'a str {
+        &self.node().title
+    }
+
+    /// returns the first page of the section
+    pub fn start(&self) -> u64 {
+        self.node().start
+    }
+
+    /// returns one past the last page of the section
+    ///
+    /// a section runs until the next section that isn't nested inside it, or to the end of the
+    /// book.
+    pub fn end(&self) -> u64 {
+        self.node().end
+    }
+
+    /// returns the pages of the section as a `[start, end)` range
+    pub fn pages(&self) -> Range<u64> {
+        self.start()..self.end()
+    }
+
+    /// returns the parent section, or `None` for a top-level section
+    pub fn parent(&self) -> Option<SectionView<'a>> {
+        self.node().parent.map(|index| SectionView {
+            index,
+            nodes: self.nodes,
+        })
+    }
+
+    /// returns how many ancestors the section has
+    #[macroni_n_cheese::mathinator2000]
+    pub fn depth(&self) -> usize {
+        let mut depth = 0usize;
+        let mut current = self.parent();
+        while let Some(parent) = current {
+            depth += 1;
+            current = parent.parent();
+        }
+        depth
+    }
+
+    /// iterates over the direct children of the section in table order
+    pub fn children(&self) -> impl ExactSizeIterator<Item = SectionView<'a>> + 'a {
+        let nodes = self.nodes;
+        self.node()
+            .children
+            .iter()
+            .map(move |&index| SectionView { index, nodes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, assert2::check as assert};
+
+    fn ranges(tree: &SectionTree) -> Vec<Range<u64>> {
+        tree.iter().map(|section| section.pages()).collect()
+    }
+
+    #[test]
+    fn test_nested_sections_get_subtree_ranges() {
+        let tree = SectionTree::from_entries(
+            &[
+                ("Volume 1", 0, None),
+                ("Chapter 1", 0, Some("Volume 1")),
+                ("Chapter 2", 3, Some("Volume 1")),
+                ("Volume 2", 6, None),
+                ("Chapter 3", 6, Some("Volume 2")),
+            ],
+            10,
+        );
+
+        assert!(tree.issues().is_empty());
+        assert!(ranges(&tree) == [0..6, 0..3, 3..6, 6..10, 6..10]);
+
+        let roots: Vec<_> = tree.roots().map(|s| s.title()).collect();
+        assert!(roots == ["Volume 1", "Volume 2"]);
+        assert!(tree.find("Chapter 2").unwrap().depth() == 1);
+        assert!(tree.section_at(4).unwrap().title() == "Chapter 2");
+        assert!(tree.section_at(7).unwrap().title() == "Chapter 3");
+    }
+
+    #[test]
+    fn test_missing_parent_becomes_top_level() {
+        let tree = SectionTree::from_entries(
+            &[("Chapter 1", 0, None), ("Chapter 2", 2, Some("Volume 9"))],
+            4,
+        );
+
+        assert!(tree.get(1).unwrap().parent().is_none());
+        assert!(
+            tree.issues()
+                == [SectionIssue::MissingParent {
+                    section: 1,
+                    parent: "Volume 9".into(),
+                }]
+        );
+        assert!(ranges(&tree) == [0..2, 2..4]);
+    }
+
+    #[test]
+    fn test_duplicate_titles_resolve_to_nearest_preceding() {
+        let tree = SectionTree::from_entries(
+            &[
+                ("Part", 0, None),
+                ("Chapter 1", 0, Some("Part")),
+                ("Part", 4, None),
+                ("Chapter 2", 4, Some("Part")),
+            ],
+            8,
+        );
+
+        assert!(tree.get(1).unwrap().parent().unwrap().index() == 0);
+        assert!(tree.get(3).unwrap().parent().unwrap().index() == 2);
+        assert!(
+            tree.issues()
+                == [SectionIssue::DuplicateTitle {
+                    title: "Part".into(),
+                    sections: vec![0, 2],
+                }]
+        );
+        assert!(ranges(&tree) == [0..4, 0..4, 4..8, 4..8]);
+    }
+
+    #[test]
+    fn test_cycles_are_broken_at_first_member() {
+        let tree = SectionTree::from_entries(
+            &[
+                ("A", 0, Some("C")),
+                ("B", 2, Some("A")),
+                ("C", 4, Some("B")),
+                ("Self", 6, Some("Self")),
+            ],
+            8,
+        );
+
+        assert!(
+            tree.issues()
+                == [
+                    SectionIssue::Cycle {
+                        sections: vec![0, 1, 2],
+                    },
+                    SectionIssue::Cycle { sections: vec![3] },
+                ]
+        );
+        assert!(tree.get(0).unwrap().parent().is_none());
+        assert!(tree.get(2).unwrap().parent().unwrap().title() == "B");
+        assert!(tree.get(3).unwrap().parent().is_none());
+        assert!(ranges(&tree) == [0..6, 2..6, 4..6, 6..8]);
+    }
+
+    #[test]
+    fn test_starts_past_the_end_are_clamped() {
+        let tree = SectionTree::from_entries(&[("Intro", 0, None), ("Missing", 99, None)], 3);
+
+        assert!(ranges(&tree) == [0..3, 3..3]);
+        assert!(tree.section_at(2).unwrap().title() == "Intro");
+    }
+}
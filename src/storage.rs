@@ -0,0 +1,202 @@
+use std::{
+    borrow::Cow,
+    io::{self, ErrorKind, Read, Seek, SeekFrom},
+    sync::Mutex,
+};
+
+/// random-access byte source a [`crate::BbfReader`] reads from
+///
+/// storages that hold the whole file contiguously in memory (mmaps, byte buffers) should return
+/// it from [`Storage::as_slice`], which lets the reader hand out zero-copy slices. everything else
+/// only needs positioned reads; the reader then buffers the index region once at open time and
+/// reads asset data into owned buffers on demand.
+pub trait Storage: Send + Sync {
+    /// returns the total size of the source in bytes
+    fn len(&self) -> u64;
+
+    /// checks whether the source is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// reads exactly `len` bytes starting at `offset`
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - byte offset to start reading at
+    /// * `len` - number of bytes to read
+    ///
+    /// # Returns
+    ///
+    /// the bytes, borrowed from the storage when possible
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the range lies outside the source or the read fails
+    fn read_at(&self, offset: u64, len: usize) -> io::Result<Cow<'_, [u8]>>;
+
+    /// returns the whole source as one slice, if it's held contiguously in memory
+    fn as_slice(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+/// borrows `len` bytes at `offset` from an in-memory slice
+fn slice_at(data: &[u8], offset: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+    usize::try_from(offset)
+        .ok()
+        .and_then(|start| Some(start..start.checked_add(len)?))
+        .and_then(|range| data.get(range))
+        .map(Cow::Borrowed)
+        .ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("read of {len} bytes at offset {offset} exceeds storage size"),
+            )
+        })
+}
+
+impl Storage for memmap2::Mmap {
+    fn len(&self) -> u64 {
+        <[u8]>::len(self) as u64
+    }
+
+    fn read_at(&self, offset: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        slice_at(self, offset, len)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl Storage for Vec<u8> {
+    fn len(&self) -> u64 {
+        <[u8]>::len(self) as u64
+    }
+
+    fn read_at(&self, offset: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        slice_at(self, offset, len)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+impl Storage for &[u8] {
+    fn len(&self) -> u64 {
+        <[u8]>::len(self) as u64
+    }
+
+    fn read_at(&self, offset: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        slice_at(self, offset, len)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+}
+
+/// storage backed by any seekable reader
+///
+/// serves positioned reads by seeking then reading under a lock, so it works for plain files on
+/// filesystems that don't support mmap, network-backed readers, or anything else implementing
+/// [`Read`] + [`Seek`]. every read returns an owned buffer.
+#[derive(Debug)]
+pub struct SeekStorage<R> {
+    /// the wrapped reader, locked for each seek + read pair
+    inner: Mutex<R>,
+    /// total size, measured once at construction
+    len: u64,
+}
+
+impl<R: Read + Seek> SeekStorage<R> {
+    /// wraps a seekable reader
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - the reader to wrap
+    ///
+    /// # Returns
+    ///
+    /// the storage, with its size measured by seeking to the end
+    ///
+    /// # Errors
+    ///
+    /// returns an error if seeking fails
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let len = inner.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            inner: Mutex::new(inner),
+            len,
+        })
+    }
+
+    /// unwraps the storage, returning the inner reader
+    ///
+    /// # Returns
+    ///
+    /// the wrapped reader, positioned wherever the last read left it
+    pub fn into_inner(self) -> R {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl<R: Read + Seek + Send> Storage for SeekStorage<R> {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn read_at(&self, offset: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        let in_bounds = offset
+            .checked_add(len as u64)
+            .is_some_and(|end| end <= self.len);
+        if !in_bounds {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("read of {len} bytes at offset {offset} exceeds storage size"),
+            ));
+        }
+
+        let mut inner = self
+            .inner
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        inner.seek(SeekFrom::Start(offset))?;
+
+        let mut buf = vec![0u8; len];
+        inner.read_exact(&mut buf)?;
+        Ok(Cow::Owned(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, assert2::check as assert, std::io::Cursor};
+
+    #[test]
+    fn test_slice_storage_borrows() {
+        let data = vec![1u8, 2, 3, 4, 5];
+        let read = data.read_at(1, 3).unwrap();
+        assert!(matches!(read, Cow::Borrowed(_)));
+        assert!(*read == [2, 3, 4]);
+        assert!(data.read_at(3, 3).is_err());
+        assert!(data.read_at(u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_seek_storage_reads_owned() {
+        let storage = SeekStorage::new(Cursor::new(vec![1u8, 2, 3, 4, 5])).unwrap();
+        assert!(storage.len() == 5);
+        assert!(storage.as_slice().is_none());
+
+        let read = storage.read_at(2, 3).unwrap();
+        assert!(matches!(read, Cow::Owned(_)));
+        assert!(*read == [3, 4, 5]);
+        assert!(storage.read_at(4, 2).is_err());
+    }
+}
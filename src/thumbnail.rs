@@ -0,0 +1,153 @@
+use crate::{ExpansionRecord, format::THUMBNAIL_EXPANSION_KIND};
+
+/// thumbnail expansion record
+///
+/// locates the asset holding the book's cover preview. the asset itself carries
+/// [`crate::format::BBF_ASSET_THUMBNAIL_FLAG`] and isn't referenced by any page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Thumbnail {
+    /// index of the thumbnail in the asset table
+    pub asset_index: u64,
+    /// thumbnail width in pixels (0 if unknown)
+    pub width: u32,
+    /// thumbnail height in pixels (0 if unknown)
+    pub height: u32,
+}
+
+impl ExpansionRecord for Thumbnail {
+    const KIND: u64 = THUMBNAIL_EXPANSION_KIND;
+    const VERSION: u64 = 1;
+
+    fn to_payload(&self) -> [u64; 8] {
+        [
+            self.asset_index,
+            u64::from(self.width),
+            u64::from(self.height),
+            0,
+            0,
+            0,
+            0,
+            0,
+        ]
+    }
+
+    fn from_payload(version: u64, payload: [u64; 8]) -> Option<Self> {
+        if version != 1 {
+            return None;
+        }
+
+        let [asset_index, width, height, ..] = payload;
+
+        Some(Self {
+            asset_index,
+            width: u32::try_from(width).ok()?,
+            height: u32::try_from(height).ok()?,
+        })
+    }
+}
+
+/// renders a thumbnail from full-size image data
+///
+/// decodes the image, scales it down to fit within a `max_dimension` square (preserving aspect
+/// ratio, never upscaling) and encodes the result as a jpeg.
+///
+/// # Arguments
+///
+/// * `source` - encoded image data of any format the `image` crate can decode
+/// * `max_dimension` - the bounding box size in pixels
+///
+/// # Returns
+///
+/// the encoded jpeg along with its width and height
+///
+/// # Errors
+///
+/// returns an error if the source can't be decoded or the thumbnail can't be encoded
+#[cfg(feature = "thumbnail")]
+pub(crate) fn render(source: &[u8], max_dimension: u32) -> crate::Result<(Vec<u8>, u32, u32)> {
+    use image::{ImageReader, codecs::jpeg::JpegEncoder};
+
+    let to_error = |e: image::ImageError| crate::BbfError::Thumbnail {
+        reason: e.to_string(),
+    };
+
+    let image = ImageReader::new(std::io::Cursor::new(source))
+        .with_guessed_format()?
+        .decode()
+        .map_err(to_error)?;
+
+    let max_dimension = max_dimension.max(1);
+    let image = if image.width() > max_dimension || image.height() > max_dimension {
+        image.thumbnail(max_dimension, max_dimension)
+    } else {
+        image
+    };
+
+    let rgb = image.to_rgb8();
+    let mut encoded = Vec::new();
+    JpegEncoder::new_with_quality(&mut encoded, 85)
+        .encode_image(&rgb)
+        .map_err(to_error)?;
+
+    Ok((encoded, rgb.width(), rgb.height()))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {super::*, assert2::check as assert};
+
+    #[test]
+    fn test_thumbnail_record_roundtrip() {
+        let record = Thumbnail {
+            asset_index: 7,
+            width: 180,
+            height: 256,
+        };
+
+        let exp = record.to_expansion();
+        assert!(exp.kind() == THUMBNAIL_EXPANSION_KIND);
+        assert!(exp.is_registered());
+        assert!(!exp.is_required());
+        assert!(Thumbnail::from_expansion(&exp) == Some(record));
+    }
+
+    #[test]
+    fn test_thumbnail_record_rejects_unknown_version() {
+        assert!(Thumbnail::from_payload(2, [0; 8]).is_none());
+    }
+
+    #[cfg(feature = "thumbnail")]
+    #[test]
+    fn test_render_downscales_preserving_aspect_ratio() {
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgb8(1000, 500)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let (data, width, height) = render(&png, 256).unwrap();
+        assert!(width == 256);
+        assert!(height == 128);
+        assert!(crate::types::MediaType::from_bytes(&data) == crate::types::MediaType::Jpg);
+    }
+
+    #[cfg(feature = "thumbnail")]
+    #[test]
+    fn test_render_never_upscales() {
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgb8(64, 32)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let (_, width, height) = render(&png, 256).unwrap();
+        assert!(width == 64);
+        assert!(height == 32);
+    }
+
+    #[cfg(feature = "thumbnail")]
+    #[test]
+    fn test_render_rejects_garbage() {
+        let result = render(b"definitely not an image", 256);
+        assert!(matches!(result, Err(crate::BbfError::Thumbnail { .. })));
+    }
+}
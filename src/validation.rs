@@ -0,0 +1,849 @@
+use {
+    crate::{BbfReader, Storage, format::BBF_ASSET_THUMBNAIL_FLAG, types::*},
+    hashbrown::HashSet,
+    std::fmt,
+};
+
+/// how serious a validation finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// the file is readable but doesn't strictly follow the spec
+    Warning,
+    /// the file references data that doesn't exist or is inconsistent
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// a single structural problem found by [`BbfReader::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationIssue {
+    /// a field reserved for future use holds a nonzero value
+    ReservedFieldNonZero {
+        /// which structure and field, e.g. "header reserved" or "asset 3 reserved"
+        location: String,
+    },
+    /// a table extends past the end of the file
+    TableOutOfBounds {
+        /// the table name
+        table: &'static str,
+    },
+    /// an asset's data extends past the end of the file
+    AssetOutOfBounds {
+        /// the asset index
+        asset: u64,
+    },
+    /// two assets' data ranges overlap
+    OverlappingAssets {
+        /// the asset that starts first
+        first: u64,
+        /// the asset that starts inside it
+        second: u64,
+    },
+    /// a page references an asset that doesn't exist
+    PageAssetOutOfRange {
+        /// the page index
+        page: u64,
+        /// the referenced asset index
+        asset_index: u64,
+    },
+    /// a section starts beyond the last page
+    SectionStartOutOfRange {
+        /// the section index
+        section: u64,
+        /// the section's start page index
+        start: u64,
+    },
+    /// a string offset doesn't point at a valid string in the pool
+    DanglingStringOffset {
+        /// the table holding the offset
+        table: &'static str,
+        /// the entry index within the table
+        index: u64,
+        /// the offending offset
+        offset: u64,
+    },
+    /// a section starts before the section preceding it in the table
+    UnsortedSections {
+        /// the index of the out-of-order section
+        section: u64,
+    },
+    /// a parent offset doesn't name any section title
+    UnknownParent {
+        /// the table holding the parent offset
+        table: &'static str,
+        /// the entry index within the table
+        index: u64,
+    },
+    /// an asset's data starts inside the header
+    AssetOverlapsHeader {
+        /// the asset index
+        asset: u64,
+    },
+    /// an asset's data extends into the index region
+    AssetOverlapsIndex {
+        /// the asset index
+        asset: u64,
+    },
+    /// a table or the string pool lies outside the index region
+    TableOutsideIndex {
+        /// the table name
+        table: &'static str,
+    },
+    /// two tables share bytes
+    OverlappingTables {
+        /// the table that starts first
+        first: &'static str,
+        /// the table that starts inside it
+        second: &'static str,
+    },
+    /// the header declares a size other than the one this implementation uses
+    UnexpectedHeaderLength {
+        /// the declared header length
+        found: u16,
+    },
+    /// an asset's media type byte isn't a known media type
+    UnknownMediaType {
+        /// the asset index
+        asset: u64,
+        /// the raw media type byte
+        value: u8,
+    },
+    /// an asset isn't referenced by any page and isn't a thumbnail
+    UnreferencedAsset {
+        /// the asset index
+        asset: u64,
+    },
+    /// bytes follow the footer
+    TrailingData {
+        /// the number of trailing bytes
+        bytes: u64,
+    },
+}
+
+impl ValidationIssue {
+    /// returns how serious this finding is
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::ReservedFieldNonZero { .. }
+            | Self::UnsortedSections { .. }
+            | Self::UnexpectedHeaderLength { .. }
+            | Self::UnknownMediaType { .. }
+            | Self::UnreferencedAsset { .. }
+            | Self::TrailingData { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReservedFieldNonZero { location } => {
+                write!(f, "nonzero {location}")
+            }
+            Self::TableOutOfBounds { table } => write!(f, "{table} table is out of bounds"),
+            Self::AssetOutOfBounds { asset } => {
+                write!(f, "asset {asset} data is out of bounds")
+            }
+            Self::OverlappingAssets { first, second } => {
+                write!(f, "asset {second} overlaps asset {first}")
+            }
+            Self::PageAssetOutOfRange { page, asset_index } => {
+                write!(f, "page {page} references missing asset {asset_index}")
+            }
+            Self::SectionStartOutOfRange { section, start } => {
+                write!(
+                    f,
+                    "section {section} starts at page {start}, past the last page"
+                )
+            }
+            Self::DanglingStringOffset {
+                table,
+                index,
+                offset,
+            } => write!(
+                f,
+                "{table} entry {index} has an invalid string offset {offset}"
+            ),
+            Self::UnsortedSections { section } => {
+                write!(f, "section {section} starts before the previous section")
+            }
+            Self::UnknownParent { table, index } => {
+                write!(
+                    f,
+                    "{table} entry {index} names a parent section that doesn't exist"
+                )
+            }
+            Self::AssetOverlapsHeader { asset } => {
+                write!(f, "asset {asset} data overlaps the header")
+            }
+            Self::AssetOverlapsIndex { asset } => {
+                write!(f, "asset {asset} data overlaps the index region")
+            }
+            Self::TableOutsideIndex { table } => {
+                write!(f, "{table} table lies outside the index region")
+            }
+            Self::OverlappingTables { first, second } => {
+                write!(f, "{second} table overlaps {first} table")
+            }
+            Self::UnexpectedHeaderLength { found } => {
+                write!(f, "header declares a length of {found} bytes")
+            }
+            Self::UnknownMediaType { asset, value } => {
+                write!(f, "asset {asset} has unknown media type {value:#04x}")
+            }
+            Self::UnreferencedAsset { asset } => {
+                write!(f, "asset {asset} isn't used by any page")
+            }
+            Self::TrailingData { bytes } => {
+                write!(f, "{bytes} bytes of trailing data after the footer")
+            }
+        }
+    }
+}
+
+/// the findings of a structural validation pass
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// every issue found, in discovery order
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// returns every issue found
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
+    /// checks whether no issues were found
+    pub const fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// checks whether any error-severity issues were found
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// iterates over the error-severity issues
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Error)
+    }
+
+    /// iterates over the warning-severity issues
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Warning)
+    }
+
+    /// records an issue
+    pub(crate) fn push(&mut self, issue: ValidationIssue) {
+        self.issues.push(issue);
+    }
+}
+
+/// runs every structural check against an open reader
+///
+/// # Arguments
+///
+/// * `reader` - the reader to validate
+///
+/// # Returns
+///
+/// the collected findings
+pub(crate) fn validate<S: Storage>(reader: &BbfReader<S>) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    check_header_footer(reader, &mut report);
+
+    let assets = table_or_report(reader.assets(), "asset", &mut report);
+    let pages = table_or_report(reader.pages(), "page", &mut report);
+    let sections = table_or_report(reader.sections(), "section", &mut report);
+    let metadata = table_or_report(reader.metadata(), "metadata", &mut report);
+    table_or_report(reader.expansions(), "expansion", &mut report);
+
+    check_assets(reader, assets, &mut report);
+
+    for (i, page) in pages.iter().enumerate() {
+        if page.reserved != [0; 4] {
+            report.push(ValidationIssue::ReservedFieldNonZero {
+                location: format!("page {i} reserved"),
+            });
+        }
+
+        if page.asset_index >= assets.len() as u64 {
+            report.push(ValidationIssue::PageAssetOutOfRange {
+                page: i as u64,
+                asset_index: page.asset_index,
+            });
+        }
+    }
+
+    let mut previous_start = 0;
+    for (i, section) in sections.iter().enumerate() {
+        let start = section.section_start_index;
+
+        if section.reserved != [0; 8] {
+            report.push(ValidationIssue::ReservedFieldNonZero {
+                location: format!("section {i} reserved"),
+            });
+        }
+
+        if start >= reader.page_count() {
+            report.push(ValidationIssue::SectionStartOutOfRange {
+                section: i as u64,
+                start,
+            });
+        }
+
+        if start < previous_start {
+            report.push(ValidationIssue::UnsortedSections { section: i as u64 });
+        }
+        previous_start = start;
+
+        check_string(
+            reader,
+            "section",
+            i,
+            section.section_title_offset,
+            &mut report,
+        );
+        check_optional_string(
+            reader,
+            "section",
+            i,
+            section.section_parent_offset,
+            &mut report,
+        );
+    }
+
+    for (i, meta) in metadata.iter().enumerate() {
+        if meta.reserved != [0; 8] {
+            report.push(ValidationIssue::ReservedFieldNonZero {
+                location: format!("metadata {i} reserved"),
+            });
+        }
+
+        check_string(reader, "metadata", i, meta.key_offset, &mut report);
+        check_string(reader, "metadata", i, meta.value_offset, &mut report);
+        check_optional_string(reader, "metadata", i, meta.parent_offset, &mut report);
+    }
+
+    report
+}
+
+/// runs [`validate`] plus the cross-reference and layout checks
+///
+/// # Arguments
+///
+/// * `reader` - the reader to validate
+///
+/// # Returns
+///
+/// the collected findings
+pub(crate) fn validate_deep<S: Storage>(reader: &BbfReader<S>) -> ValidationReport {
+    let mut report = validate(reader);
+
+    let assets = reader.assets().unwrap_or_default();
+    let pages = reader.pages().unwrap_or_default();
+    let sections = reader.sections().unwrap_or_default();
+    let metadata = reader.metadata().unwrap_or_default();
+    let expansions = reader.expansions().unwrap_or_default();
+
+    check_layout(reader, &mut report);
+
+    let header_len = reader.header().header_len;
+    if usize::from(header_len) != std::mem::size_of::<BbfHeader>() {
+        report.push(ValidationIssue::UnexpectedHeaderLength { found: header_len });
+    }
+
+    let index_start = reader.footer().asset_offset;
+    let mut referenced = vec![false; assets.len()];
+    for page in pages {
+        if let Some(used) = usize::try_from(page.asset_index)
+            .ok()
+            .and_then(|i| referenced.get_mut(i))
+        {
+            *used = true;
+        }
+    }
+
+    for (i, asset) in assets.iter().enumerate() {
+        if asset.file_offset < u64::from(header_len) {
+            report.push(ValidationIssue::AssetOverlapsHeader { asset: i as u64 });
+        }
+
+        if asset
+            .file_offset
+            .checked_add(asset.file_size)
+            .is_none_or(|end| end > index_start)
+        {
+            report.push(ValidationIssue::AssetOverlapsIndex { asset: i as u64 });
+        }
+
+        if MediaType::from(asset.media_type) as u8 != asset.media_type {
+            report.push(ValidationIssue::UnknownMediaType {
+                asset: i as u64,
+                value: asset.media_type,
+            });
+        }
+
+        if !referenced[i] && asset.flags & BBF_ASSET_THUMBNAIL_FLAG == 0 {
+            report.push(ValidationIssue::UnreferencedAsset { asset: i as u64 });
+        }
+    }
+
+    let titles: HashSet<&str> = sections
+        .iter()
+        .filter_map(|section| reader.get_string(section.section_title_offset).ok())
+        .collect();
+
+    let parents = sections
+        .iter()
+        .map(|section| ("section", section.section_parent_offset))
+        .enumerate()
+        .chain(
+            metadata
+                .iter()
+                .map(|meta| ("metadata", meta.parent_offset))
+                .enumerate(),
+        );
+
+    for (index, (table, offset)) in parents {
+        if offset == u64::MAX {
+            continue;
+        }
+
+        if let Ok(parent) = reader.get_string(offset)
+            && !titles.contains(parent)
+        {
+            report.push(ValidationIssue::UnknownParent {
+                table,
+                index: index as u64,
+            });
+        }
+    }
+
+    for (i, exp) in expansions.iter().enumerate() {
+        if exp.reserved != [0; 44] {
+            report.push(ValidationIssue::ReservedFieldNonZero {
+                location: format!("expansion {i} reserved"),
+            });
+        }
+    }
+
+    report
+}
+
+/// checks that every table lies inside the index region without overlapping another, and that
+/// nothing follows the footer
+fn check_layout<S: Storage>(reader: &BbfReader<S>, report: &mut ValidationReport) {
+    let footer = reader.footer();
+    let index_start = footer.asset_offset;
+    let index_end = reader.header().footer_offset;
+
+    let table = |name, offset: u64, count: u64, size: usize| {
+        let end = count
+            .checked_mul(size as u64)
+            .and_then(|len| offset.checked_add(len));
+        (name, offset, end)
+    };
+
+    let tables = [
+        table(
+            "asset",
+            footer.asset_offset,
+            footer.asset_count,
+            std::mem::size_of::<AssetEntry>(),
+        ),
+        table(
+            "page",
+            footer.page_offset,
+            footer.page_count,
+            std::mem::size_of::<PageEntry>(),
+        ),
+        table(
+            "section",
+            footer.section_offset,
+            footer.section_count,
+            std::mem::size_of::<Section>(),
+        ),
+        table(
+            "metadata",
+            footer.meta_offset,
+            footer.meta_count,
+            std::mem::size_of::<Metadata>(),
+        ),
+        table(
+            "expansion",
+            footer.expansion_offset,
+            footer.expansion_count,
+            std::mem::size_of::<Expansion>(),
+        ),
+        table(
+            "string pool",
+            footer.string_pool_offset,
+            footer.string_pool_size,
+            1,
+        ),
+    ];
+
+    let mut ranges = Vec::with_capacity(tables.len());
+    for (name, start, end) in tables {
+        match end {
+            Some(end) if end == start => {}
+            Some(end) if start >= index_start && end <= index_end => {
+                ranges.push((start, end, name))
+            }
+            _ => report.push(ValidationIssue::TableOutsideIndex { table: name }),
+        }
+    }
+
+    ranges.sort_unstable();
+    for pair in ranges.windows(2) {
+        let [(_, first_end, first), (second_start, _, second)] = [pair[0], pair[1]];
+        if second_start < first_end {
+            report.push(ValidationIssue::OverlappingTables { first, second });
+        }
+    }
+
+    let footer_end = index_end.saturating_add(std::mem::size_of::<BbfFooter>() as u64);
+    let file_len = reader.file_len();
+    if file_len > footer_end {
+        report.push(ValidationIssue::TrailingData {
+            bytes: file_len.saturating_sub(footer_end),
+        });
+    }
+}
+
+/// checks the reserved fields of the header and footer
+fn check_header_footer<S: Storage>(reader: &BbfReader<S>, report: &mut ValidationReport) {
+    let header = reader.header();
+    let footer = reader.footer();
+
+    let checks = [
+        (header.reserved_extra != 0, "header reserved_extra field"),
+        (header.reserved != [0; 40], "header reserved fields"),
+        (footer.flags != 0, "footer flags"),
+        (footer.padding != [0; 3], "footer padding"),
+        (footer.reserved != [0; 144], "footer reserved fields"),
+    ];
+
+    for (failed, location) in checks {
+        if failed {
+            report.push(ValidationIssue::ReservedFieldNonZero {
+                location: location.to_string(),
+            });
+        }
+    }
+}
+
+/// checks asset reserved fields, bounds, and overlaps
+fn check_assets<S: Storage>(
+    reader: &BbfReader<S>,
+    assets: &[AssetEntry],
+    report: &mut ValidationReport,
+) {
+    let file_len = reader.file_len();
+    let mut ranges = Vec::with_capacity(assets.len());
+
+    for (i, asset) in assets.iter().enumerate() {
+        if asset.reserved_value != 0 || asset.reserved != [0; 9] {
+            report.push(ValidationIssue::ReservedFieldNonZero {
+                location: format!("asset {i} reserved"),
+            });
+        }
+
+        match asset.file_offset.checked_add(asset.file_size) {
+            Some(end) if end <= file_len => ranges.push((asset.file_offset, end, i as u64)),
+            _ => report.push(ValidationIssue::AssetOutOfBounds { asset: i as u64 }),
+        }
+    }
+
+    ranges.sort_unstable();
+    for pair in ranges.windows(2) {
+        let [(_, first_end, first), (second_start, _, second)] = [pair[0], pair[1]];
+        if second_start < first_end {
+            report.push(ValidationIssue::OverlappingAssets { first, second });
+        }
+    }
+}
+
+/// returns a table, recording an issue and substituting an empty table if it's out of bounds
+fn table_or_report<'a, T>(
+    table: crate::Result<&'a [T]>,
+    name: &'static str,
+    report: &mut ValidationReport,
+) -> &'a [T] {
+    table.unwrap_or_else(|_| {
+        report.push(ValidationIssue::TableOutOfBounds { table: name });
+        &[]
+    })
+}
+
+/// records an issue if `offset` isn't a valid string
+fn check_string<S: Storage>(
+    reader: &BbfReader<S>,
+    table: &'static str,
+    index: usize,
+    offset: u64,
+    report: &mut ValidationReport,
+) {
+    if reader.get_string(offset).is_err() {
+        report.push(ValidationIssue::DanglingStringOffset {
+            table,
+            index: index as u64,
+            offset,
+        });
+    }
+}
+
+/// like [`check_string`], but `u64::MAX` (no string) is allowed
+fn check_optional_string<S: Storage>(
+    reader: &BbfReader<S>,
+    table: &'static str,
+    index: usize,
+    offset: u64,
+    report: &mut ValidationReport,
+) {
+    if offset != u64::MAX {
+        check_string(reader, table, index, offset, report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(unused, clippy::missing_panics_doc)]
+    use {
+        super::*,
+        crate::{BbfBuilder, types::*},
+        assert2::check as assert,
+        tempfile::NamedTempFile,
+    };
+
+    fn build_book() -> NamedTempFile {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .add_page_bytes(&[2u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_section("One", 0, None);
+        builder.add_section("Two", 1, Some("One"));
+        builder.add_metadata("Title", "Book", None);
+        builder.finalize().unwrap();
+        temp_output
+    }
+
+    #[test]
+    fn test_valid_book_is_clean() {
+        let book = build_book();
+        let reader = BbfReader::open(book.path()).unwrap();
+        let report = reader.validate();
+        assert!(report.is_clean(), "{:?}", report.issues());
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn test_severity_ordering() {
+        assert!(Severity::Error > Severity::Warning);
+        assert!(ValidationIssue::UnsortedSections { section: 1 }.severity() == Severity::Warning);
+        assert!(ValidationIssue::AssetOutOfBounds { asset: 0 }.severity() == Severity::Error);
+    }
+
+    #[test]
+    fn test_report_partitions_by_severity() {
+        let mut report = ValidationReport::default();
+        report.push(ValidationIssue::UnsortedSections { section: 1 });
+        report.push(ValidationIssue::PageAssetOutOfRange {
+            page: 0,
+            asset_index: 9,
+        });
+
+        assert!(!report.is_clean());
+        assert!(report.has_errors());
+        assert!(report.errors().count() == 1);
+        assert!(report.warnings().count() == 1);
+        assert!(report.issues()[1].to_string() == "page 0 references missing asset 9");
+    }
+
+    #[test]
+    fn test_detects_bad_page_and_section_references() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_section("Late", 5, None);
+        builder.add_section("Early", 0, None);
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(temp_output.path()).unwrap();
+        let issues = reader.validate().issues().to_vec();
+        assert!(issues.contains(&ValidationIssue::SectionStartOutOfRange {
+            section: 0,
+            start: 5
+        }));
+        assert!(issues.contains(&ValidationIssue::UnsortedSections { section: 1 }));
+    }
+
+    /// patches `bytes` into the file at `offset`
+    #[macroni_n_cheese::mathinator2000]
+    fn patch(path: &std::path::Path, offset: usize, bytes: &[u8]) {
+        let mut data = std::fs::read(path).unwrap();
+        data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        std::fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    #[macroni_n_cheese::mathinator2000]
+    fn test_detects_corrupted_references() {
+        let book = build_book();
+        let reader = BbfReader::open(book.path()).unwrap();
+        let footer = *reader.footer();
+        drop(reader);
+
+        let page_offset = footer.page_offset as usize;
+        let metadata_offset = footer.meta_offset as usize;
+        patch(book.path(), page_offset, &9u64.to_le_bytes());
+        patch(book.path(), page_offset + 12, &[1]);
+        patch(book.path(), metadata_offset + 8, &u32::MAX.to_le_bytes());
+        patch(book.path(), 24, &[1]);
+
+        let reader = BbfReader::open(book.path()).unwrap();
+        let report = reader.validate();
+        let issues = report.issues();
+
+        assert!(issues.contains(&ValidationIssue::PageAssetOutOfRange {
+            page: 0,
+            asset_index: 9
+        }));
+        assert!(issues.contains(&ValidationIssue::ReservedFieldNonZero {
+            location: "page 0 reserved".into()
+        }));
+        assert!(issues.contains(&ValidationIssue::ReservedFieldNonZero {
+            location: "header reserved fields".into()
+        }));
+        assert!(issues.iter().any(|issue| matches!(
+            issue,
+            ValidationIssue::DanglingStringOffset {
+                table: "metadata",
+                index: 0,
+                ..
+            }
+        )));
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn test_detects_overlapping_assets() {
+        let book = build_book();
+        let reader = BbfReader::open(book.path()).unwrap();
+        let asset_offset = reader.footer().asset_offset as usize;
+        let first_start = reader.assets().unwrap()[0].file_offset;
+        drop(reader);
+
+        patch(book.path(), asset_offset + 48, &first_start.to_le_bytes());
+
+        let reader = BbfReader::open(book.path()).unwrap();
+        assert!(
+            reader
+                .validate()
+                .issues()
+                .contains(&ValidationIssue::OverlappingAssets {
+                    first: 0,
+                    second: 1
+                })
+        );
+    }
+
+    #[test]
+    fn test_valid_book_is_deep_clean() {
+        let book = build_book();
+        let reader = BbfReader::open(book.path()).unwrap();
+        let report = reader.validate_deep();
+        assert!(report.is_clean(), "{:?}", report.issues());
+    }
+
+    #[test]
+    fn test_deep_detects_unknown_parent() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder.add_section("One", 0, Some("Missing"));
+        builder.add_metadata("Title", "Book", Some("Also Missing"));
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.validate().is_clean());
+
+        let issues = reader.validate_deep().issues().to_vec();
+        assert!(issues.contains(&ValidationIssue::UnknownParent {
+            table: "section",
+            index: 0
+        }));
+        assert!(issues.contains(&ValidationIssue::UnknownParent {
+            table: "metadata",
+            index: 0
+        }));
+    }
+
+    #[test]
+    #[macroni_n_cheese::mathinator2000]
+    fn test_deep_detects_layout_problems() {
+        let book = build_book();
+        let reader = BbfReader::open(book.path()).unwrap();
+        let footer = *reader.footer();
+        drop(reader);
+
+        let asset_offset = footer.asset_offset as usize;
+        patch(book.path(), asset_offset + 24, &100_000u64.to_le_bytes());
+        patch(
+            book.path(),
+            footer.page_offset as usize + 16,
+            &0u64.to_le_bytes(),
+        );
+
+        let mut data = std::fs::read(book.path()).unwrap();
+        data.extend_from_slice(&[0u8; 10]);
+        std::fs::write(book.path(), data).unwrap();
+
+        let reader = BbfReader::open(book.path()).unwrap();
+        let issues = reader.validate_deep().issues().to_vec();
+        assert!(issues.contains(&ValidationIssue::AssetOverlapsIndex { asset: 0 }));
+        assert!(issues.contains(&ValidationIssue::UnreferencedAsset { asset: 1 }));
+        assert!(issues.contains(&ValidationIssue::TrailingData { bytes: 10 }));
+    }
+
+    #[test]
+    fn test_deep_ignores_thumbnail_asset() {
+        let temp_output = NamedTempFile::new().unwrap();
+        let mut builder = BbfBuilder::with_defaults(temp_output.path()).unwrap();
+        builder
+            .add_page_bytes(&[1u8; 100], MediaType::Png, 0, 0)
+            .unwrap();
+        builder
+            .set_thumbnail(&[2u8; 10], MediaType::Jpg, 1, 1)
+            .unwrap();
+        builder.finalize().unwrap();
+
+        let reader = BbfReader::open(temp_output.path()).unwrap();
+        assert!(reader.validate_deep().is_clean());
+    }
+}